@@ -25,17 +25,18 @@ mod storage;
 mod tests;
 mod uploader;
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{Context, Result};
-use bonsai_sdk::alpha_async::get_client_from_parts;
+pub use bonsai_sdk::groth16::ControlId;
+use bonsai_sdk::{alpha::Client as BonsaiClient, alpha_async::get_client_from_parts};
 pub use client_config::EthersClientConfig;
 use downloader::{
     proxy_callback_proof_processor::ProxyCallbackProofRequestProcessor,
     proxy_callback_proof_request_stream::ProxyCallbackProofRequestStream,
 };
 use ethers::core::types::Address;
-use storage::{in_memory::InMemoryStorage, Storage};
+use storage::{in_memory::InMemoryStorage, sqlite::SqliteStorage, Storage};
 use tokio::sync::Notify;
 use tracing::info;
 pub use uploader::completed_proofs::snark::tokenize_snark_receipt;
@@ -55,6 +56,10 @@ pub struct Relayer {
     pub rest_api: bool,
     /// Toggle for generating real or fake receipts.
     pub dev_mode: bool,
+    /// Control ID that the Groth16 verifier of the Bonsai Relay contract was
+    /// deployed with. SNARK receipts are checked against it before they are
+    /// sent to Ethereum.
+    pub control_id: ControlId,
     /// Port serving the relayer REST API.
     pub rest_api_port: String,
     /// Bonsai API URL.
@@ -63,6 +68,10 @@ pub struct Relayer {
     pub bonsai_api_key: String,
     /// The Ethereum address of the deployed Bonsai Relay contract.
     pub relay_contract_address: Address,
+    /// Path to a SQLite database that persists proof requests across restarts.
+    ///
+    /// Proof requests are only kept in memory if this is [None].
+    pub storage_path: Option<PathBuf>,
 }
 
 impl Relayer {
@@ -85,8 +94,28 @@ impl Relayer {
         .await
         .context("Failed to create Bonsai client.")?;
 
-        let storage = InMemoryStorage::new();
+        match self.storage_path.clone() {
+            Some(path) => {
+                let storage = SqliteStorage::open(&path).with_context(|| {
+                    format!("Failed to open relay storage at {}", path.display())
+                })?;
+                info!(path = %path.display(), "Using persistent relay storage");
+                self.run_with_storage(client_config, bonsai_client, storage)
+                    .await
+            }
+            None => {
+                self.run_with_storage(client_config, bonsai_client, InMemoryStorage::new())
+                    .await
+            }
+        }
+    }
 
+    async fn run_with_storage<S: Storage + Sync + Send + Clone + 'static>(
+        self,
+        client_config: EthersClientConfig,
+        bonsai_client: BonsaiClient,
+        storage: S,
+    ) -> Result<()> {
         // Setup Downloader
         let new_pending_proof_request_notifier = Arc::new(Notify::new());
         let proxy_callback_proof_request_processor = ProxyCallbackProofRequestProcessor::new(
@@ -118,6 +147,7 @@ impl Relayer {
         let uploader_complete_proof_manager = BonsaiCompleteProofManager::new(
             bonsai_client.clone(),
             self.dev_mode,
+            self.control_id,
             storage.clone(),
             new_complete_proof_notifier.clone(),
             send_batch_notifier.clone(),
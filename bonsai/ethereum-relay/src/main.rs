@@ -12,10 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use anyhow::Result;
-use bonsai_ethereum_relay::{EthersClientConfig, Relayer};
+use bonsai_ethereum_relay::{ControlId, EthersClientConfig, Relayer};
 use clap::Parser;
 use ethers::core::types::Address;
 
@@ -63,6 +63,26 @@ struct Args {
     /// zkVM program and no proof is generated.
     #[arg(long, env, default_value_t = false)]
     risc0_dev_mode: bool,
+
+    /// Low 128 bits of the control ID that the Groth16 verifier was deployed
+    /// with, in hex. Defaults to the control ID of this release.
+    #[arg(long, env, value_parser = parse_hex_u128, requires = "control_id_1")]
+    control_id_0: Option<u128>,
+
+    /// High 128 bits of the control ID that the Groth16 verifier was
+    /// deployed with, in hex. Defaults to the control ID of this release.
+    #[arg(long, env, value_parser = parse_hex_u128, requires = "control_id_0")]
+    control_id_1: Option<u128>,
+
+    /// Path to a SQLite database that persists proof requests, so that they
+    /// are resumed when the relay restarts. Proof requests are only kept in
+    /// memory if this is not set.
+    #[arg(long, env)]
+    storage_path: Option<PathBuf>,
+}
+
+fn parse_hex_u128(value: &str) -> Result<u128, std::num::ParseIntError> {
+    u128::from_str_radix(value.trim_start_matches("0x"), 16)
 }
 
 #[tokio::main]
@@ -72,10 +92,15 @@ async fn main() -> Result<()> {
     let relayer = Relayer {
         rest_api: args.rest_api,
         dev_mode: args.risc0_dev_mode,
+        control_id: match (args.control_id_0, args.control_id_1) {
+            (Some(id_0), Some(id_1)) => ControlId { id_0, id_1 },
+            _ => ControlId::default(),
+        },
         rest_api_port: args.port,
         bonsai_api_url: args.bonsai_api_url,
         bonsai_api_key: args.bonsai_api_key,
         relay_contract_address: args.contract_address,
+        storage_path: args.storage_path,
     };
 
     const WAIT_DURATION: Duration = Duration::from_secs(5);
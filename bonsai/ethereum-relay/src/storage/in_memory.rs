@@ -153,6 +153,15 @@ impl Storage for InMemoryStorage {
         Ok(hashmap.values().cloned().collect())
     }
 
+    async fn fetch_pending_bonsai_requests(
+        &self,
+        _limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>, Error> {
+        let hashmap = self.pending_proofs.read()?;
+
+        Ok(hashmap.values().cloned().collect())
+    }
+
     async fn fetch_completed_bonsai_requests(
         &self,
         _limit: Option<u64>,
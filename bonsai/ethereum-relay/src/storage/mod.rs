@@ -18,10 +18,11 @@ use bonsai_ethereum_contracts::i_bonsai_relay::CallbackRequestFilter;
 use ethers::types::H256;
 
 pub(crate) mod in_memory;
+pub(crate) mod sqlite;
 
 use bonsai_sdk::alpha::SessionId;
 
-use self::in_memory::InMemoryStorageError;
+use self::{in_memory::InMemoryStorageError, sqlite::SqliteStorageError};
 
 pub(crate) type ProofID = SessionId;
 
@@ -33,6 +34,8 @@ pub(crate) const MAX_PROOF_RETRIES: u64 = 5;
 pub(crate) enum Error {
     #[error("Failed to transition proof request")]
     TransitionProofRequest(#[from] InMemoryStorageError),
+    #[error("SQLite storage error")]
+    Sqlite(#[from] SqliteStorageError),
     #[error("Proof not found")]
     ProofNotFound { id: ProofID },
     // TODO: We lose the underlying error here. We should probably wrap it in a
@@ -113,6 +116,10 @@ pub(crate) trait Storage {
         &self,
         limit: Option<u64>,
     ) -> Result<Vec<ProofRequestInformation>>;
+    async fn fetch_pending_bonsai_requests(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>>;
     async fn fetch_completed_bonsai_requests(
         &self,
         limit: Option<u64>,
@@ -0,0 +1,461 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bonsai_ethereum_contracts::i_bonsai_relay::CallbackRequestFilter;
+use ethers::types::{Address, Bytes};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::storage::{
+    Error, ProofID, ProofRequestInformation, ProofRequestState, Storage, MAX_PROOF_RETRIES,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS proof_requests (
+        id TEXT PRIMARY KEY NOT NULL,
+        state TEXT NOT NULL,
+        retries INTEGER NOT NULL,
+        account BLOB NOT NULL,
+        image_id BLOB NOT NULL,
+        input BLOB NOT NULL,
+        callback_contract BLOB NOT NULL,
+        function_selector BLOB NOT NULL,
+        gas_limit INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS proof_requests_state ON proof_requests (state);
+";
+
+/// A [Storage] backed by a SQLite database, which survives restarts of the
+/// relay.
+///
+/// Every state transition is applied in a single transaction. Proof requests
+/// that complete on chain are removed, as in [super::in_memory::InMemoryStorage].
+#[derive(Debug, Clone)]
+pub(crate) struct SqliteStorage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SqliteStorageError {
+    #[error("Invalid proof state transition_proof_request")]
+    InvalidProofStateTransition {
+        proof_id: ProofID,
+        from_state: ProofRequestState,
+        new_state: ProofRequestState,
+    },
+    #[error("Database error")]
+    Database(#[from] rusqlite::Error),
+    #[error("Invalid proof request state in database: {0}")]
+    InvalidState(String),
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        SqliteStorageError::from(err).into()
+    }
+}
+
+/// The name of a [ProofRequestState] in the database.
+///
+/// [ProofRequestState::CompletedOnchain] is never stored.
+fn state_name(state: ProofRequestState) -> &'static str {
+    match state {
+        ProofRequestState::New => "new",
+        ProofRequestState::Pending => "pending",
+        ProofRequestState::Completed => "completed",
+        ProofRequestState::Failed => "failed",
+        ProofRequestState::PreparingOnchain => "preparing_onchain",
+        ProofRequestState::CompletedOnchain(_) => "completed_onchain",
+    }
+}
+
+fn parse_state(name: &str) -> Result<ProofRequestState, Error> {
+    match name {
+        "new" => Ok(ProofRequestState::New),
+        "pending" => Ok(ProofRequestState::Pending),
+        "completed" => Ok(ProofRequestState::Completed),
+        "failed" => Ok(ProofRequestState::Failed),
+        "preparing_onchain" => Ok(ProofRequestState::PreparingOnchain),
+        _ => Err(SqliteStorageError::InvalidState(name.to_string()))?,
+    }
+}
+
+fn read_proof_request(row: &Row<'_>) -> rusqlite::Result<ProofRequestInformation> {
+    let account: Vec<u8> = row.get("account")?;
+    let image_id: [u8; 32] = row.get("image_id")?;
+    let input: Vec<u8> = row.get("input")?;
+    let callback_contract: Vec<u8> = row.get("callback_contract")?;
+    let function_selector: [u8; 4] = row.get("function_selector")?;
+    let gas_limit: i64 = row.get("gas_limit")?;
+    Ok(ProofRequestInformation {
+        proof_request_id: ProofID::new(row.get("id")?),
+        callback_proof_request_event: CallbackRequestFilter {
+            account: Address::from_slice(&account),
+            image_id,
+            input: Bytes::from(input),
+            callback_contract: Address::from_slice(&callback_contract),
+            function_selector,
+            gas_limit: gas_limit as u64,
+        },
+    })
+}
+
+impl SqliteStorage {
+    /// Open the database at the given path, creating it if it does not exist.
+    pub(crate) fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    fn fetch_requests_in_state(
+        &self,
+        state: ProofRequestState,
+        limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>, Error> {
+        let conn = self.conn.lock()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT * FROM proof_requests WHERE state = ?1 ORDER BY rowid LIMIT ?2",
+        )?;
+        // A negative limit means no limit.
+        let limit = limit.map_or(-1, |limit| limit as i64);
+        let requests = stmt
+            .query_map(params![state_name(state), limit], read_proof_request)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(requests)
+    }
+
+    #[cfg(test)]
+    fn get_proof_retries(&self, proof_id: &ProofID) -> Result<u64, Error> {
+        let retries: Option<i64> = self
+            .conn
+            .lock()?
+            .query_row(
+                "SELECT retries FROM proof_requests WHERE id = ?1",
+                params![proof_id.uuid],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match retries {
+            Some(retries) => Ok(retries as u64),
+            None => Err(Error::ProofNotFound {
+                id: proof_id.clone(),
+            }),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for SqliteStorage {
+    async fn add_new_bonsai_proof_request(
+        &self,
+        proof: ProofRequestInformation,
+    ) -> Result<(), Error> {
+        let event = &proof.callback_proof_request_event;
+        let inserted = self.conn.lock()?.execute(
+            "INSERT OR IGNORE INTO proof_requests (
+                id, state, retries, account, image_id, input, callback_contract,
+                function_selector, gas_limit
+            ) VALUES (?1, ?2, 0, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                proof.proof_request_id.uuid,
+                state_name(ProofRequestState::New),
+                event.account.as_bytes(),
+                event.image_id,
+                event.input.to_vec(),
+                event.callback_contract.as_bytes(),
+                event.function_selector,
+                event.gas_limit as i64,
+            ],
+        )?;
+
+        if inserted == 0 {
+            return Err(Error::ProofAlreadyExists {
+                id: proof.proof_request_id,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_new_bonsai_requests(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>, Error> {
+        self.fetch_requests_in_state(ProofRequestState::New, limit)
+    }
+
+    async fn fetch_pending_bonsai_requests(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>, Error> {
+        self.fetch_requests_in_state(ProofRequestState::Pending, limit)
+    }
+
+    async fn fetch_completed_bonsai_requests(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>, Error> {
+        self.fetch_requests_in_state(ProofRequestState::Completed, limit)
+    }
+
+    async fn fetch_preparing_onchain_proof_requests(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<Vec<ProofRequestInformation>, Error> {
+        self.fetch_requests_in_state(ProofRequestState::PreparingOnchain, limit)
+    }
+
+    async fn get_proof_request_state(&self, proof_id: ProofID) -> Result<ProofRequestState, Error> {
+        let state: Option<String> = self
+            .conn
+            .lock()?
+            .query_row(
+                "SELECT state FROM proof_requests WHERE id = ?1",
+                params![proof_id.uuid],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match state {
+            Some(state) => parse_state(&state),
+            None => Err(Error::ProofNotFound { id: proof_id }),
+        }
+    }
+
+    async fn transition_proof_request(
+        &self,
+        proof_id: ProofID,
+        new_state: ProofRequestState,
+    ) -> Result<(), Error> {
+        let mut conn = self.conn.lock()?;
+        let tx = conn.transaction()?;
+
+        let row: Option<(String, i64)> = tx
+            .query_row(
+                "SELECT state, retries FROM proof_requests WHERE id = ?1",
+                params![proof_id.uuid],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (current_state, mut retries) = match row {
+            Some((state, retries)) => (parse_state(&state)?, retries as u64),
+            None => return Err(Error::ProofNotFound { id: proof_id }),
+        };
+
+        if !current_state.is_valid_state_transition(new_state, retries) {
+            return Err(SqliteStorageError::InvalidProofStateTransition {
+                proof_id,
+                from_state: current_state,
+                new_state,
+            })?;
+        }
+
+        if current_state.should_increment_retries(&new_state) {
+            if retries >= MAX_PROOF_RETRIES {
+                return Err(Error::MaxRetriesExceeded { id: proof_id });
+            }
+            retries += 1;
+        }
+
+        if let ProofRequestState::CompletedOnchain(_) = new_state {
+            // We don't need to keep requests that completed onchain
+            tx.execute(
+                "DELETE FROM proof_requests WHERE id = ?1",
+                params![proof_id.uuid],
+            )?;
+        } else {
+            tx.execute(
+                "UPDATE proof_requests SET state = ?2, retries = ?3 WHERE id = ?1",
+                params![proof_id.uuid, state_name(new_state), retries as i64],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bonsai_ethereum_contracts::i_bonsai_relay::CallbackRequestFilter;
+    use ethers::types::{Address, Bytes, H256};
+    use rstest::*;
+
+    use super::*;
+
+    #[fixture]
+    fn storage() -> SqliteStorage {
+        SqliteStorage::open_in_memory().unwrap()
+    }
+
+    #[fixture]
+    fn proof_request_information(#[default("test")] id: String) -> ProofRequestInformation {
+        ProofRequestInformation {
+            proof_request_id: ProofID::new(id),
+            callback_proof_request_event: CallbackRequestFilter {
+                account: Address::repeat_byte(1),
+                image_id: H256::repeat_byte(2).into(),
+                input: Bytes::from(vec![1, 2, 3]),
+                callback_contract: Address::repeat_byte(3),
+                function_selector: [0xab, 0xcd, 0xef, 0xab],
+                gas_limit: 3000000,
+            },
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn round_trip_proof_request(
+        storage: SqliteStorage,
+        proof_request_information: ProofRequestInformation,
+    ) {
+        storage
+            .add_new_bonsai_proof_request(proof_request_information.clone())
+            .await
+            .unwrap();
+
+        let requests = storage.fetch_new_bonsai_requests(None).await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].proof_request_id.uuid,
+            proof_request_information.proof_request_id.uuid
+        );
+        assert_eq!(
+            requests[0].callback_proof_request_event,
+            proof_request_information.callback_proof_request_event
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn enforce_non_duplicated_proof_ids(
+        storage: SqliteStorage,
+        proof_request_information: ProofRequestInformation,
+    ) {
+        storage
+            .add_new_bonsai_proof_request(proof_request_information.clone())
+            .await
+            .unwrap();
+
+        let result = storage
+            .add_new_bonsai_proof_request(proof_request_information)
+            .await;
+        assert!(matches!(result, Err(Error::ProofAlreadyExists { .. })));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn failed_proofs_retry_only_max_retries(
+        storage: SqliteStorage,
+        proof_request_information: ProofRequestInformation,
+    ) {
+        let proof_id = proof_request_information.proof_request_id.clone();
+        storage
+            .add_new_bonsai_proof_request(proof_request_information)
+            .await
+            .unwrap();
+
+        for _ in 0..MAX_PROOF_RETRIES {
+            storage
+                .transition_proof_request(proof_id.clone(), ProofRequestState::Pending)
+                .await
+                .unwrap();
+            storage
+                .transition_proof_request(proof_id.clone(), ProofRequestState::New)
+                .await
+                .unwrap();
+        }
+
+        storage
+            .transition_proof_request(proof_id.clone(), ProofRequestState::Pending)
+            .await
+            .unwrap();
+        let result = storage
+            .transition_proof_request(proof_id.clone(), ProofRequestState::New)
+            .await;
+        assert!(result.is_err());
+
+        // A rejected transition leaves the request untouched
+        assert_eq!(
+            storage
+                .get_proof_request_state(proof_id.clone())
+                .await
+                .unwrap(),
+            ProofRequestState::Pending
+        );
+        assert_eq!(
+            storage.get_proof_retries(&proof_id).unwrap(),
+            MAX_PROOF_RETRIES
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn state_survives_reopen(proof_request_information: ProofRequestInformation) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relay.sqlite");
+        let proof_id = proof_request_information.proof_request_id.clone();
+
+        {
+            let storage = SqliteStorage::open(&path).unwrap();
+            storage
+                .add_new_bonsai_proof_request(proof_request_information)
+                .await
+                .unwrap();
+            storage
+                .transition_proof_request(proof_id.clone(), ProofRequestState::Pending)
+                .await
+                .unwrap();
+        }
+
+        let storage = SqliteStorage::open(&path).unwrap();
+        let pending = storage.fetch_pending_bonsai_requests(None).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].proof_request_id.uuid, proof_id.uuid);
+
+        storage
+            .transition_proof_request(proof_id.clone(), ProofRequestState::Completed)
+            .await
+            .unwrap();
+        storage
+            .transition_proof_request(proof_id.clone(), ProofRequestState::PreparingOnchain)
+            .await
+            .unwrap();
+        storage
+            .transition_proof_request(
+                proof_id.clone(),
+                ProofRequestState::CompletedOnchain(H256::zero()),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            storage.get_proof_request_state(proof_id).await,
+            Err(Error::ProofNotFound { .. })
+        ));
+    }
+}
@@ -17,7 +17,7 @@ pub(crate) mod tests {
     use std::sync::Arc;
 
     use bonsai_ethereum_contracts::i_bonsai_relay::CallbackRequestFilter;
-    use bonsai_sdk::alpha_async::get_client_from_parts;
+    use bonsai_sdk::{alpha_async::get_client_from_parts, groth16::ControlId};
     use ethers::types::{Address, Bytes, H256};
     use tokio::sync::Notify;
 
@@ -135,6 +135,7 @@ pub(crate) mod tests {
         let mut manager = BonsaiCompleteProofManager::new(
             bonsai_client,
             true,
+            ControlId::default(),
             storage.clone(),
             new_complete_proofs_notifier.clone(),
             send_batch_notifier.clone(),
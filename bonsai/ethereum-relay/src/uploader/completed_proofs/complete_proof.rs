@@ -18,6 +18,7 @@ use bonsai_ethereum_contracts::i_bonsai_relay::{
 use bonsai_sdk::{
     alpha::{Client, SessionId},
     alpha_async::session_status,
+    groth16::ControlId,
 };
 use ethers::abi;
 
@@ -33,6 +34,7 @@ pub(crate) struct CompleteProof {
 pub(crate) async fn get_complete_proof(
     bonsai_client: Client,
     dev_mode: bool,
+    control_id: ControlId,
     bonsai_proof_id: SessionId,
     callback_request: CallbackRequestFilter,
 ) -> Result<CompleteProof, CompleteProofError> {
@@ -48,6 +50,17 @@ pub(crate) async fn get_complete_proof(
     let snark_receipt =
         super::snark::get_snark_receipt(bonsai_client.clone(), snark_id, bonsai_proof_id.clone())
             .await?;
+
+    // Check the SNARK before paying for a transaction that would revert.
+    if !dev_mode {
+        snark_receipt
+            .verify_with_control_id(&control_id, &callback_request.image_id)
+            .map_err(|err| CompleteProofError::SnarkInvalid {
+                source: err,
+                id: bonsai_proof_id.clone(),
+            })?;
+    }
+
     let seal = match dev_mode {
         true => vec![],
         false => abi::encode(&[tokenize_snark_receipt(&snark_receipt.snark).map_err(|_| {
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bonsai_sdk::groth16::Groth16Error;
 use displaydoc::Display;
 use ethers::{prelude::ProviderError, types::H256};
 use thiserror::Error;
@@ -74,6 +75,8 @@ pub(crate) enum CompleteProofError {
     SnarkAborted { id: ProofID },
     /// bonsai snark is in unknown state
     SnarkUnknown { id: ProofID },
+    /// bonsai snark failed off-chain verification
+    SnarkInvalid { source: Groth16Error, id: ProofID },
 }
 
 impl CompleteProofError {
@@ -83,6 +86,7 @@ impl CompleteProofError {
             | CompleteProofError::SnarkFailed { id }
             | CompleteProofError::SnarkTimedOut { id }
             | CompleteProofError::SnarkUnknown { id }
+            | CompleteProofError::SnarkInvalid { id, .. }
             | CompleteProofError::ClientAPI { id, .. } => id,
         }
     }
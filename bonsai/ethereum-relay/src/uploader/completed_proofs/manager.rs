@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use bonsai_ethereum_contracts::{i_bonsai_relay::Callback, IBonsaiRelay};
-use bonsai_sdk::alpha::Client;
+use bonsai_sdk::{alpha::Client, groth16::ControlId};
 use ethers::prelude::{k256::ecdsa::SigningKey, *};
 use futures::{stream::FuturesUnordered, StreamExt};
 use tokio::{sync::Notify, task::JoinHandle};
@@ -35,6 +35,7 @@ const BONSAI_RELAY_GAS_LIMIT: u64 = 3000000;
 pub(crate) struct BonsaiCompleteProofManager<S: Storage> {
     client: Client,
     dev_mode: bool,
+    control_id: ControlId,
     storage: S,
     new_complete_proofs_notifier: Arc<Notify>,
     ready_to_send_batch: Vec<CompleteProof>,
@@ -51,6 +52,7 @@ impl<S: Storage> BonsaiCompleteProofManager<S> {
     pub(crate) fn new(
         client: Client,
         dev_mode: bool,
+        control_id: ControlId,
         storage: S,
         new_complete_proofs_notifier: Arc<Notify>,
         send_batch_notifier: Arc<Notify>,
@@ -62,6 +64,7 @@ impl<S: Storage> BonsaiCompleteProofManager<S> {
         Self {
             client,
             dev_mode,
+            control_id,
             storage,
             new_complete_proofs_notifier,
             ready_to_send_batch: Vec::new(),
@@ -144,6 +147,7 @@ impl<S: Storage> BonsaiCompleteProofManager<S> {
             let completed_proof_request_handler = tokio::spawn(get_complete_proof(
                 self.client.clone(),
                 self.dev_mode,
+                self.control_id,
                 request.proof_request_id.clone(),
                 request.callback_proof_request_event,
             ));
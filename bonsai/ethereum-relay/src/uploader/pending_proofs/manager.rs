@@ -89,6 +89,25 @@ impl<S: Storage> BonsaiPendingProofManager<S> {
         Ok(())
     }
 
+    /// Resume polling Bonsai for the requests that were pending when the relay
+    /// last stopped.
+    async fn resume_pending_proof_requests(
+        &mut self,
+    ) -> Result<(), BonsaiPendingProofManagerError> {
+        let pending_proof_requests = self.storage.fetch_pending_bonsai_requests(None).await?;
+
+        for request in pending_proof_requests.into_iter() {
+            let pending_proof_request =
+                PendingProofRequest::new(self.client.clone(), request.proof_request_id.clone());
+            self.futures_set.push(tokio::spawn(pending_proof_request));
+
+            let log_id = request.proof_request_id.clone();
+            info!(?log_id, "resuming pending proof");
+        }
+
+        Ok(())
+    }
+
     pub(crate) async fn handle_pending_proof_result(
         &self,
         pending_proof_result: Result<ProofRequestID, PendingProofError>,
@@ -162,6 +181,7 @@ impl<S: Storage> BonsaiPendingProofManager<S> {
     }
 
     pub(crate) async fn run(mut self) -> Result<(), BonsaiPendingProofManagerError> {
+        self.resume_pending_proof_requests().await?;
         self.process_new_pending_proof_requests().await?;
 
         loop {
@@ -27,7 +27,7 @@ mod tests {
             client::{CallbackRequest, Client},
             utils,
         },
-        Relayer,
+        ControlId, Relayer,
     };
     use bonsai_sdk::{
         alpha::Client as BonsaiClient,
@@ -144,10 +144,12 @@ mod tests {
         let relayer = Relayer {
             rest_api: false,
             dev_mode: dev_mode().unwrap(),
+            control_id: ControlId::default(),
             rest_api_port: "8080".to_string(),
             bonsai_api_url: get_bonsai_url(),
             bonsai_api_key: get_api_key(),
             relay_contract_address: bonsai_relay_contract,
+            storage_path: None,
         };
 
         dbg!("starting bonsai relayer");
@@ -291,10 +293,12 @@ mod tests {
         let relayer = Relayer {
             rest_api: true,
             dev_mode: dev_mode().unwrap(),
+            control_id: ControlId::default(),
             rest_api_port: "8080".to_string(),
             bonsai_api_url: get_bonsai_url(),
             bonsai_api_key: get_api_key(),
             relay_contract_address: bonsai_relay_contract,
+            storage_path: None,
         };
 
         dbg!("starting bonsai relayer");
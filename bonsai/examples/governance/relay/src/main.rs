@@ -15,7 +15,7 @@
 use std::io::Write;
 
 use anyhow::Context;
-use bonsai_ethereum_relay::{tokenize_snark_receipt, EthersClientConfig, Relayer};
+use bonsai_ethereum_relay::{tokenize_snark_receipt, ControlId, EthersClientConfig, Relayer};
 use bonsai_ethereum_relay_cli::{resolve_guest_entry, resolve_image_output, Output};
 use bonsai_sdk::alpha_async::{get_client_from_parts, upload_img};
 use clap::{Args, Parser, Subcommand};
@@ -197,10 +197,12 @@ async fn main() -> anyhow::Result<()> {
             let relayer = Relayer {
                 rest_api: true,
                 dev_mode: dev_mode,
+                control_id: ControlId::default(),
                 rest_api_port: "8080".to_string(),
                 bonsai_api_url: args.global_opts.bonsai_api_url.clone(),
                 bonsai_api_key: args.global_opts.bonsai_api_key.clone(),
                 relay_contract_address: relay_address,
+                storage_path: None,
             };
             let client_config = EthersClientConfig::new(
                 eth_node,
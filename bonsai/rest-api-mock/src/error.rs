@@ -61,6 +61,15 @@ impl Error {
             | Error::Join { .. } => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    /// The full chain of causes of this error, as reported in the
+    /// `error_msg` of a failed session.
+    pub(crate) fn message(&self) -> String {
+        match self {
+            Error::Unspecified(err) => format!("{err:#}"),
+            err => DisplayErrorCauses(err).to_string(),
+        }
+    }
 }
 
 impl From<TryFromIntError> for Error {
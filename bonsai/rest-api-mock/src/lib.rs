@@ -17,7 +17,11 @@ mod prover;
 mod routes;
 mod state;
 
-use std::sync::{Arc, RwLock};
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use anyhow::Context;
 use axum::{
@@ -25,15 +29,17 @@ use axum::{
     routing::{get, post, put},
     Extension, Router,
 };
+use risc0_zkvm::ProverOpts;
 use tokio::sync::mpsc;
 use tower_http::trace::{DefaultOnRequest, TraceLayer};
 use tracing::{info, Level};
 
 use crate::{
-    prover::{Prover, ProverHandle},
+    prover::{Prover, ProverContext, ProverHandle},
     routes::{
-        create_session, create_snark, get_image_upload, get_input_upload, get_receipt,
-        put_image_upload, put_input_upload, session_status, snark_status,
+        cancel_session, create_session, create_snark, get_image_upload, get_input_upload,
+        get_receipt, list_sessions, put_image_upload, put_input_upload, quotas, session_logs,
+        session_status, snark_status, version,
     },
     state::BonsaiState,
 };
@@ -44,11 +50,16 @@ fn app(state: Arc<RwLock<BonsaiState>>, prover_handle: ProverHandle) -> Router {
         .route("/images/:image_id", put(put_image_upload))
         .route("/inputs/upload", get(get_input_upload))
         .route("/inputs/:input_id", put(put_input_upload))
+        .route("/sessions", get(list_sessions))
         .route("/sessions/create", post(create_session))
+        .route("/sessions/cancel/:session_id", post(cancel_session))
         .route("/sessions/status/:session_id", get(session_status))
+        .route("/sessions/logs/:session_id", get(session_logs))
         .route("/snark/create", post(create_snark))
         .route("/snark/status/:snark_id", get(snark_status))
-        .route("/receipts/:session_id", get(get_receipt))
+        .route("/receipts/:receipt_id", get(get_receipt))
+        .route("/version", get(version))
+        .route("/user/quotas", get(quotas))
         .layer(Extension(prover_handle))
         .with_state(state)
         .layer(DefaultBodyLimit::max(256 * 1024 * 1024))
@@ -57,19 +68,86 @@ fn app(state: Arc<RwLock<BonsaiState>>, prover_handle: ProverHandle) -> Router {
         ))
 }
 
+/// Options for [serve_with_options].
+#[derive(Clone, Debug)]
+pub struct MockOptions {
+    /// Directory to persist images, inputs, sessions and receipts to, so that
+    /// they survive a restart of the mock. State is only kept in memory if
+    /// this is `None`.
+    pub state_dir: Option<PathBuf>,
+
+    /// Sessions that run for longer than this are reported as `TIMED_OUT`.
+    pub session_timeout: Option<Duration>,
+
+    /// Prove sessions with these options, which select the hash function and
+    /// whether receipts are compressed to a [risc0_zkvm::SuccinctReceipt].
+    /// Sessions are only executed, and their receipts are fake, if this is
+    /// `None`.
+    pub prover_opts: Option<ProverOpts>,
+
+    /// The number of sessions to run concurrently.
+    pub workers: usize,
+
+    /// The segment limit that sessions are executed with.
+    pub segment_limit_po2: u32,
+}
+
+impl Default for MockOptions {
+    /// Return [MockOptions] that keep state in memory, never time out, only
+    /// execute sessions and run one session at a time.
+    fn default() -> Self {
+        Self {
+            state_dir: None,
+            session_timeout: None,
+            prover_opts: None,
+            workers: 1,
+            segment_limit_po2: 20,
+        }
+    }
+}
+
 /// Starts a mock of Bonsai on localhost at the given port. It exposes the same
 /// REST API of Bonsai alpha.
 ///
-/// Note that this mock only performs execution, no proving.
+/// Note that this mock only performs execution, no proving. Use
+/// [serve_with_options] with [MockOptions::prover_opts] to produce real
+/// receipts.
 pub async fn serve(port: String) -> anyhow::Result<()> {
+    serve_with_options(port, MockOptions::default()).await
+}
+
+/// Starts a mock of Bonsai on localhost at the given port, configured with
+/// the given [MockOptions].
+pub async fn serve_with_options(port: String, options: MockOptions) -> anyhow::Result<()> {
     let local_url = format!("http://localhost:{port}");
     let bind_address = &format!("0.0.0.0:{port}");
-    let state = Arc::new(RwLock::new(BonsaiState::new(local_url)));
+    let state = match options.state_dir {
+        Some(ref state_dir) => BonsaiState::open(local_url, state_dir).with_context(|| {
+            format!(
+                "failed to load Local Bonsai state from {}",
+                state_dir.display()
+            )
+        })?,
+        None => BonsaiState::new(local_url),
+    };
+    let state = Arc::new(RwLock::new(state));
 
     let (sender, receiver) = mpsc::channel(8);
-    let mut prover = Prover::new(receiver, Arc::clone(&state));
-
-    let prover_handle = ProverHandle { sender };
+    let prover_handle = ProverHandle {
+        sender,
+        workers: options.workers.max(1),
+        proving: options.prover_opts.is_some(),
+    };
+    let mut prover = Prover::new(
+        receiver,
+        options.workers,
+        ProverContext {
+            storage: Arc::clone(&state),
+            session_timeout: options.session_timeout,
+            prover_opts: options.prover_opts,
+            segment_limit_po2: options.segment_limit_po2,
+        },
+    );
 
     tokio::spawn(async move { prover.run().await });
 
@@ -91,12 +169,23 @@ pub async fn serve(port: String) -> anyhow::Result<()> {
 mod test {
     use std::time::Duration;
 
+    use ::bonsai_sdk::{
+        alpha::{
+            responses::{SessionStatus, SessionStatusRes},
+            Client, SessionId,
+        },
+        non_blocking,
+    };
     use anyhow::{bail, Result};
     use bonsai_sdk::alpha_async as bonsai_sdk;
-    use risc0_zkvm::{MemoryImage, Program, GUEST_MAX_MEM, PAGE_SIZE};
-    use risc0_zkvm_methods::HELLO_COMMIT_ELF;
+    use risc0_zkvm::{
+        InnerReceipt, MemoryImage, Program, ProverOpts, Receipt, GUEST_MAX_MEM, PAGE_SIZE,
+    };
+    use risc0_zkvm_methods::{
+        multi_test::MultiTestSpec, HELLO_COMMIT_ELF, MULTI_TEST_ELF, MULTI_TEST_ID,
+    };
 
-    use crate::serve;
+    use crate::{serve, serve_with_options, MockOptions};
 
     async fn run_bonsai(
         bonsai_api_url: String,
@@ -124,11 +213,11 @@ mod test {
         let session = bonsai_sdk::create_session(client.clone(), img_id, input_id).await?;
         loop {
             let res = bonsai_sdk::session_status(client.clone(), session.clone()).await?;
-            if res.status == "RUNNING" {
+            if res.session_status() == SessionStatus::Running {
                 std::thread::sleep(Duration::from_secs(15));
                 continue;
             }
-            if res.status == "SUCCEEDED" {
+            if res.session_status() == SessionStatus::Succeeded {
                 // Download the receipt, containing the output
                 let receipt_url = res
                     .receipt_url
@@ -183,4 +272,246 @@ mod test {
 
         local_bonsai_handle.abort();
     }
+
+    async fn client(port: u16) -> Client {
+        bonsai_sdk::get_client_from_parts(
+            format!("http://localhost:{port}"),
+            "test_key".to_string(),
+            risc0_zkvm::VERSION,
+        )
+        .await
+        .unwrap()
+    }
+
+    // Runs MULTI_TEST_ELF with the given spec, followed by `stdin`, and waits
+    // for the session to finish.
+    async fn run_multi_test(
+        client: &Client,
+        spec: MultiTestSpec,
+        stdin: &[u8],
+    ) -> (SessionId, SessionStatusRes) {
+        let image_id = {
+            let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+            let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+            hex::encode(image.compute_id())
+        };
+        bonsai_sdk::upload_img(client.clone(), image_id.clone(), MULTI_TEST_ELF.to_vec())
+            .await
+            .unwrap();
+
+        let mut input: Vec<u8> = risc0_zkvm::serde::to_vec(&spec)
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        input.extend_from_slice(stdin);
+        let input_id = bonsai_sdk::upload_input(client.clone(), input)
+            .await
+            .unwrap();
+
+        let session = bonsai_sdk::create_session(client.clone(), image_id, input_id)
+            .await
+            .unwrap();
+        loop {
+            let res = bonsai_sdk::session_status(client.clone(), session.clone())
+                .await
+                .unwrap();
+            if res.session_status().is_done() {
+                return (session, res);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_guest_panic() {
+        let local_bonsai_handle = tokio::spawn(async move { serve("9009".to_string()).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = client(9009).await;
+        let (_, res) = run_multi_test(&client, MultiTestSpec::Panic, &[]).await;
+        assert_eq!(res.session_status(), SessionStatus::Failed);
+        assert!(res.receipt_url.is_none());
+        let error_msg = res.error_msg.unwrap();
+        assert!(
+            error_msg.contains("MultiTestSpec::Panic invoked"),
+            "{error_msg}"
+        );
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_timeout() {
+        let options = MockOptions {
+            session_timeout: Some(Duration::from_millis(1)),
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_options("9019".to_string(), options).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = client(9019).await;
+        let spec = MultiTestSpec::BusyLoop { cycles: 1 << 24 };
+        let (_, res) = run_multi_test(&client, spec, &[]).await;
+        assert_eq!(res.session_status(), SessionStatus::TimedOut);
+        assert!(res.error_msg.is_some());
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_logs() {
+        let local_bonsai_handle = tokio::spawn(async move { serve("9029".to_string()).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = client(9029).await;
+        let spec = MultiTestSpec::EchoStdout { nbytes: 5, fd: 0 };
+        let (session, res) = run_multi_test(&client, spec, b"hello from the guest").await;
+        assert_eq!(res.session_status(), SessionStatus::Succeeded);
+        let logs = bonsai_sdk::session_logs(client.clone(), session)
+            .await
+            .unwrap();
+        assert_eq!(logs, "hello from the guest");
+
+        let quotas = bonsai_sdk::quotas(client.clone()).await.unwrap();
+        assert!(quotas.cycle_usage > 0);
+        let version = tokio::task::spawn_blocking(move || client.version())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.risc0_zkvm, [risc0_zkvm::VERSION]);
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_state_dir() {
+        let state_dir = tempfile::tempdir().unwrap();
+
+        let options = MockOptions {
+            state_dir: Some(state_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_options("9039".to_string(), options).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let (session, res) =
+            run_multi_test(&client(9039).await, MultiTestSpec::DoNothing, &[]).await;
+        assert_eq!(res.session_status(), SessionStatus::Succeeded);
+        local_bonsai_handle.abort();
+
+        // A new mock sharing the state directory serves the same session.
+        let options = MockOptions {
+            state_dir: Some(state_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_options("9049".to_string(), options).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let client = client(9049).await;
+        let res = bonsai_sdk::session_status(client.clone(), session)
+            .await
+            .unwrap();
+        assert_eq!(res.session_status(), SessionStatus::Succeeded);
+        let receipt_url = res.receipt_url.unwrap();
+        assert!(receipt_url.starts_with("http://localhost:9049/"));
+        bonsai_sdk::download(client, receipt_url).await.unwrap();
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_prove() {
+        let options = MockOptions {
+            prover_opts: Some(ProverOpts::default()),
+            workers: 2,
+            segment_limit_po2: 14,
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_options("9059".to_string(), options).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = client(9059).await;
+        let quotas = bonsai_sdk::quotas(client.clone()).await.unwrap();
+        assert_eq!(quotas.concurrent_proofs, 2);
+
+        let spec = MultiTestSpec::BusyLoop { cycles: 1 << 15 };
+        let (session, res) = run_multi_test(&client, spec, &[]).await;
+        assert_eq!(res.session_status(), SessionStatus::Succeeded);
+        let receipt = bonsai_sdk::download(client.clone(), res.receipt_url.unwrap())
+            .await
+            .unwrap();
+        let receipt: Receipt = bincode::deserialize(&receipt).unwrap();
+        receipt.verify(MULTI_TEST_ID).unwrap();
+        let InnerReceipt::Composite(composite) = receipt.inner else {
+            panic!("expected a composite receipt");
+        };
+        assert!(composite.segments.len() > 1);
+
+        // The mock cannot produce a Groth16 seal for a real receipt.
+        let snark = bonsai_sdk::create_snark(client.clone(), session.uuid)
+            .await
+            .unwrap();
+        let res = bonsai_sdk::snark_status(client, snark).await.unwrap();
+        assert_eq!(res.session_status(), SessionStatus::Failed);
+        assert!(res.output.is_none());
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_cancel() {
+        let local_bonsai_handle = tokio::spawn(async move { serve("9069".to_string()).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = non_blocking::Client::from_parts(
+            "http://localhost:9069".to_string(),
+            "test_key".to_string(),
+            risc0_zkvm::VERSION,
+        )
+        .unwrap();
+        let image_id = {
+            let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+            let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+            hex::encode(image.compute_id())
+        };
+        client
+            .upload_img(&image_id, MULTI_TEST_ELF.to_vec())
+            .await
+            .unwrap();
+        let input = risc0_zkvm::serde::to_vec(&MultiTestSpec::BusyLoop { cycles: 1 << 24 })
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let input_id = client.upload_input(input).await.unwrap();
+        let session = client.create_session(image_id, input_id).await.unwrap();
+
+        let running = client
+            .list_sessions(Some(SessionStatus::Running))
+            .await
+            .unwrap();
+        assert!(running.iter().any(|info| info.uuid == session.uuid));
+
+        session.cancel(&client).await.unwrap();
+        let res = session
+            .wait(&client, Duration::from_secs(10), Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(res.session_status(), SessionStatus::Aborted);
+        assert!(res.receipt_url.is_none());
+
+        // Only running sessions can be cancelled.
+        assert!(session.cancel(&client).await.is_err());
+        let aborted = client
+            .list_sessions(Some(SessionStatus::Aborted))
+            .await
+            .unwrap();
+        assert_eq!(aborted.len(), 1);
+        assert_eq!(aborted[0].uuid, session.uuid);
+
+        local_bonsai_handle.abort();
+    }
 }
@@ -14,15 +14,18 @@
 
 use std::{
     fmt,
-    sync::{Arc, RwLock},
+    io::Write,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
 };
 
 use anyhow::Context;
+use bonsai_sdk::alpha::responses::SessionStatus;
 use risc0_zkvm::{
-    ExecutorEnv, ExecutorImpl, InnerReceipt, MemoryImage, Program, Receipt, GUEST_MAX_MEM,
-    PAGE_SIZE,
+    get_prover_server, ExecutorEnv, ExecutorImpl, ExitCode, InnerReceipt, MemoryImage, Program,
+    ProverOpts, Receipt, VerifierContext, GUEST_MAX_MEM, PAGE_SIZE,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
 
 use crate::{error::Error, state::BonsaiState};
 
@@ -51,6 +54,10 @@ impl fmt::Display for ProverMessage {
 #[derive(Clone)]
 pub(crate) struct ProverHandle {
     pub sender: mpsc::Sender<ProverMessage>,
+    /// The number of sessions run concurrently.
+    pub workers: usize,
+    /// Whether sessions are proven, rather than only executed.
+    pub proving: bool,
 }
 
 impl ProverHandle {
@@ -66,83 +73,215 @@ impl ProverHandle {
 
 const ELF_MAGIC: [u8; 4] = [0x7f, 0x45, 0x4c, 0x46];
 
+/// Collects the guest's stdout and stderr into a single log.
+#[derive(Clone, Default)]
+struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+impl LogWriter {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 pub(crate) struct Prover {
     pub(crate) receiver: mpsc::Receiver<ProverMessage>,
+    pub(crate) workers: usize,
+    pub(crate) context: Arc<ProverContext>,
+}
+
+/// The state shared by the workers that run sessions.
+pub(crate) struct ProverContext {
     pub(crate) storage: Arc<RwLock<BonsaiState>>,
+    pub(crate) session_timeout: Option<Duration>,
+    pub(crate) prover_opts: Option<ProverOpts>,
+    pub(crate) segment_limit_po2: u32,
 }
 
 impl Prover {
     pub(crate) fn new(
         receiver: mpsc::Receiver<ProverMessage>,
-        storage: Arc<RwLock<BonsaiState>>,
+        workers: usize,
+        context: ProverContext,
     ) -> Self {
-        Prover { receiver, storage }
+        Prover {
+            receiver,
+            workers: workers.max(1),
+            context: Arc::new(context),
+        }
     }
 
-    pub async fn handle_message(&mut self, msg: &ProverMessage) -> Result<(), Error> {
+    /// Run the tasks received on the channel, up to `workers` at a time.
+    pub(crate) async fn run(&mut self) -> Result<(), Error> {
+        let workers = Arc::new(Semaphore::new(self.workers));
+        while let Some(msg) = self.receiver.recv().await {
+            tracing::info!("Receiver: {}", &msg);
+            let permit = Arc::clone(&workers)
+                .acquire_owned()
+                .await
+                .map_err(anyhow::Error::from)?;
+            let context = Arc::clone(&self.context);
+            tokio::spawn(async move {
+                if let Err(err) = context.run_task(&msg, permit).await {
+                    tracing::error!("Failed to record the result of {}: {:?}", msg, err);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ProverContext {
+    async fn run_task(
+        &self,
+        msg: &ProverMessage,
+        permit: OwnedSemaphorePermit,
+    ) -> Result<(), Error> {
+        match self.handle_message(msg, permit).await {
+            Ok(_) => tracing::info!("Task done!"),
+            Err(err) => {
+                match msg {
+                    ProverMessage::RunSession(task) => self.finish_session(task, |session| {
+                        session.status = SessionStatus::Failed;
+                        session.error_msg = Some(err.message());
+                    })?,
+                };
+                tracing::error!("Task {} failed! - {:?}", msg, err)
+            }
+        }
+        Ok(())
+    }
+
+    /// The `permit` is held until the guest stops running, even if the
+    /// session has already timed out.
+    async fn handle_message(
+        &self,
+        msg: &ProverMessage,
+        permit: OwnedSemaphorePermit,
+    ) -> Result<(), Error> {
         match msg {
             ProverMessage::RunSession(task) => {
+                if self.is_cancelled(task)? {
+                    tracing::info!("Session {} was cancelled", task.session_id);
+                    return Ok(());
+                }
                 tracing::info!("Running task...");
                 let image = self.get_image(task).await?;
                 let input = self.get_input(task).await?;
-                let mem_img = image.as_slice();
-                let mem_img = if mem_img[0..ELF_MAGIC.len()] == ELF_MAGIC {
-                    tracing::info!("Loading guest image from ELF binary");
-                    let program = Program::load_elf(mem_img, GUEST_MAX_MEM as u32)?;
-                    MemoryImage::new(&program, PAGE_SIZE as u32)?
-                } else {
-                    bincode::deserialize(mem_img).context("failed to decode memory image")?
-                };
 
-                let env = ExecutorEnv::builder()
-                    .write_slice(&input)
-                    .session_limit(None)
-                    .segment_limit_po2(20)
-                    .build()
-                    .map_err(|e| {
-                        anyhow::anyhow!("failed to build executor environment: {:?}", e)
+                self.storage
+                    .write()?
+                    .update_session(&task.session_id, |session| {
+                        session.state = Some("Executor".to_string())
                     })?;
-                let mut exec = ExecutorImpl::new(env, mem_img)?;
-                let session = exec
-                    .run()
-                    .context("Executor failed to generate a successful session")?;
-
-                let receipt = Receipt {
-                    inner: InnerReceipt::Fake {
-                        metadata: session.get_metadata()?,
+
+                // Execute on a blocking thread, so that a guest that runs too
+                // long can time out and a panic can be reported.
+                let logs = LogWriter::default();
+                let segment_limit_po2 = self.segment_limit_po2;
+                let prover_opts = self.prover_opts.clone();
+                let guest_logs = logs.clone();
+                let handle = tokio::task::spawn_blocking(move || {
+                    let result = execute(
+                        &image,
+                        input,
+                        segment_limit_po2,
+                        prover_opts.as_ref(),
+                        guest_logs,
+                    );
+                    drop(permit);
+                    result
+                });
+                let result = match self.session_timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, handle).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            // The blocking thread cannot be interrupted, so its
+                            // result is discarded once it finishes.
+                            return self.finish_session(task, |session| {
+                                session.status = SessionStatus::TimedOut;
+                                session.error_msg =
+                                    Some(format!("Session exceeded the timeout of {timeout:?}"));
+                                session.logs = logs.contents();
+                            });
+                        }
                     },
-                    journal: session.journal.unwrap_or_default(),
+                    None => handle.await,
+                };
+                let result = match result {
+                    Ok(result) => result,
+                    Err(err) if err.is_panic() => {
+                        let panic = err.into_panic();
+                        let msg = panic
+                            .downcast_ref::<&str>()
+                            .map(|msg| msg.to_string())
+                            .or_else(|| panic.downcast_ref::<String>().cloned())
+                            .unwrap_or_default();
+                        Err(anyhow::anyhow!("Executor panicked: {msg}").into())
+                    }
+                    Err(err) => Err(err.into()),
                 };
-                let receipt_bytes = bincode::serialize(&receipt)?;
-                self.storage
-                    .write()?
-                    .put_receipt(task.session_id.clone(), receipt_bytes);
                 self.storage
                     .write()?
-                    .put_session(task.session_id.clone(), "SUCCEEDED".to_string());
+                    .update_session(&task.session_id, |session| session.logs = logs.contents())?;
+
+                let (receipt, cycles) = result?;
+                let receipt_bytes = bincode::serialize(&receipt)?;
+
+                // Check for a cancel and record the result under one lock, so
+                // that a cancelled session never ends up with a receipt.
+                let mut storage = self.storage.write()?;
+                let running = storage
+                    .get_session(&task.session_id)
+                    .is_some_and(|session| session.status == SessionStatus::Running);
+                if !running {
+                    return Ok(());
+                }
+                storage.put_receipt(task.session_id.clone(), receipt_bytes)?;
+                storage.update_session(&task.session_id, |session| {
+                    session.status = SessionStatus::Succeeded;
+                    session.state = None;
+                    session.cycles = cycles;
+                })?;
             }
         }
 
         Ok(())
     }
 
-    pub(crate) async fn run(&mut self) -> Result<(), Error> {
-        while let Some(msg) = self.receiver.recv().await {
-            tracing::info!("Receiver: {}", &msg);
-            match self.handle_message(&msg).await {
-                Ok(_) => tracing::info!("Task done!"),
-                Err(err) => {
-                    match &msg {
-                        ProverMessage::RunSession(task) => self
-                            .storage
-                            .write()?
-                            .put_session(task.session_id.clone(), "FAILED".to_string()),
-                    };
-                    tracing::error!("Task {} failed! - {:?}", msg, err)
+    /// Whether the session was cancelled while it was queued or running.
+    fn is_cancelled(&self, task: &Task) -> Result<bool, Error> {
+        Ok(self
+            .storage
+            .read()?
+            .get_session(&task.session_id)
+            .is_some_and(|session| session.status == SessionStatus::Aborted))
+    }
+
+    /// Record the outcome of a session, unless it was cancelled.
+    fn finish_session(
+        &self,
+        task: &Task,
+        update: impl FnOnce(&mut crate::state::Session),
+    ) -> Result<(), Error> {
+        self.storage
+            .write()?
+            .update_session(&task.session_id, |session| {
+                if session.status == SessionStatus::Running {
+                    session.state = None;
+                    update(session);
                 }
-            }
-        }
-        Ok(())
+            })
     }
 
     async fn get_image(&self, task: &Task) -> Result<Vec<u8>, Error> {
@@ -161,3 +300,58 @@ impl Prover {
             .ok_or_else(|| anyhow::anyhow!("Failed to get input for ID: {:?}", task.input_id))?)
     }
 }
+
+/// Execute the guest, returning a receipt for the session and its total
+/// cycles. The session is proven with `prover_opts` if given, otherwise the
+/// receipt is fake.
+fn execute(
+    image: &[u8],
+    input: Vec<u8>,
+    segment_limit_po2: u32,
+    prover_opts: Option<&ProverOpts>,
+    logs: LogWriter,
+) -> Result<(Receipt, u64), Error> {
+    let mem_img = if image.starts_with(&ELF_MAGIC) {
+        tracing::info!("Loading guest image from ELF binary");
+        let program = Program::load_elf(image, GUEST_MAX_MEM as u32)?;
+        MemoryImage::new(&program, PAGE_SIZE as u32)?
+    } else {
+        bincode::deserialize(image).context("failed to decode memory image")?
+    };
+
+    let env = ExecutorEnv::builder()
+        .write_slice(&input)
+        .session_limit(None)
+        .segment_limit_po2(segment_limit_po2)
+        .stdout(logs.clone())
+        .stderr(logs)
+        .build()
+        .map_err(|e| anyhow::anyhow!("failed to build executor environment: {:?}", e))?;
+    let mut exec = ExecutorImpl::new(env, mem_img)?;
+    let session = exec
+        .run()
+        .context("Executor failed to generate a successful session")?;
+    if session.exit_code == ExitCode::Fault {
+        Err(anyhow::anyhow!("Guest faulted"))?;
+    }
+
+    let receipt = match prover_opts {
+        Some(prover_opts) => {
+            tracing::info!(
+                "Proving session with receipt kind {:?}",
+                prover_opts.receipt_kind
+            );
+            let prover = get_prover_server(prover_opts)?;
+            prover
+                .prove_session(&VerifierContext::default(), &session)
+                .context("Prover failed to prove the session")?
+        }
+        None => Receipt {
+            inner: InnerReceipt::Fake {
+                metadata: session.get_metadata()?,
+            },
+            journal: session.journal.clone().unwrap_or_default(),
+        },
+    };
+    Ok((receipt, session.stats.total_cycles))
+}
@@ -16,22 +16,29 @@ use std::vec;
 
 use axum::{
     body::Bytes,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Extension, Json,
 };
 use bonsai_sdk::alpha::responses::{
-    CreateSessRes, Groth16Seal, ImgUploadRes, ProofReq, SessionStatusRes, SnarkReceipt, SnarkReq,
-    SnarkStatusRes, UploadRes,
+    CreateSessRes, Groth16Seal, ImgUploadRes, ListSessionsRes, ProofReq, Quotas, SessionInfo,
+    SessionStatus, SessionStatusRes, SnarkReceipt, SnarkReq, SnarkStatusRes, UploadRes,
+    VersionInfo,
 };
-use risc0_zkvm::Receipt;
+use risc0_zkvm::{sha::Digestible, Receipt};
+use serde::Deserialize;
 use tracing::info;
 
 use crate::{
     error::Error,
     prover::{ProverHandle, Task},
-    state::AppState,
+    state::{AppState, Session},
 };
 
+// The quotas reported by the mock. Only the cycle usage is tracked, and the
+// parallelism is the number of workers.
+const EXEC_CYCLE_LIMIT: u64 = 100_000; // in millions of cycles
+const CYCLE_BUDGET: u64 = u64::MAX;
+
 pub(crate) async fn get_image_upload(
     State(s): State<AppState>,
     Path(image_id): Path<String>,
@@ -50,7 +57,7 @@ pub(crate) async fn put_image_upload(
     Path(image_id): Path<String>,
     body: Bytes,
 ) -> Result<(), Error> {
-    s.write()?.put_image(image_id.clone(), body.to_vec());
+    s.write()?.put_image(image_id.clone(), body.to_vec())?;
     info!("ImageID {image_id} uploaded");
     Ok(())
 }
@@ -69,7 +76,7 @@ pub(crate) async fn put_input_upload(
     Path(input_id): Path<String>,
     body: Bytes,
 ) -> Result<(), Error> {
-    s.write()?.put_input(input_id, body.to_vec());
+    s.write()?.put_input(input_id, body.to_vec())?;
     Ok(())
 }
 
@@ -80,7 +87,7 @@ pub(crate) async fn create_session(
 ) -> Result<Json<CreateSessRes>, Error> {
     let session_id = uuid::Uuid::new_v4();
     s.write()?
-        .put_session(session_id.to_string(), "RUNNING".to_string());
+        .put_session(session_id.to_string(), Session::running())?;
     let task = Task {
         image_id: request.img,
         input_id: request.input,
@@ -98,29 +105,107 @@ pub(crate) async fn session_status(
     Path(session_id): Path<String>,
 ) -> Result<Json<SessionStatusRes>, Error> {
     let storage = s.read()?;
-    let status = storage
+    let session = storage
         .get_session(&session_id)
         .ok_or_else(|| anyhow::anyhow!("Session not found for session id: {:?}", &session_id))?;
-    let receipt = storage.get_receipt(&session_id);
-    match receipt {
-        Some(_) => Ok(Json(SessionStatusRes {
-            status,
-            receipt_url: Some(format!("{}/receipts/{}", storage.local_url, session_id)),
-            error_msg: None,
-            state: None,
-        })),
-        None => Ok(Json(SessionStatusRes {
-            status,
-            receipt_url: None,
-            error_msg: None,
-            state: None,
-        })),
+    let receipt_url = storage
+        .get_receipt(&session_id)
+        .map(|_| format!("{}/receipts/{}", storage.local_url, session_id));
+    Ok(Json(SessionStatusRes {
+        status: session.status.to_string(),
+        receipt_url,
+        error_msg: session.error_msg,
+        state: session.state,
+    }))
+}
+
+pub(crate) async fn cancel_session(
+    State(s): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<(), Error> {
+    let mut storage = s.write()?;
+    let session = storage
+        .get_session(&session_id)
+        .ok_or_else(|| anyhow::anyhow!("Session not found for session id: {:?}", &session_id))?;
+    if session.status != SessionStatus::Running {
+        Err(anyhow::anyhow!(
+            "Session {session_id} is not running: {}",
+            session.status
+        ))?;
     }
+    // A running guest cannot be interrupted, so its worker stays busy until
+    // it finishes, but its result is discarded.
+    storage.update_session(&session_id, |session| {
+        session.status = SessionStatus::Aborted;
+        session.state = None;
+        session.error_msg = Some("Session cancelled".to_string());
+    })?;
+    info!("Session {session_id} cancelled");
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ListSessionsQuery {
+    status: Option<SessionStatus>,
+}
+
+pub(crate) async fn list_sessions(
+    State(s): State<AppState>,
+    Query(query): Query<ListSessionsQuery>,
+) -> Result<Json<ListSessionsRes>, Error> {
+    let sessions = s
+        .read()?
+        .sessions()
+        .filter(|(_, session)| query.status.is_none() || query.status == Some(session.status))
+        .map(|(uuid, session)| SessionInfo {
+            uuid: uuid.clone(),
+            status: session.status,
+        })
+        .collect();
+    Ok(Json(ListSessionsRes { sessions }))
+}
+
+pub(crate) async fn session_logs(
+    State(s): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<String, Error> {
+    let session = s
+        .read()?
+        .get_session(&session_id)
+        .ok_or_else(|| anyhow::anyhow!("Session not found for session id: {:?}", &session_id))?;
+    Ok(session.logs)
+}
+
+pub(crate) async fn version() -> Json<VersionInfo> {
+    Json(VersionInfo {
+        risc0_zkvm: vec![risc0_zkvm::VERSION.to_string()],
+    })
+}
+
+pub(crate) async fn quotas(
+    State(s): State<AppState>,
+    Extension(prover_handle): Extension<ProverHandle>,
+) -> Result<Json<Quotas>, Error> {
+    let cycle_usage = s.read()?.cycle_usage();
+    Ok(Json(Quotas {
+        exec_cycle_limit: EXEC_CYCLE_LIMIT,
+        max_parallelism: prover_handle.workers as u64,
+        concurrent_proofs: prover_handle.workers as u64,
+        cycle_budget: CYCLE_BUDGET.saturating_sub(cycle_usage),
+        cycle_usage,
+    }))
 }
 
 pub(crate) async fn create_snark(
+    State(s): State<AppState>,
     Json(request): Json<SnarkReq>,
 ) -> Result<Json<CreateSessRes>, Error> {
+    s.read()?.get_session(&request.session_id).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Session not found for session id: {:?}",
+            &request.session_id
+        )
+    })?;
     Ok(Json(CreateSessRes {
         uuid: request.session_id,
     }))
@@ -128,18 +213,31 @@ pub(crate) async fn create_snark(
 
 pub(crate) async fn snark_status(
     State(s): State<AppState>,
+    Extension(prover_handle): Extension<ProverHandle>,
     Path(snark_id): Path<String>,
 ) -> Result<Json<SnarkStatusRes>, Error> {
     let storage = s.read()?;
-    storage
+    let session = storage
         .get_session(&snark_id)
         .ok_or_else(|| anyhow::anyhow!("Snark status not found for snark id: {:?}", &snark_id))?;
     let receipt = storage.get_receipt(&snark_id);
     match receipt {
+        // The mock cannot produce a Groth16 seal. Rather than pass off an empty
+        // seal as a proof of a real receipt, the SNARK fails.
+        Some(_) if prover_handle.proving => Ok(Json(SnarkStatusRes {
+            status: SessionStatus::Failed.to_string(),
+            output: None,
+            error_msg: Some("Local Bonsai cannot produce SNARK proofs".to_string()),
+        })),
         Some(bytes) => {
             let receipt: Receipt = bincode::deserialize(&bytes)?;
+            let post_state_digest = receipt
+                .get_metadata()
+                .map_err(|err| anyhow::anyhow!("Invalid receipt metadata: {err}"))?
+                .post
+                .digest();
             Ok(Json(SnarkStatusRes {
-                status: "SUCCEEDED".to_string(),
+                status: SessionStatus::Succeeded.to_string(),
                 output: Some(SnarkReceipt {
                     snark: Groth16Seal {
                         a: vec![],
@@ -147,16 +245,17 @@ pub(crate) async fn snark_status(
                         c: vec![],
                         public: vec![],
                     },
-                    post_state_digest: vec![],
+                    post_state_digest: post_state_digest.as_bytes().to_vec(),
                     journal: receipt.journal.bytes,
                 }),
                 error_msg: None,
             }))
         }
+        // Until there is a receipt, the SNARK shares the status of its session.
         None => Ok(Json(SnarkStatusRes {
-            status: "RUNNING".to_string(),
+            status: session.status.to_string(),
             output: None,
-            error_msg: None,
+            error_msg: session.error_msg,
         })),
     }
 }
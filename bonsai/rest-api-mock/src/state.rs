@@ -14,56 +14,207 @@
 
 use std::{
     collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
 };
 
+use anyhow::{bail, Context};
+use bonsai_sdk::alpha::responses::SessionStatus;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
 pub(crate) type AppState = Arc<RwLock<BonsaiState>>;
 
+/// The status of a proving session, as reported by `/sessions/status`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Session {
+    pub(crate) status: SessionStatus,
+    pub(crate) error_msg: Option<String>,
+    /// The stage of a `RUNNING` session.
+    pub(crate) state: Option<String>,
+    /// The guest's stdout and stderr, merged.
+    pub(crate) logs: String,
+    /// The total cycles of the session, once it has been executed.
+    pub(crate) cycles: u64,
+}
+
+impl Session {
+    pub(crate) fn running() -> Self {
+        Self {
+            status: SessionStatus::Running,
+            error_msg: None,
+            state: Some("Setup".to_string()),
+            logs: String::new(),
+            cycles: 0,
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct BonsaiState {
     pub(crate) local_url: String,
+    // Directory that the state is persisted to, if any
+    pub(crate) state_dir: Option<PathBuf>,
     // ImageID - MemoryImage
     pub(crate) images: HashMap<String, Vec<u8>>,
     // InputID - input
     pub(crate) inputs: HashMap<String, Vec<u8>>,
-    // SessionID - Status
-    pub(crate) sessions: HashMap<String, String>,
+    // SessionID - Session
+    pub(crate) sessions: HashMap<String, Session>,
     // SessionID - Receipts
     pub(crate) receipts: HashMap<String, Vec<u8>>,
 }
 
+const IMAGES_DIR: &str = "images";
+const INPUTS_DIR: &str = "inputs";
+const SESSIONS_DIR: &str = "sessions";
+const RECEIPTS_DIR: &str = "receipts";
+
 impl BonsaiState {
     pub(crate) fn new(local_address: String) -> Self {
         Self {
             local_url: local_address,
+            state_dir: None,
             images: HashMap::new(),
             inputs: HashMap::new(),
             sessions: HashMap::new(),
             receipts: HashMap::new(),
         }
     }
-    pub(crate) fn put_image(&mut self, image_id: String, image: Vec<u8>) -> Option<Vec<u8>> {
-        self.images.insert(image_id, image)
+
+    /// Load the state persisted in `state_dir`, creating it if needed. All
+    /// later changes are written through to `state_dir`.
+    ///
+    /// Sessions that were still running when the state was last written can
+    /// never finish, so they are marked as failed.
+    pub(crate) fn open(local_address: String, state_dir: &Path) -> anyhow::Result<Self> {
+        let mut state = Self::new(local_address);
+        state.images = load_dir(&state_dir.join(IMAGES_DIR))?;
+        state.inputs = load_dir(&state_dir.join(INPUTS_DIR))?;
+        state.receipts = load_dir(&state_dir.join(RECEIPTS_DIR))?;
+        for (session_id, session) in load_dir(&state_dir.join(SESSIONS_DIR))? {
+            let session = serde_json::from_slice(&session)
+                .with_context(|| format!("failed to decode session {session_id}"))?;
+            state.sessions.insert(session_id, session);
+        }
+        state.state_dir = Some(state_dir.to_path_buf());
+
+        let interrupted: Vec<_> = state
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.status == SessionStatus::Running)
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in interrupted {
+            state.update_session(&session_id, |session| {
+                session.status = SessionStatus::Failed;
+                session.state = None;
+                session.error_msg = Some("Session interrupted by a restart".to_string());
+            })?;
+        }
+
+        Ok(state)
+    }
+
+    pub(crate) fn put_image(&mut self, image_id: String, image: Vec<u8>) -> Result<(), Error> {
+        self.persist(IMAGES_DIR, &image_id, &image)?;
+        self.images.insert(image_id, image);
+        Ok(())
     }
     pub(crate) fn get_image(&self, image_id: impl AsRef<str>) -> Option<Vec<u8>> {
         self.images.get(image_id.as_ref()).cloned()
     }
-    pub(crate) fn put_input(&mut self, input_id: String, input: Vec<u8>) -> Option<Vec<u8>> {
-        self.inputs.insert(input_id, input)
+    pub(crate) fn put_input(&mut self, input_id: String, input: Vec<u8>) -> Result<(), Error> {
+        self.persist(INPUTS_DIR, &input_id, &input)?;
+        self.inputs.insert(input_id, input);
+        Ok(())
     }
     pub(crate) fn get_input(&self, input_id: impl AsRef<str>) -> Option<Vec<u8>> {
         self.inputs.get(input_id.as_ref()).cloned()
     }
-    pub(crate) fn put_session(&mut self, session_id: String, status: String) -> Option<String> {
-        self.sessions.insert(session_id, status)
+    pub(crate) fn put_session(
+        &mut self,
+        session_id: String,
+        session: Session,
+    ) -> Result<(), Error> {
+        self.persist(SESSIONS_DIR, &session_id, &serde_json::to_vec(&session)?)?;
+        self.sessions.insert(session_id, session);
+        Ok(())
     }
-    pub(crate) fn get_session(&self, session_id: impl AsRef<str>) -> Option<String> {
+    pub(crate) fn get_session(&self, session_id: impl AsRef<str>) -> Option<Session> {
         self.sessions.get(session_id.as_ref()).cloned()
     }
-    pub(crate) fn put_receipt(&mut self, session_id: String, receipt: Vec<u8>) -> Option<Vec<u8>> {
-        self.receipts.insert(session_id, receipt)
+    pub(crate) fn sessions(&self) -> impl Iterator<Item = (&String, &Session)> {
+        self.sessions.iter()
+    }
+    pub(crate) fn update_session(
+        &mut self,
+        session_id: &str,
+        update: impl FnOnce(&mut Session),
+    ) -> Result<(), Error> {
+        let mut session = self
+            .get_session(session_id)
+            .ok_or_else(|| anyhow::anyhow!("Session not found for session id: {session_id:?}"))?;
+        update(&mut session);
+        self.put_session(session_id.to_string(), session)
+    }
+    pub(crate) fn put_receipt(
+        &mut self,
+        session_id: String,
+        receipt: Vec<u8>,
+    ) -> Result<(), Error> {
+        self.persist(RECEIPTS_DIR, &session_id, &receipt)?;
+        self.receipts.insert(session_id, receipt);
+        Ok(())
     }
     pub(crate) fn get_receipt(&self, session_id: impl AsRef<str>) -> Option<Vec<u8>> {
         self.receipts.get(session_id.as_ref()).cloned()
     }
+    /// The total cycles executed by all sessions.
+    pub(crate) fn cycle_usage(&self) -> u64 {
+        self.sessions.values().map(|session| session.cycles).sum()
+    }
+
+    fn persist(&self, kind: &str, id: &str, data: &[u8]) -> Result<(), Error> {
+        let Some(state_dir) = self.state_dir.as_ref() else {
+            return Ok(());
+        };
+        // IDs come from request paths, so make sure they can't escape `state_dir`.
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            Err(anyhow::anyhow!("Invalid ID: {id:?}"))?;
+        }
+        let dir = state_dir.join(kind);
+        fs::create_dir_all(&dir)?;
+        // Write to a temporary file first, so that a crash never leaves a
+        // partially written entry behind.
+        let tmp_path = dir.join(format!(".{id}.tmp"));
+        fs::write(&tmp_path, data)?;
+        fs::rename(tmp_path, dir.join(id))?;
+        Ok(())
+    }
+}
+
+fn load_dir(dir: &Path) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+    let mut entries = HashMap::new();
+    if !dir.exists() {
+        return Ok(entries);
+    }
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let Some(id) = path.file_name().and_then(|name| name.to_str()) else {
+            bail!("Unexpected file in state directory: {}", path.display());
+        };
+        if id.starts_with('.') {
+            continue;
+        }
+        let data = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        entries.insert(id.to_string(), data);
+    }
+    Ok(entries)
 }
@@ -12,14 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    path::Path,
+    time::{Duration, Instant},
+};
 
 use reqwest::{blocking::Client as BlockingClient, header};
 use thiserror::Error;
 
 use self::responses::{
-    CreateSessRes, ImgUploadRes, ProofReq, Quotas, SessionStatusRes, SnarkReq, SnarkStatusRes,
-    UploadRes, VersionInfo,
+    CreateSessRes, ImgUploadRes, ListSessionsRes, ProofReq, Quotas, SessionInfo, SessionStatus,
+    SessionStatusRes, SnarkReq, SnarkStatusRes, UploadRes, VersionInfo,
 };
 use crate::{API_KEY_ENVVAR, API_KEY_HEADER, API_URL_ENVVAR, VERSION_HEADER};
 
@@ -44,10 +48,15 @@ pub enum SdkErr {
     /// Missing file
     #[error("failed to find file on disk")]
     FileNotFound(#[from] std::io::Error),
+    /// Session still running when waiting for it timed out
+    #[error("timed out waiting for session `{0}`")]
+    Timeout(String),
 }
 
 /// Collection of serialization object for the REST api
 pub mod responses {
+    use std::fmt;
+
     use serde::{Deserialize, Serialize};
 
     /// Response of a upload request
@@ -82,8 +91,65 @@ pub mod responses {
         pub input: String,
     }
 
+    /// Status of a proof request Session or a SNARK Session
+    #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum SessionStatus {
+        /// Queued or in progress
+        Running,
+        /// Completed, with its output available
+        Succeeded,
+        /// Failed, with the reason in the error message
+        Failed,
+        /// Exceeded its time limit
+        TimedOut,
+        /// Cancelled before it completed
+        Aborted,
+        /// A status this version of the SDK does not know about
+        #[serde(other)]
+        Unknown,
+    }
+
+    impl SessionStatus {
+        /// Returns the status as it is sent over the wire, e.g. `RUNNING`
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                SessionStatus::Running => "RUNNING",
+                SessionStatus::Succeeded => "SUCCEEDED",
+                SessionStatus::Failed => "FAILED",
+                SessionStatus::TimedOut => "TIMED_OUT",
+                SessionStatus::Aborted => "ABORTED",
+                SessionStatus::Unknown => "UNKNOWN",
+            }
+        }
+
+        /// Returns true once the Session is no longer running
+        pub fn is_done(&self) -> bool {
+            *self != SessionStatus::Running
+        }
+    }
+
+    impl From<&str> for SessionStatus {
+        fn from(status: &str) -> Self {
+            match status {
+                "RUNNING" => SessionStatus::Running,
+                "SUCCEEDED" => SessionStatus::Succeeded,
+                "FAILED" => SessionStatus::Failed,
+                "TIMED_OUT" => SessionStatus::TimedOut,
+                "ABORTED" => SessionStatus::Aborted,
+                _ => SessionStatus::Unknown,
+            }
+        }
+    }
+
+    impl fmt::Display for SessionStatus {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
     /// Session Status response
-    #[derive(Deserialize, Serialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub struct SessionStatusRes {
         /// Current status
         ///
@@ -114,6 +180,29 @@ pub mod responses {
         pub state: Option<String>,
     }
 
+    impl SessionStatusRes {
+        /// Returns [Self::status] as a [SessionStatus]
+        pub fn session_status(&self) -> SessionStatus {
+            self.status.as_str().into()
+        }
+    }
+
+    /// Summary of a Session, as listed by [super::Client::list_sessions]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+    pub struct SessionInfo {
+        /// Session UUID
+        pub uuid: String,
+        /// Current status
+        pub status: SessionStatus,
+    }
+
+    /// Session listing response
+    #[derive(Debug, Deserialize, Serialize)]
+    pub struct ListSessionsRes {
+        /// Sessions of the current user
+        pub sessions: Vec<SessionInfo>,
+    }
+
     /// Snark proof request object
     #[derive(Deserialize, Serialize)]
     pub struct SnarkReq {
@@ -155,7 +244,7 @@ pub mod responses {
     }
 
     /// Session Status response
-    #[derive(Deserialize, Serialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub struct SnarkStatusRes {
         /// Current status
         ///
@@ -172,15 +261,22 @@ pub mod responses {
         pub error_msg: Option<String>,
     }
 
+    impl SnarkStatusRes {
+        /// Returns [Self::status] as a [SessionStatus]
+        pub fn session_status(&self) -> SessionStatus {
+            self.status.as_str().into()
+        }
+    }
+
     /// Bonsai supported versions
-    #[derive(Deserialize, Serialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub struct VersionInfo {
         /// Supported versions of the risc0-zkvm crate
         pub risc0_zkvm: Vec<String>,
     }
 
     /// User quotas and cycle budgets
-    #[derive(Deserialize, Serialize)]
+    #[derive(Debug, Deserialize, Serialize)]
     pub struct Quotas {
         /// Executor cycle limit, in millions of cycles
         pub exec_cycle_limit: u64,
@@ -238,6 +334,45 @@ impl SessionId {
         }
         Ok(res.text()?)
     }
+
+    /// Cancels the Session
+    ///
+    /// A cancelled Session stops running and reports the `ABORTED` status.
+    pub fn cancel(&self, client: &Client) -> Result<(), SdkErr> {
+        let url = format!("{}/sessions/cancel/{}", client.url, self.uuid);
+        let res = client.client.post(url).send()?;
+
+        if !res.status().is_success() {
+            let body = res.text()?;
+            return Err(SdkErr::InternalServerErr(body));
+        }
+        Ok(())
+    }
+
+    /// Polls the status of the Session every `poll_interval` until it is no
+    /// longer running
+    ///
+    /// Returns [SdkErr::Timeout] if the Session is still running after
+    /// `timeout`.
+    pub fn wait(
+        &self,
+        client: &Client,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<SessionStatusRes, SdkErr> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let res = self.status(client)?;
+            if res.session_status().is_done() {
+                return Ok(res);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(SdkErr::Timeout(self.uuid.clone()));
+            }
+            std::thread::sleep(poll_interval.min(remaining));
+        }
+    }
 }
 
 /// Stark2Snark Session representation
@@ -462,6 +597,24 @@ impl Client {
         Ok(SessionId::new(res.uuid))
     }
 
+    /// Lists the Sessions of the current user
+    ///
+    /// If `status` is given, only Sessions with that status are listed.
+    pub fn list_sessions(&self, status: Option<SessionStatus>) -> Result<Vec<SessionInfo>, SdkErr> {
+        let mut req = self.client.get(format!("{}/sessions", self.url));
+        if let Some(status) = status {
+            req = req.query(&[("status", status.as_str())]);
+        }
+        let res = req.send()?;
+
+        if !res.status().is_success() {
+            let body = res.text()?;
+            return Err(SdkErr::InternalServerErr(body));
+        }
+
+        Ok(res.json::<ListSessionsRes>()?.sessions)
+    }
+
     // Utilities
 
     /// Download a given url to a buffer
@@ -719,7 +872,7 @@ mod tests {
         let uuid = Uuid::new_v4().to_string();
         let session_id = SessionId::new(uuid);
         let response = SessionStatusRes {
-            status: "RUNNING".to_string(),
+            status: SessionStatus::Running.to_string(),
             receipt_url: None,
             error_msg: None,
             state: None,
@@ -775,6 +928,149 @@ mod tests {
         create_mock.assert();
     }
 
+    #[test]
+    fn session_status_wire_format() {
+        let status: SessionStatus = serde_json::from_str("\"TIMED_OUT\"").unwrap();
+        assert_eq!(status, SessionStatus::TimedOut);
+        assert_eq!(status.to_string(), "TIMED_OUT");
+        assert_eq!(
+            serde_json::to_value(SessionStatus::Running).unwrap(),
+            serde_json::json!("RUNNING")
+        );
+
+        let status: SessionStatus = serde_json::from_str("\"PAUSED\"").unwrap();
+        assert_eq!(status, SessionStatus::Unknown);
+        assert_eq!(SessionStatus::from("TIMED_OUT"), SessionStatus::TimedOut);
+        assert_eq!(SessionStatus::from("PAUSED"), SessionStatus::Unknown);
+        assert!(status.is_done());
+        assert!(!SessionStatus::Running.is_done());
+    }
+
+    #[test]
+    fn session_cancel() {
+        let server = MockServer::start();
+
+        let uuid = Uuid::new_v4().to_string();
+        let session_id = SessionId::new(uuid);
+
+        let cancel_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/sessions/cancel/{}", session_id.uuid))
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        session_id.cancel(&client).unwrap();
+
+        cancel_mock.assert();
+    }
+
+    #[test]
+    fn session_wait() {
+        let server = MockServer::start();
+
+        let uuid = Uuid::new_v4().to_string();
+        let session_id = SessionId::new(uuid);
+        let response = SessionStatusRes {
+            status: SessionStatus::Succeeded.to_string(),
+            receipt_url: Some("https://example.com/receipt".to_string()),
+            error_msg: None,
+            state: None,
+        };
+
+        let status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&response);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let res = session_id
+            .wait(&client, Duration::from_secs(10), Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(res.session_status(), SessionStatus::Succeeded);
+        assert_eq!(res.receipt_url, response.receipt_url);
+
+        status_mock.assert_hits(1);
+    }
+
+    #[test]
+    fn session_wait_timeout() {
+        let server = MockServer::start();
+
+        let uuid = Uuid::new_v4().to_string();
+        let session_id = SessionId::new(uuid);
+        let response = SessionStatusRes {
+            status: SessionStatus::Running.to_string(),
+            receipt_url: None,
+            error_msg: None,
+            state: Some("Executor".to_string()),
+        };
+
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&response);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let err = session_id
+            .wait(
+                &client,
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+            )
+            .unwrap_err();
+        assert!(matches!(err, SdkErr::Timeout(uuid) if uuid == session_id.uuid));
+    }
+
+    #[test]
+    fn session_list() {
+        let server = MockServer::start();
+
+        let response = ListSessionsRes {
+            sessions: vec![SessionInfo {
+                uuid: Uuid::new_v4().to_string(),
+                status: SessionStatus::Running,
+            }],
+        };
+
+        let list_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/sessions")
+                .query_param("status", "RUNNING")
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&response);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let sessions = client.list_sessions(Some(SessionStatus::Running)).unwrap();
+        assert_eq!(sessions, response.sessions);
+
+        list_mock.assert();
+    }
+
     #[test]
     fn snark_create() {
         let server = MockServer::start();
@@ -815,7 +1111,7 @@ mod tests {
         let uuid = Uuid::new_v4().to_string();
         let snark_id = SnarkId::new(uuid);
         let response = SnarkStatusRes {
-            status: "RUNNING".to_string(),
+            status: SessionStatus::Running.to_string(),
             output: None,
             error_msg: None,
         };
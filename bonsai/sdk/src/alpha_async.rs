@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Async wrappers of the blocking [crate::alpha::Client], each running on a
+//! blocking thread. See [crate::non_blocking] for a natively async client.
+
 use crate::alpha::{
     responses::{Quotas, SessionStatusRes, SnarkStatusRes},
     Client, SdkErr, SessionId, SnarkId,
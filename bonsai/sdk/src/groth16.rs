@@ -0,0 +1,441 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Off-chain verification of Bonsai SNARK receipts.
+//!
+//! This is a pure-Rust port of the on-chain `RiscZeroGroth16Verifier`
+//! contract: it uses the same BN254 verifying key and control ID, and derives
+//! the public inputs from the receipt metadata the same way, so a
+//! [SnarkReceipt] that passes here will also be accepted on Ethereum.
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInt, MontFp, PrimeField, Zero};
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+
+use crate::alpha::responses::{Groth16Seal, SnarkReceipt};
+
+/// Low 128 bits of the control ID of the `identity_p254` recursion program,
+/// split as in `RiscZeroGroth16Verifier.splitDigest`.
+pub const CONTROL_ID_0: u128 = 0x68e42d8b3ddc499f4e1799a767052ab3;
+/// High 128 bits of the control ID of the `identity_p254` recursion program.
+pub const CONTROL_ID_1: u128 = 0x3802684f1645e0a028585b0445d39231;
+
+/// Control ID of the recursion program that a SNARK attests to.
+///
+/// A `RiscZeroGroth16Verifier` contract is deployed with this as its
+/// `CONTROL_ID_0` and `CONTROL_ID_1`, so a verifier deployed for another
+/// version of the recursion circuit needs a matching [ControlId] here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ControlId {
+    /// Low 128 bits of the control ID
+    pub id_0: u128,
+    /// High 128 bits of the control ID
+    pub id_1: u128,
+}
+
+impl Default for ControlId {
+    /// The control ID in the verifier contracts of this release.
+    fn default() -> Self {
+        Self {
+            id_0: CONTROL_ID_0,
+            id_1: CONTROL_ID_1,
+        }
+    }
+}
+
+/// Groth16 verification errors
+#[derive(Debug, Error, PartialEq)]
+pub enum Groth16Error {
+    /// The seal does not have the snarkjs calldata shape
+    #[error("malformed Groth16 seal")]
+    MalformedSeal,
+    /// A seal coordinate is not a valid BN254 base field element
+    #[error("invalid field element in Groth16 seal")]
+    InvalidFieldElement,
+    /// A seal point is not on the curve or not in the prime order subgroup
+    #[error("invalid curve point in Groth16 seal")]
+    InvalidPoint,
+    /// A digest is not 32 bytes long
+    #[error("invalid digest length: {0}")]
+    InvalidDigest(usize),
+    /// The pairing check failed
+    #[error("Groth16 verification failed")]
+    VerificationFailed,
+}
+
+/// A Groth16 verifying key over BN254.
+struct VerifyingKey {
+    alpha_g1: G1Affine,
+    beta_g2: G2Affine,
+    gamma_g2: G2Affine,
+    delta_g2: G2Affine,
+    ic: [G1Affine; 5],
+}
+
+// Verifying key of `contracts/groth16/Groth16Verifier.sol`. G2 coordinates
+// there are stored as (c1, c0), following the EVM precompile encoding.
+fn verifying_key() -> VerifyingKey {
+    const fn g1(x: Fq, y: Fq) -> G1Affine {
+        G1Affine::new_unchecked(x, y)
+    }
+
+    const fn g2(x1: Fq, x2: Fq, y1: Fq, y2: Fq) -> G2Affine {
+        G2Affine::new_unchecked(Fq2::new(x2, x1), Fq2::new(y2, y1))
+    }
+
+    VerifyingKey {
+        alpha_g1: g1(
+            MontFp!(
+                "20491192805390485299153009773594534940189261866228447918068658471970481763042"
+            ),
+            MontFp!("9383485363053290200918347156157836566562967994039712273449902621266178545958"),
+        ),
+        beta_g2: g2(
+            MontFp!("4252822878758300859123897981450591353533073413197771768651442665752259397132"),
+            MontFp!("6375614351688725206403948262868962793625744043794305715222011528459656738731"),
+            MontFp!(
+                "21847035105528745403288232691147584728191162732299865338377159692350059136679"
+            ),
+            MontFp!(
+                "10505242626370262277552901082094356697409835680220590971873171140371331206856"
+            ),
+        ),
+        gamma_g2: g2(
+            MontFp!(
+                "11559732032986387107991004021392285783925812861821192530917403151452391805634"
+            ),
+            MontFp!(
+                "10857046999023057135944570762232829481370756359578518086990519993285655852781"
+            ),
+            MontFp!("4082367875863433681332203403145435568316851327593401208105741076214120093531"),
+            MontFp!("8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+        ),
+        delta_g2: g2(
+            MontFp!(
+                "18518940221910320856687047018635785128750837022059566906616608708313475199865"
+            ),
+            MontFp!("9492326610711013918333865133991413442330971822743127449106067493230447878125"),
+            MontFp!(
+                "19483644759748826533215810634368877792922012485854314246298395665859158607201"
+            ),
+            MontFp!(
+                "21375251776817431660251933179512026180139877181625068362970095925425149918084"
+            ),
+        ),
+        ic: [
+            g1(
+                MontFp!(
+                    "5283414572476013565779278723585415063371186194506872223482170607932178811733"
+                ),
+                MontFp!(
+                    "18704069070102836155408936676819275373965966640372164023392964533091458933020"
+                ),
+            ),
+            g1(
+                MontFp!(
+                    "4204832149120840018317309580010992142700029278901617154852760187580780425598"
+                ),
+                MontFp!(
+                    "12454324579480242399557363837918019584959512625719173397955145140913291575910"
+                ),
+            ),
+            g1(
+                MontFp!(
+                    "14956117485756386823219519866025248834283088288522682527835557402788427995664"
+                ),
+                MontFp!(
+                    "6968527870554016879785099818512699922114301060378071349626144898778340839382"
+                ),
+            ),
+            g1(
+                MontFp!(
+                    "6512168907754184210144919576616764035747139382744482291187821746087116094329"
+                ),
+                MontFp!(
+                    "17156131719875889332084290091263207055049222677188492681713268727972722760739"
+                ),
+            ),
+            g1(
+                MontFp!(
+                    "5195346330747727606774560791771406703229046454464300598774280139349802276261"
+                ),
+                MontFp!(
+                    "16279160127031959334335024858510026085227931356896384961436876214395869945425"
+                ),
+            ),
+        ],
+    }
+}
+
+/// Compute the digest of the receipt metadata for a guest that halted with
+/// exit code 0 and committed no input, as `ReceiptMetadataLib.digest` does.
+pub fn metadata_digest(
+    image_id: &[u8; 32],
+    post_state_digest: &[u8; 32],
+    journal_digest: &[u8; 32],
+) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(Sha256::digest(b"risc0.ReceiptMeta"))
+        // down
+        .chain_update([0u8; 32])
+        .chain_update(image_id)
+        .chain_update(post_state_digest)
+        .chain_update(journal_digest)
+        // data: ExitCode(Halted, 0)
+        .chain_update(0u32.to_le_bytes())
+        .chain_update(0u32.to_le_bytes())
+        // down.length
+        .chain_update(4u16.to_le_bytes())
+        .finalize()
+        .into()
+}
+
+/// Split a digest into the two 128-bit public inputs expected by the circuit.
+fn split_digest(digest: &[u8; 32]) -> [Fr; 2] {
+    let (lo, hi) = digest.split_at(16);
+    [
+        Fr::from(u128::from_le_bytes(lo.try_into().unwrap())),
+        Fr::from(u128::from_le_bytes(hi.try_into().unwrap())),
+    ]
+}
+
+fn decode_fq(bytes: &[u8]) -> Result<Fq, Groth16Error> {
+    if bytes.len() > 32 {
+        return Err(Groth16Error::InvalidFieldElement);
+    }
+    let mut be = [0u8; 32];
+    be[32 - bytes.len()..].copy_from_slice(bytes);
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(be.rchunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    Fq::from_bigint(BigInt(limbs)).ok_or(Groth16Error::InvalidFieldElement)
+}
+
+fn decode_pair(pair: &[Vec<u8>]) -> Result<(Fq, Fq), Groth16Error> {
+    match pair {
+        [x, y] => Ok((decode_fq(x)?, decode_fq(y)?)),
+        _ => Err(Groth16Error::MalformedSeal),
+    }
+}
+
+fn decode_g1(pair: &[Vec<u8>]) -> Result<G1Affine, Groth16Error> {
+    let (x, y) = decode_pair(pair)?;
+    // The EVM precompiles encode the point at infinity as (0, 0).
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Groth16Error::InvalidPoint);
+    }
+    Ok(point)
+}
+
+fn decode_g2(pairs: &[Vec<Vec<u8>>]) -> Result<G2Affine, Groth16Error> {
+    let [x, y] = pairs else {
+        return Err(Groth16Error::MalformedSeal);
+    };
+    let (x1, x0) = decode_pair(x)?;
+    let (y1, y0) = decode_pair(y)?;
+    let (x, y) = (Fq2::new(x0, x1), Fq2::new(y0, y1));
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Groth16Error::InvalidPoint);
+    }
+    Ok(point)
+}
+
+/// Verify a Groth16 seal for the given control ID against the given image
+/// ID, post-state digest and journal digest.
+///
+/// The public inputs are derived from the arguments, the `public` field of
+/// the seal is not used.
+pub fn verify(
+    seal: &Groth16Seal,
+    control_id: &ControlId,
+    image_id: &[u8; 32],
+    post_state_digest: &[u8; 32],
+    journal_digest: &[u8; 32],
+) -> Result<(), Groth16Error> {
+    let a = decode_g1(&seal.a)?;
+    let b = decode_g2(&seal.b)?;
+    let c = decode_g1(&seal.c)?;
+
+    let [meta0, meta1] = split_digest(&metadata_digest(
+        image_id,
+        post_state_digest,
+        journal_digest,
+    ));
+    let public = [
+        Fr::from(control_id.id_0),
+        Fr::from(control_id.id_1),
+        meta0,
+        meta1,
+    ];
+
+    let vk = verifying_key();
+    let vk_x = public
+        .iter()
+        .zip(&vk.ic[1..])
+        .fold(G1Projective::from(vk.ic[0]), |acc, (input, ic)| {
+            acc + ic.mul_bigint(input.into_bigint())
+        });
+
+    let check = Bn254::multi_pairing(
+        [-a, vk.alpha_g1, vk_x.into_affine(), c],
+        [b, vk.beta_g2, vk.gamma_g2, vk.delta_g2],
+    );
+    if !check.is_zero() {
+        return Err(Groth16Error::VerificationFailed);
+    }
+    Ok(())
+}
+
+impl SnarkReceipt {
+    /// Verify this receipt was produced by the guest with the given image ID.
+    ///
+    /// Checks the SNARK against the receipt's own post-state digest and
+    /// journal, exactly as `RiscZeroGroth16Verifier.verify` would on-chain.
+    pub fn verify(&self, image_id: &[u8; 32]) -> Result<(), Groth16Error> {
+        self.verify_with_control_id(&ControlId::default(), image_id)
+    }
+
+    /// Verify this receipt as [Self::verify] does, for a verifier contract
+    /// deployed with the given control ID.
+    pub fn verify_with_control_id(
+        &self,
+        control_id: &ControlId,
+        image_id: &[u8; 32],
+    ) -> Result<(), Groth16Error> {
+        let post_state_digest: &[u8; 32] = self
+            .post_state_digest
+            .as_slice()
+            .try_into()
+            .map_err(|_| Groth16Error::InvalidDigest(self.post_state_digest.len()))?;
+        let journal_digest: [u8; 32] = Sha256::digest(&self.journal).into();
+        verify(
+            &self.snark,
+            control_id,
+            image_id,
+            post_state_digest,
+            &journal_digest,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Known-good receipt from `test/RiscZeroGroth16Verifier.t.sol`.
+    const TEST_SEAL: &str = "10f8f660f2c27383dd333b53f04c041e48bf30522ab60765ff5b20d3926a6d772e2ff16d91b26c69240797f55711539392896b57418a53d94185e588548c7c5d00d42ba7fda15337125236856174dec47bf5284719df3ffb454f5bd517d9f4f62dfbd8718c356e3f5aeca944158c8cf659840b6306e496de267d393d78b9899526573eeeb2a8d12320ffb35b9d4273ca89ea7efb986ace50c0011d2d82ed152e2428ea88a9f7f99f629505673cdc2dee73391c010705045b947f186b2a92310409cce5ef234e49f80fc88914e94dcfdc88f86e2f643829cb18b890dd1e7a038c1a7d2e2b5123df062c2a6d71077714caeda56467a5df01c4a0b1a30f1bcd0709";
+    const TEST_IMAGE_ID: &str = "1350c208ff5d21a71766e136b2acd5819765e70ed263c8e99c1367d8b06bd19e";
+    const TEST_POST_STATE_DIGEST: &str =
+        "0d39fb9a2f18526100657516c42a64d58864d4a9602dc3e322f3d74626217323";
+    const TEST_JOURNAL: &str = "5818100a2105c60d4f73044fe09a9cb0ba9801a4f5775e79cbb8934b23caab65fc78119308df16cf37c04f2bc4fca8e041425d654ce274515bbdfbea1f9070f000000001c48217f74c7707ba564c32cd3db0abcd2057a41e00000001ed28d58ebad0ccb2bccf71425d785388b9914029";
+
+    fn test_receipt() -> (SnarkReceipt, [u8; 32]) {
+        let words: Vec<Vec<u8>> = hex::decode(TEST_SEAL)
+            .unwrap()
+            .chunks(32)
+            .map(<[u8]>::to_vec)
+            .collect();
+        let snark = Groth16Seal {
+            a: words[0..2].to_vec(),
+            b: vec![words[2..4].to_vec(), words[4..6].to_vec()],
+            c: words[6..8].to_vec(),
+            public: vec![],
+        };
+        let receipt = SnarkReceipt {
+            snark,
+            post_state_digest: hex::decode(TEST_POST_STATE_DIGEST).unwrap(),
+            journal: hex::decode(TEST_JOURNAL).unwrap(),
+        };
+        let image_id = hex::decode(TEST_IMAGE_ID).unwrap().try_into().unwrap();
+        (receipt, image_id)
+    }
+
+    #[test]
+    fn verifying_key_points() {
+        let vk = verifying_key();
+        for point in [vk.alpha_g1].iter().chain(vk.ic.iter()) {
+            assert!(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve());
+        }
+        for point in [vk.beta_g2, vk.gamma_g2, vk.delta_g2] {
+            assert!(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve());
+        }
+    }
+
+    #[test]
+    fn verify_known_good_receipt() {
+        let (receipt, image_id) = test_receipt();
+        receipt.verify(&image_id).unwrap();
+    }
+
+    #[test]
+    fn verify_mangled_receipts() {
+        let (receipt, image_id) = test_receipt();
+
+        let mut mangled_id = image_id;
+        mangled_id[0] ^= 1;
+        assert_eq!(
+            receipt.verify(&mangled_id),
+            Err(Groth16Error::VerificationFailed)
+        );
+
+        let (mut mangled, _) = test_receipt();
+        mangled.post_state_digest[31] ^= 1;
+        assert_eq!(
+            mangled.verify(&image_id),
+            Err(Groth16Error::VerificationFailed)
+        );
+
+        let (mut mangled, _) = test_receipt();
+        mangled.journal[0] ^= 1;
+        assert_eq!(
+            mangled.verify(&image_id),
+            Err(Groth16Error::VerificationFailed)
+        );
+
+        let (mut mangled, _) = test_receipt();
+        mangled.snark.c.swap(0, 1);
+        assert!(mangled.verify(&image_id).is_err());
+
+        let (mut mangled, _) = test_receipt();
+        mangled.snark.b.pop();
+        assert_eq!(mangled.verify(&image_id), Err(Groth16Error::MalformedSeal));
+
+        let control_id = ControlId {
+            id_0: CONTROL_ID_0 ^ 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            receipt.verify_with_control_id(&control_id, &image_id),
+            Err(Groth16Error::VerificationFailed)
+        );
+
+        let (mut mangled, _) = test_receipt();
+        mangled.post_state_digest.pop();
+        assert_eq!(
+            mangled.verify(&image_id),
+            Err(Groth16Error::InvalidDigest(31))
+        );
+    }
+}
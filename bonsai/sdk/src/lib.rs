@@ -20,6 +20,10 @@ pub mod alpha;
 #[cfg(feature = "async")]
 /// Bonsai Alpha SDK async
 pub mod alpha_async;
+#[cfg(feature = "async")]
+pub mod non_blocking;
+#[cfg(feature = "groth16")]
+pub mod groth16;
 
 /// HTTP header key for the API key
 pub const API_KEY_HEADER: &str = "x-api-key";
@@ -0,0 +1,583 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A natively async client of the Bonsai Alpha REST api.
+//!
+//! Unlike [crate::alpha_async], which runs the blocking [crate::alpha::Client]
+//! on a blocking thread, requests are made with an async [reqwest::Client].
+//! Idempotent requests that fail with a transient error are retried with
+//! exponential backoff, according to the [RetryPolicy] of the [Client].
+
+use std::{path::Path, time::Duration};
+
+use reqwest::{header, Body, Method, RequestBuilder, Response, StatusCode};
+use tokio::time::Instant;
+
+pub use crate::alpha::{responses, SdkErr};
+use crate::{
+    alpha::responses::{
+        CreateSessRes, ImgUploadRes, ListSessionsRes, ProofReq, Quotas, SessionInfo, SessionStatus,
+        SessionStatusRes, SnarkReq, SnarkStatusRes, UploadRes, VersionInfo,
+    },
+    API_KEY_ENVVAR, API_KEY_HEADER, API_URL_ENVVAR, VERSION_HEADER,
+};
+
+/// How a [Client] retries requests that fail with a transient error
+///
+/// Only `GET`, `HEAD` and `PUT` requests are retried: repeating a `POST` such
+/// as `/sessions/create` after a timeout could start a duplicate session.
+///
+/// Connection failures, timeouts and the `429`, `502`, `503` and `504` HTTP
+/// statuses are transient. The delay before each retry starts at
+/// `initial_backoff` and doubles after every attempt, up to `max_backoff`.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Number of times a request is retried before its error is returned
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// A [RetryPolicy] that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Return a [RetryPolicy] that retries up to 3 times, starting with a delay
+    /// of 500ms and waiting at most 8s between retries.
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+fn is_transient_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT)
+}
+
+/// Proof Session representation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionId {
+    /// Session UUID
+    pub uuid: String,
+}
+
+impl SessionId {
+    /// Construct a [SessionId] from a UUID [String]
+    pub fn new(uuid: String) -> Self {
+        Self { uuid }
+    }
+
+    /// Fetches the current status of the Session
+    pub async fn status(&self, client: &Client) -> Result<SessionStatusRes, SdkErr> {
+        let url = format!("{}/sessions/status/{}", client.url, self.uuid);
+        let res = client.send(client.client.get(url)).await?;
+        Ok(res.json::<SessionStatusRes>().await?)
+    }
+
+    /// Fetches the zkvm guest logs for a session
+    ///
+    /// See [crate::alpha::SessionId::logs].
+    pub async fn logs(&self, client: &Client) -> Result<String, SdkErr> {
+        let url = format!("{}/sessions/logs/{}", client.url, self.uuid);
+        let res = client.send(client.client.get(url)).await?;
+        Ok(res.text().await?)
+    }
+
+    /// Cancels the Session
+    ///
+    /// A cancelled Session stops running and reports the
+    /// [SessionStatus::Aborted] status.
+    pub async fn cancel(&self, client: &Client) -> Result<(), SdkErr> {
+        let url = format!("{}/sessions/cancel/{}", client.url, self.uuid);
+        client.send(client.client.post(url)).await?;
+        Ok(())
+    }
+
+    /// Polls the status of the Session every `poll_interval` until it is no
+    /// longer running
+    ///
+    /// Returns [SdkErr::Timeout] if the Session is still running after
+    /// `timeout`.
+    pub async fn wait(
+        &self,
+        client: &Client,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<SessionStatusRes, SdkErr> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let res = self.status(client).await?;
+            if res.session_status().is_done() {
+                return Ok(res);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(SdkErr::Timeout(self.uuid.clone()));
+            }
+            tokio::time::sleep(poll_interval.min(remaining)).await;
+        }
+    }
+}
+
+/// Stark2Snark Session representation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnarkId {
+    /// Snark Session UUID
+    pub uuid: String,
+}
+
+impl SnarkId {
+    /// Construct a [SnarkId] from a UUID [String]
+    pub fn new(uuid: String) -> Self {
+        Self { uuid }
+    }
+
+    /// Fetches the current status of the Snark Session
+    pub async fn status(&self, client: &Client) -> Result<SnarkStatusRes, SdkErr> {
+        let url = format!("{}/snark/status/{}", client.url, self.uuid);
+        let res = client.send(client.client.get(url)).await?;
+        Ok(res.json::<SnarkStatusRes>().await?)
+    }
+}
+
+/// Represents an async client of the REST api
+#[derive(Clone)]
+pub struct Client {
+    pub(crate) url: String,
+    pub(crate) client: reqwest::Client,
+    pub(crate) retry_policy: RetryPolicy,
+}
+
+enum ImageExistsOpt {
+    Exists,
+    New(ImgUploadRes),
+}
+
+/// Creates a [reqwest::Client] for internal connection pooling
+fn construct_req_client(api_key: &str, version: &str) -> Result<reqwest::Client, SdkErr> {
+    let mut headers = header::HeaderMap::new();
+    headers.insert(API_KEY_HEADER, header::HeaderValue::from_str(api_key)?);
+    headers.insert(VERSION_HEADER, header::HeaderValue::from_str(version)?);
+
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .pool_max_idle_per_host(0)
+        .build()?)
+}
+
+impl Client {
+    /// Construct a [Client] from env vars
+    ///
+    /// Uses the BONSAI_API_URL and BONSAI_API_KEY environment variables to
+    /// construct a client. The risc0_version should be the crate version of the
+    /// risc0-zkvm crate
+    pub fn from_env(risc0_version: &str) -> Result<Self, SdkErr> {
+        let api_url = std::env::var(API_URL_ENVVAR).map_err(|_| SdkErr::MissingApiUrl)?;
+        let api_key = std::env::var(API_KEY_ENVVAR).map_err(|_| SdkErr::MissingApiKey)?;
+        Self::from_parts(api_url, api_key, risc0_version)
+    }
+
+    /// Construct a [Client] from url, api key, and zkvm version
+    pub fn from_parts(url: String, key: String, risc0_version: &str) -> Result<Self, SdkErr> {
+        let client = construct_req_client(&key, risc0_version)?;
+        let url = url.strip_suffix('/').unwrap_or(&url).to_string();
+        Ok(Self {
+            url,
+            client,
+            retry_policy: RetryPolicy::default(),
+        })
+    }
+
+    /// Replace the [RetryPolicy] of this [Client]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Send a request, retrying it on transient errors
+    ///
+    /// Requests that are not idempotent or have a body that cannot be cloned
+    /// are sent once.
+    async fn send(&self, req: RequestBuilder) -> Result<Response, SdkErr> {
+        let req = req.build()?;
+        let retryable = is_idempotent(req.method());
+        let mut attempt = 0;
+        loop {
+            let retry = match req.try_clone() {
+                Some(retry) if retryable && attempt < self.retry_policy.max_retries => retry,
+                _ => return check_status(self.client.execute(req).await?).await,
+            };
+            match self.client.execute(retry).await {
+                Ok(res) if !is_transient_status(res.status()) => return check_status(res).await,
+                Err(err) if !is_transient_error(&err) => return Err(err.into()),
+                _ => {}
+            }
+            tokio::time::sleep(self.retry_policy.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Fetch a upload presigned url for a given route
+    async fn get_upload_url(&self, route: &str) -> Result<UploadRes, SdkErr> {
+        let req = self.client.get(format!("{}/{}/upload", self.url, route));
+        Ok(self.send(req).await?.json::<UploadRes>().await?)
+    }
+
+    async fn get_image_upload_url(&self, image_id: &str) -> Result<ImageExistsOpt, SdkErr> {
+        let req = self
+            .client
+            .get(format!("{}/images/upload/{}", self.url, image_id));
+        let res = self.send(req).await?;
+
+        if res.status() == 204 {
+            return Ok(ImageExistsOpt::Exists);
+        }
+
+        Ok(ImageExistsOpt::New(res.json::<ImgUploadRes>().await?))
+    }
+
+    /// Upload body to a given URL
+    async fn put_data<T: Into<Body>>(&self, url: &str, body: T) -> Result<(), SdkErr> {
+        self.send(self.client.put(url).body(body)).await?;
+        Ok(())
+    }
+
+    // - /images
+
+    /// Upload a image buffer to the /images/ route
+    ///
+    /// The boolean return indicates if the image already exists in bonsai
+    ///
+    /// The image data can be either:
+    /// * ELF file bytes
+    /// * bincode encoded MemoryImage
+    pub async fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<bool, SdkErr> {
+        match self.get_image_upload_url(image_id).await? {
+            ImageExistsOpt::Exists => Ok(true),
+            ImageExistsOpt::New(upload_res) => {
+                self.put_data(&upload_res.url, buf).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Upload a image file to the /images/ route
+    ///
+    /// The boolean return indicates if the image already exists in bonsai
+    ///
+    /// The image data can be either:
+    /// * ELF file bytes
+    /// * bincode encoded MemoryImage
+    pub async fn upload_img_file(&self, image_id: &str, path: &Path) -> Result<bool, SdkErr> {
+        let buf = tokio::fs::read(path).await?;
+        self.upload_img(image_id, buf).await
+    }
+
+    // - /inputs
+
+    /// Upload a input buffer to the /inputs/ route
+    pub async fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        let upload_data = self.get_upload_url("inputs").await?;
+        self.put_data(&upload_data.url, buf).await?;
+        Ok(upload_data.uuid)
+    }
+
+    /// Upload a input file to the /inputs/ route
+    pub async fn upload_input_file(&self, path: &Path) -> Result<String, SdkErr> {
+        let buf = tokio::fs::read(path).await?;
+        self.upload_input(buf).await
+    }
+
+    // - /sessions
+
+    /// Create a new proof request Session
+    ///
+    /// Supply the image_id and input_id created from uploading those files in
+    /// previous steps
+    pub async fn create_session(
+        &self,
+        img_id: String,
+        input_id: String,
+    ) -> Result<SessionId, SdkErr> {
+        let url = format!("{}/sessions/create", self.url);
+
+        let req = ProofReq {
+            img: img_id,
+            input: input_id,
+        };
+
+        let res = self.send(self.client.post(url).json(&req)).await?;
+        let res: CreateSessRes = res.json().await?;
+
+        Ok(SessionId::new(res.uuid))
+    }
+
+    /// Lists the Sessions of the current user
+    ///
+    /// If `status` is given, only Sessions with that status are listed.
+    pub async fn list_sessions(
+        &self,
+        status: Option<SessionStatus>,
+    ) -> Result<Vec<SessionInfo>, SdkErr> {
+        let mut req = self.client.get(format!("{}/sessions", self.url));
+        if let Some(status) = status {
+            req = req.query(&[("status", status.as_str())]);
+        }
+        let res = self.send(req).await?;
+        Ok(res.json::<ListSessionsRes>().await?.sessions)
+    }
+
+    // Utilities
+
+    /// Download a given url to a buffer
+    ///
+    /// Useful to download a [SessionId] receipt_url
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
+        let res = self.send(self.client.get(url)).await?;
+        Ok(res.bytes().await?.into())
+    }
+
+    // - /snark
+
+    /// Requests a SNARK proof be created from a existing sessionId
+    ///
+    /// Supply a completed sessionId to convert the risc0 STARK proof into
+    /// a SNARK proof that can be validated on ethereum-like blockchains
+    pub async fn create_snark(&self, session_id: String) -> Result<SnarkId, SdkErr> {
+        let url = format!("{}/snark/create", self.url);
+
+        let snark_req = SnarkReq { session_id };
+
+        let res = self.send(self.client.post(url).json(&snark_req)).await?;
+
+        // Reuse the session response because its the same member format
+        let res: CreateSessRes = res.json().await?;
+
+        Ok(SnarkId::new(res.uuid))
+    }
+
+    // - /version
+
+    /// Fetches the current component versions from bonsai
+    ///
+    /// Fetches the risc0 zkvm supported versions as well as other
+    /// sub-components of bonsai
+    pub async fn version(&self) -> Result<VersionInfo, SdkErr> {
+        let req = self.client.get(format!("{}/version", self.url));
+        Ok(self.send(req).await?.json::<VersionInfo>().await?)
+    }
+
+    // - /user
+
+    /// Fetches your current users quotas
+    ///
+    /// Returns the [Quotas] structure with relevant data on cycle budget, quotas etc.
+    pub async fn quotas(&self) -> Result<Quotas, SdkErr> {
+        let req = self.client.get(format!("{}/user/quotas", self.url));
+        Ok(self.send(req).await?.json::<Quotas>().await?)
+    }
+}
+
+/// Turn an unsuccessful response into an [SdkErr]
+async fn check_status(res: Response) -> Result<Response, SdkErr> {
+    if !res.status().is_success() {
+        let body = res.text().await?;
+        return Err(SdkErr::InternalServerErr(body));
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::prelude::*;
+    use uuid::Uuid;
+
+    use super::*;
+
+    const TEST_KEY: &str = "TESTKEY";
+    const TEST_VERSION: &str = "0.1.0";
+
+    fn test_client(server: &MockServer) -> Client {
+        let server_url = format!("http://{}", server.address());
+        Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_retries: 2,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(2),
+            })
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(10), Duration::from_secs(8));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(8));
+    }
+
+    #[tokio::test]
+    async fn session_status() {
+        let server = MockServer::start_async().await;
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let response = SessionStatusRes {
+            status: SessionStatus::Failed.to_string(),
+            receipt_url: None,
+            error_msg: Some("guest panicked".to_string()),
+            state: None,
+        };
+
+        let status_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/sessions/status/{}", session_id.uuid))
+                    .header(API_KEY_HEADER, TEST_KEY)
+                    .header(VERSION_HEADER, TEST_VERSION);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&response);
+            })
+            .await;
+
+        let status = session_id.status(&test_client(&server)).await.unwrap();
+        assert_eq!(status.session_status(), SessionStatus::Failed);
+        assert_eq!(status.error_msg, response.error_msg);
+
+        status_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let server = MockServer::start_async().await;
+
+        let unavailable_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/version");
+                then.status(503).body("unavailable");
+            })
+            .await;
+
+        let err = test_client(&server).version().await.unwrap_err();
+        assert!(matches!(err, SdkErr::InternalServerErr(body) if body == "unavailable"));
+
+        // The first attempt and two retries.
+        unavailable_mock.assert_hits_async(3).await;
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let server = MockServer::start_async().await;
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let not_found_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path(format!("/sessions/cancel/{}", session_id.uuid));
+                then.status(404).body("session not found");
+            })
+            .await;
+
+        let err = session_id.cancel(&test_client(&server)).await.unwrap_err();
+        assert!(matches!(err, SdkErr::InternalServerErr(body) if body == "session not found"));
+
+        not_found_mock.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_post() {
+        let server = MockServer::start_async().await;
+
+        let unavailable_mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/sessions/create");
+                then.status(503).body("unavailable");
+            })
+            .await;
+
+        let err = test_client(&server)
+            .create_session("image".to_string(), "input".to_string())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkErr::InternalServerErr(body) if body == "unavailable"));
+
+        unavailable_mock.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    async fn session_wait_timeout() {
+        let server = MockServer::start_async().await;
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let response = SessionStatusRes {
+            status: SessionStatus::Running.to_string(),
+            receipt_url: None,
+            error_msg: None,
+            state: Some("Executor".to_string()),
+        };
+
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/sessions/status/{}", session_id.uuid));
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&response);
+            })
+            .await;
+
+        let err = session_id
+            .wait(
+                &test_client(&server),
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkErr::Timeout(uuid) if uuid == session_id.uuid));
+    }
+}
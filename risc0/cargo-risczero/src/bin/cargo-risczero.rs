@@ -30,6 +30,16 @@ fn main() -> Result<()> {
         RisczeroCmd::BuildToolchain(cmd) => cmd.run(),
         RisczeroCmd::Install(cmd) => cmd.run(),
         RisczeroCmd::New(cmd) => cmd.run(),
+        #[cfg(feature = "zkvm")]
+        RisczeroCmd::Execute(cmd) => cmd.run(),
+        #[cfg(feature = "zkvm")]
+        RisczeroCmd::Prove(cmd) => cmd.run(),
+        #[cfg(feature = "zkvm")]
+        RisczeroCmd::ImageId(cmd) => cmd.run(),
+        #[cfg(feature = "zkvm")]
+        RisczeroCmd::Verify(cmd) => cmd.run(),
+        #[cfg(feature = "zkvm")]
+        RisczeroCmd::Inspect(cmd) => cmd.run(),
         #[cfg(feature = "experimental")]
         RisczeroCmd::BuildCrate(build) => build.run(BuildSubcommand::Build),
         #[cfg(feature = "experimental")]
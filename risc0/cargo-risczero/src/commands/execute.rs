@@ -0,0 +1,224 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, Parser};
+use risc0_zkvm::{default_executor, ExecutorEnv, ExitCode, SegmentInfo, Session, SessionInfo};
+
+use crate::utils::resolve_guest;
+
+/// `cargo risczero execute`
+#[derive(Parser)]
+pub struct ExecuteCommand {
+    #[command(flatten)]
+    pub guest: GuestArgs,
+}
+
+impl ExecuteCommand {
+    pub fn run(&self) -> Result<()> {
+        let elf = self.guest.load_elf()?;
+        let input = self.guest.read_input()?;
+        let env = self.guest.build_env(&input)?;
+        let info = default_executor().execute_elf(env, &elf)?;
+
+        print_stats(&info);
+        exit_with(info.exit_code)
+    }
+}
+
+/// Options for running a guest, shared by `execute` and `prove`.
+#[derive(Args)]
+pub struct GuestArgs {
+    /// Path to the guest ELF, or the name of a guest binary in the target
+    /// directory.
+    pub guest: String,
+
+    /// Files to pass to the guest on stdin, in order. Use `-` to pass this
+    /// process's stdin.
+    #[arg(long)]
+    pub input: Vec<PathBuf>,
+
+    /// Environment variables to set for the guest, in the form NAME=value.
+    #[arg(long, value_parser = parse_env_var)]
+    pub env: Vec<(String, String)>,
+
+    /// Files the guest can read from a file descriptor, in the form FD=PATH.
+    #[arg(long, value_parser = parse_fd_path)]
+    pub read_fd: Vec<(u32, PathBuf)>,
+
+    /// Files to write to when the guest writes to a file descriptor, in the
+    /// form FD=PATH.
+    #[arg(long, value_parser = parse_fd_path)]
+    pub write_fd: Vec<(u32, PathBuf)>,
+
+    /// Maximum number of cycles in a segment, as a power of two.
+    #[arg(long)]
+    pub segment_limit_po2: Option<u32>,
+
+    /// Maximum number of cycles in the session.
+    #[arg(long)]
+    pub session_limit: Option<u64>,
+
+    /// Arguments to pass to the guest, following `--`.
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+impl GuestArgs {
+    pub(crate) fn load_elf(&self) -> Result<Vec<u8>> {
+        let elf_path = resolve_guest(&self.guest)?;
+        std::fs::read(&elf_path).with_context(|| format!("failed to read {}", elf_path.display()))
+    }
+
+    /// Read all of the guest's input up front, concatenating the `--input`
+    /// files in order.
+    pub(crate) fn read_input(&self) -> Result<Vec<u8>> {
+        let mut input = vec![];
+        for path in self.input.iter() {
+            if path.as_os_str() == "-" {
+                std::io::stdin()
+                    .read_to_end(&mut input)
+                    .context("failed to read stdin")?;
+            } else {
+                let mut file = File::open(path)
+                    .with_context(|| format!("failed to open {}", path.display()))?;
+                file.read_to_end(&mut input)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+            }
+        }
+        Ok(input)
+    }
+
+    pub(crate) fn build_env<'a>(&self, input: &'a [u8]) -> Result<ExecutorEnv<'a>> {
+        let mut builder = ExecutorEnv::builder();
+        builder.stdin(input).args(&self.args);
+        for (name, value) in self.env.iter() {
+            builder.env_var(name, value);
+        }
+        for (fd, path) in self.read_fd.iter() {
+            let file =
+                File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+            builder.read_fd(*fd, BufReader::new(file));
+        }
+        for (fd, path) in self.write_fd.iter() {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            builder.write_fd(*fd, file);
+        }
+        if let Some(po2) = self.segment_limit_po2 {
+            builder.segment_limit_po2(po2);
+        }
+        if let Some(limit) = self.session_limit {
+            builder.session_limit(Some(limit));
+        }
+        builder.build()
+    }
+}
+
+fn parse_env_var(var: &str) -> Result<(String, String), String> {
+    let (name, value) = var
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=value, found `{var}`"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_fd_path(arg: &str) -> Result<(u32, PathBuf), String> {
+    let (fd, path) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected FD=PATH, found `{arg}`"))?;
+    let fd = fd
+        .parse()
+        .map_err(|_| format!("invalid file descriptor `{fd}`"))?;
+    Ok((fd, path.into()))
+}
+
+/// Summarize a [Session] that was executed locally.
+pub(crate) fn session_info(session: &Session) -> Result<SessionInfo> {
+    let mut segments = vec![];
+    for segment in session.resolve()? {
+        segments.push(SegmentInfo {
+            po2: segment.po2,
+            cycles: segment.cycles,
+            stats: segment.stats,
+        });
+    }
+    Ok(SessionInfo {
+        segments,
+        journal: session.journal.clone().unwrap_or_default(),
+        exit_code: session.exit_code,
+        stats: session.stats.clone(),
+    })
+}
+
+/// Print the exit code and the cycles of each segment and of the whole
+/// session to stderr.
+pub(crate) fn print_stats(info: &SessionInfo) {
+    for (idx, segment) in info.segments.iter().enumerate() {
+        eprintln!(
+            "segment {idx}: {} user cycles, po2 {}",
+            segment.cycles, segment.po2
+        );
+    }
+    eprintln!("session:\n{}", info.stats);
+    eprintln!("exit code: {:?}", info.exit_code);
+}
+
+/// Exit the process with the guest's exit code.
+///
+/// Sessions that did not halt or pause, such as those that faulted, exit with
+/// status 1.
+pub(crate) fn exit_with(exit_code: ExitCode) -> Result<()> {
+    let status = exit_status(exit_code);
+    if status != 0 {
+        std::process::exit(status);
+    }
+    Ok(())
+}
+
+/// The process exit status for a guest exit code.
+///
+/// A process can only report a status up to 255, so larger guest exit codes
+/// are clamped to 255 rather than wrapping around to success.
+fn exit_status(exit_code: ExitCode) -> i32 {
+    match exit_code {
+        ExitCode::Halted(0) | ExitCode::Paused(0) => 0,
+        ExitCode::Halted(code) | ExitCode::Paused(code) => code.min(255) as i32,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::ExitCode;
+
+    use super::exit_status;
+
+    #[test]
+    fn exit_status_is_nonzero_for_nonzero_codes() {
+        assert_eq!(exit_status(ExitCode::Halted(0)), 0);
+        assert_eq!(exit_status(ExitCode::Paused(0)), 0);
+        assert_eq!(exit_status(ExitCode::Halted(3)), 3);
+        assert_eq!(exit_status(ExitCode::Halted(255)), 255);
+        assert_eq!(exit_status(ExitCode::Halted(256)), 255);
+        assert_eq!(exit_status(ExitCode::Paused(u32::MAX)), 255);
+        assert_eq!(exit_status(ExitCode::SystemSplit), 1);
+        assert_eq!(exit_status(ExitCode::Fault), 1);
+    }
+}
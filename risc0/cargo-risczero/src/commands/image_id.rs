@@ -0,0 +1,35 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::utils::{compute_image_id, resolve_guest};
+
+/// `cargo risczero image-id`
+#[derive(Parser)]
+pub struct ImageIdCommand {
+    /// Path to the guest ELF, or the name of a guest binary in the target
+    /// directory.
+    pub guest: String,
+}
+
+impl ImageIdCommand {
+    pub fn run(&self) -> Result<()> {
+        let image_id = compute_image_id(&resolve_guest(&self.guest)?)?;
+        println!("{image_id}");
+        println!("{:?}", image_id.as_words());
+        Ok(())
+    }
+}
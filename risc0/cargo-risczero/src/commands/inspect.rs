@@ -0,0 +1,224 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use clap::Parser;
+use risc0_zkvm::{
+    receipt_metadata::MaybePruned, sha::Digestible, InnerReceipt, Output, Receipt, SystemState,
+};
+
+use crate::utils::load_receipt;
+
+/// `cargo risczero inspect`
+#[derive(Parser)]
+pub struct InspectCommand {
+    /// Path to a bincode encoded receipt, as written by `r0vm --receipt`.
+    #[arg(long)]
+    pub receipt: PathBuf,
+}
+
+impl InspectCommand {
+    pub fn run(&self) -> Result<()> {
+        let receipt = load_receipt(&self.receipt)?;
+        inspect(&receipt, &mut io::stdout().lock())
+    }
+}
+
+fn inspect(receipt: &Receipt, out: &mut impl Write) -> Result<()> {
+    match &receipt.inner {
+        InnerReceipt::Composite(inner) => {
+            let hashfns: BTreeSet<_> = inner.segments.iter().map(|x| x.hashfn.as_str()).collect();
+            writeln!(out, "Kind:          composite")?;
+            writeln!(out, "Segments:      {}", inner.segments.len())?;
+            writeln!(
+                out,
+                "Hash function: {}",
+                hashfns.into_iter().collect::<Vec<_>>().join(", ")
+            )?;
+        }
+        InnerReceipt::Succinct(inner) => {
+            // The segments are compressed into a single seal by the recursion
+            // circuit, which always uses Poseidon as its hash function.
+            writeln!(out, "Kind:          succinct")?;
+            writeln!(out, "Segments:      1 (compressed)")?;
+            writeln!(out, "Hash function: poseidon")?;
+            writeln!(out, "Control ID:    {}", inner.control_id)?;
+        }
+        InnerReceipt::Fake { .. } => {
+            writeln!(
+                out,
+                "Kind:          fake (only accepted with RISC0_DEV_MODE=1)"
+            )?;
+            writeln!(out, "Segments:      0")?;
+            writeln!(out, "Hash function: none")?;
+        }
+    }
+
+    let metadata = receipt.inner.get_metadata()?;
+    writeln!(out)?;
+    write_state(out, "Pre-state:", &metadata.pre)?;
+    write_state(out, "Post-state:", &metadata.post)?;
+    writeln!(out, "Exit code:  {:?}", metadata.exit_code)?;
+    writeln!(out, "Input:      {}", metadata.input)?;
+    write_output(out, &metadata.output)?;
+
+    writeln!(out)?;
+    write_journal(out, &receipt.journal.bytes)
+}
+
+fn write_state(
+    out: &mut impl Write,
+    label: &str,
+    state: &MaybePruned<SystemState>,
+) -> io::Result<()> {
+    writeln!(out, "{label:<12}{}", state.digest())?;
+    match state {
+        MaybePruned::Value(state) => {
+            writeln!(out, "  pc:          {:#010x}", state.pc)?;
+            writeln!(out, "  merkle root: {}", state.merkle_root)
+        }
+        MaybePruned::Pruned(_) => writeln!(out, "  (pruned)"),
+    }
+}
+
+fn write_output(out: &mut impl Write, output: &MaybePruned<Option<Output>>) -> io::Result<()> {
+    writeln!(out, "Output:     {}", output.digest())?;
+    let output = match output {
+        MaybePruned::Value(Some(output)) => output,
+        MaybePruned::Value(None) => return writeln!(out, "  (none)"),
+        MaybePruned::Pruned(_) => return writeln!(out, "  (pruned)"),
+    };
+    writeln!(out, "  journal:     {}", output.journal.digest())?;
+    match &output.assumptions {
+        MaybePruned::Value(assumptions) if assumptions.is_empty() => {
+            writeln!(out, "  assumptions: (none)")
+        }
+        MaybePruned::Value(assumptions) => {
+            writeln!(out, "  assumptions:")?;
+            for assumption in assumptions.iter() {
+                writeln!(out, "    {}", assumption.digest())?;
+            }
+            Ok(())
+        }
+        MaybePruned::Pruned(digest) => writeln!(out, "  assumptions: {digest} (pruned)"),
+    }
+}
+
+fn write_journal(out: &mut impl Write, journal: &[u8]) -> Result<()> {
+    writeln!(out, "Journal ({} bytes):", journal.len())?;
+    if journal.is_empty() {
+        return Ok(());
+    }
+    match std::str::from_utf8(journal) {
+        Ok(text) if !text.contains(|c: char| c.is_control() && !c.is_whitespace()) => {
+            writeln!(out, "{}", text.trim_end_matches('\n'))?
+        }
+        _ => writeln!(out, "{}", hex::encode(journal))?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use risc0_zkvm::{
+        get_prover_server,
+        receipt_metadata::{Assumptions, MaybePruned},
+        sha::Digest,
+        ExecutorEnv, ExitCode, InnerReceipt, Output, ProverOpts, Receipt, ReceiptMetadata,
+        SystemState,
+    };
+    use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+
+    use super::inspect;
+
+    /// A fake receipt for a guest that halted successfully, committing
+    /// `journal`.
+    pub(crate) fn fake_receipt(journal: &[u8]) -> Receipt {
+        let metadata = ReceiptMetadata {
+            pre: SystemState {
+                pc: 0x1000,
+                merkle_root: Digest::from([1, 2, 3, 4, 5, 6, 7, 8]),
+            }
+            .into(),
+            post: SystemState {
+                pc: 0x2000,
+                merkle_root: Digest::from([8, 7, 6, 5, 4, 3, 2, 1]),
+            }
+            .into(),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(Output {
+                journal: MaybePruned::Value(journal.to_vec()),
+                assumptions: Assumptions(vec![]).into(),
+            })
+            .into(),
+        };
+        Receipt::new(InnerReceipt::Fake { metadata }, journal.to_vec())
+    }
+
+    /// A receipt proven for a guest that does nothing, with the default
+    /// [ProverOpts].
+    pub(crate) fn proven_receipt() -> Receipt {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::DoNothing)
+            .unwrap()
+            .build()
+            .unwrap();
+        get_prover_server(&ProverOpts::default())
+            .unwrap()
+            .prove_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+    }
+
+    fn inspect_to_string(receipt: &Receipt) -> String {
+        let mut out = Vec::new();
+        inspect(receipt, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn inspect_fake_receipt() {
+        let out = inspect_to_string(&fake_receipt(b"hello world\n"));
+        assert!(out.contains("Kind:          fake"));
+        assert!(out.contains("Segments:      0"));
+        assert!(out.contains("Hash function: none"));
+        assert!(out.contains("  pc:          0x00001000"));
+        assert!(out.contains("  pc:          0x00002000"));
+        assert!(out.contains("Exit code:  Halted(0)"));
+        assert!(out.contains("  assumptions: (none)"));
+        assert!(out.ends_with("Journal (12 bytes):\nhello world\n"));
+    }
+
+    #[test]
+    fn inspect_binary_journal() {
+        let out = inspect_to_string(&fake_receipt(&[0, 1, 0xff]));
+        assert!(out.ends_with("Journal (3 bytes):\n0001ff\n"));
+    }
+
+    #[test]
+    fn inspect_proven_receipt() {
+        let out = inspect_to_string(&proven_receipt());
+        assert!(out.contains("Kind:          composite"));
+        assert!(out.contains("Segments:      1"));
+        assert!(out.contains("Hash function: poseidon"));
+        assert!(out.contains("Exit code:  Halted(0)"));
+        assert!(out.ends_with("Journal (0 bytes):\n"));
+    }
+}
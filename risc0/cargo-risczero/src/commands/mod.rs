@@ -16,5 +16,15 @@
 pub mod build;
 pub mod build_guest;
 pub mod build_toolchain;
+#[cfg(feature = "zkvm")]
+pub mod execute;
+#[cfg(feature = "zkvm")]
+pub mod image_id;
+#[cfg(feature = "zkvm")]
+pub mod inspect;
 pub mod install;
 pub mod new;
+#[cfg(feature = "zkvm")]
+pub mod prove;
+#[cfg(feature = "zkvm")]
+pub mod verify;
@@ -0,0 +1,80 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use risc0_zkvm::{get_prover_server, ExecutorImpl, ExitCode, ProverOpts, VerifierContext};
+
+use super::execute::{exit_with, print_stats, session_info, GuestArgs};
+
+/// `cargo risczero prove`
+#[derive(Parser)]
+pub struct ProveCommand {
+    #[command(flatten)]
+    pub guest: GuestArgs,
+
+    /// File to write the bincode encoded receipt to.
+    #[arg(long)]
+    pub receipt: Option<PathBuf>,
+
+    /// The kind of receipt to produce.
+    #[arg(long, value_enum, default_value_t = ReceiptKind::Composite)]
+    pub receipt_kind: ReceiptKind,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ReceiptKind {
+    #[value(name = "composite")]
+    Composite,
+    #[value(name = "succinct")]
+    Succinct,
+}
+
+impl ProveCommand {
+    pub fn run(&self) -> Result<()> {
+        let elf = self.guest.load_elf()?;
+        let input = self.guest.read_input()?;
+
+        // Execute the guest locally so that its stats can be reported, and so
+        // that a session that failed is not proven.
+        let env = self.guest.build_env(&input)?;
+        let session = ExecutorImpl::from_elf(env, &elf)?.run()?;
+        print_stats(&session_info(&session)?);
+        let (ExitCode::Halted(0) | ExitCode::Paused(0)) = session.exit_code else {
+            return exit_with(session.exit_code);
+        };
+
+        let opts = ProverOpts::default().with_receipt_kind(match self.receipt_kind {
+            ReceiptKind::Composite => risc0_zkvm::ReceiptKind::Composite,
+            ReceiptKind::Succinct => risc0_zkvm::ReceiptKind::Succinct,
+        });
+        let receipt =
+            get_prover_server(&opts)?.prove_session(&VerifierContext::default(), &session)?;
+
+        if let Some(path) = self.receipt.as_ref() {
+            let receipt_data = bincode::serialize(&receipt)?;
+            std::fs::write(path, &receipt_data)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!(
+                "Wrote {} bytes of receipt to {}",
+                receipt_data.len(),
+                path.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use hex::FromHex;
+use risc0_zkvm::{sha::Digest, VerifierContext};
+
+use crate::utils::{compute_image_id, load_receipt};
+
+/// `cargo risczero verify`
+#[derive(Parser)]
+pub struct VerifyCommand {
+    /// Path to a bincode encoded receipt, as written by `r0vm --receipt`.
+    #[arg(long)]
+    pub receipt: PathBuf,
+
+    /// Image ID the receipt is expected to attest to, in hex.
+    #[arg(long, required_unless_present = "elf", conflicts_with = "elf")]
+    pub image_id: Option<String>,
+
+    /// ELF of the guest the receipt is expected to attest to.
+    ///
+    /// The image ID is computed from this file.
+    #[arg(long)]
+    pub elf: Option<PathBuf>,
+}
+
+impl VerifyCommand {
+    pub fn run(&self) -> Result<()> {
+        let image_id = match (&self.image_id, &self.elf) {
+            (Some(image_id), _) => Digest::from_hex(image_id.trim_start_matches("0x"))
+                .with_context(|| format!("invalid image ID: {image_id}"))?,
+            (None, Some(elf)) => compute_image_id(elf)?,
+            (None, None) => unreachable!("clap requires --image-id or --elf"),
+        };
+
+        let receipt = load_receipt(&self.receipt)?;
+        receipt
+            .verify_with_context(&VerifierContext::default(), image_id)
+            .with_context(|| format!("receipt verification failed for image ID {image_id}"))?;
+
+        println!("Receipt verified for image ID {image_id}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::sha::{Digest, Digestible};
+    use risc0_zkvm_methods::MULTI_TEST_ID;
+
+    use super::VerifyCommand;
+    use crate::commands::inspect::tests::{fake_receipt, proven_receipt};
+
+    fn verify(receipt_path: &std::path::Path, image_id: Digest) -> anyhow::Result<()> {
+        VerifyCommand {
+            receipt: receipt_path.to_path_buf(),
+            image_id: Some(hex::encode(image_id)),
+            elf: None,
+        }
+        .run()
+    }
+
+    #[test]
+    fn verify_fake_receipt() {
+        let receipt = fake_receipt(b"journal");
+        let image_id = receipt.get_metadata().unwrap().pre.digest();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), bincode::serialize(&receipt).unwrap()).unwrap();
+
+        // Fake receipts are rejected unless dev mode is enabled. This is the
+        // only test in this crate that reads RISC0_DEV_MODE.
+        std::env::remove_var("RISC0_DEV_MODE");
+        assert!(verify(file.path(), image_id).is_err());
+
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        verify(file.path(), image_id).unwrap();
+        assert!(verify(file.path(), Digest::ZERO).is_err());
+        std::env::remove_var("RISC0_DEV_MODE");
+    }
+
+    #[test]
+    fn verify_proven_receipt() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), bincode::serialize(&proven_receipt()).unwrap()).unwrap();
+        verify(file.path(), MULTI_TEST_ID.into()).unwrap();
+        assert!(verify(file.path(), Digest::ZERO).is_err());
+    }
+
+    #[test]
+    fn verify_malformed_receipt() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"not a receipt").unwrap();
+        assert!(verify(file.path(), Digest::ZERO).is_err());
+    }
+}
@@ -29,6 +29,11 @@ use self::commands::build::BuildCommand;
 use self::commands::{
     build_guest::BuildGuest, build_toolchain::BuildToolchain, install::Install, new::NewCommand,
 };
+#[cfg(feature = "zkvm")]
+use self::commands::{
+    execute::ExecuteCommand, image_id::ImageIdCommand, inspect::InspectCommand,
+    prove::ProveCommand, verify::VerifyCommand,
+};
 
 #[derive(Parser)]
 #[command(name = "cargo", bin_name = "cargo")]
@@ -58,6 +63,21 @@ pub enum RisczeroCmd {
     Install(Install),
     /// Creates a new risczero starter project.
     New(NewCommand),
+    /// Execute a guest and report its cycle counts.
+    #[cfg(feature = "zkvm")]
+    Execute(ExecuteCommand),
+    /// Execute and prove a guest.
+    #[cfg(feature = "zkvm")]
+    Prove(ProveCommand),
+    /// Print the image ID of a guest.
+    #[cfg(feature = "zkvm")]
+    ImageId(ImageIdCommand),
+    /// Verify a receipt against an image ID or ELF.
+    #[cfg(feature = "zkvm")]
+    Verify(VerifyCommand),
+    /// Print the metadata and journal of a receipt.
+    #[cfg(feature = "zkvm")]
+    Inspect(InspectCommand),
     /// Build a crate for RISC Zero.
     #[cfg(feature = "experimental")]
     BuildCrate(BuildCommand),
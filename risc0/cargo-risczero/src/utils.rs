@@ -23,6 +23,8 @@ use std::{
 
 use anyhow::{anyhow, Context, Result};
 use fs2::FileExt;
+#[cfg(feature = "zkvm")]
+use risc0_zkvm::{sha::Digest, MemoryImage, Program, Receipt, GUEST_MAX_MEM, PAGE_SIZE};
 
 pub fn risc0_data() -> Result<PathBuf> {
     let dir = if let Ok(dir) = std::env::var("RISC0_DATA_DIR") {
@@ -49,6 +51,80 @@ pub fn ensure_binary(command: &str, args: &[&str]) -> Result<()> {
     Ok(())
 }
 
+/// Load a bincode encoded [Receipt], as written by `r0vm --receipt`.
+#[cfg(feature = "zkvm")]
+pub fn load_receipt(path: &Path) -> Result<Receipt> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    bincode::deserialize(&bytes)
+        .with_context(|| format!("failed to decode receipt from {}", path.display()))
+}
+
+/// Compute the image ID of the ELF at the given path.
+#[cfg(feature = "zkvm")]
+pub fn compute_image_id(elf_path: &Path) -> Result<Digest> {
+    let elf = std::fs::read(elf_path)
+        .with_context(|| format!("failed to read {}", elf_path.display()))?;
+    let program = Program::load_elf(&elf, GUEST_MAX_MEM as u32).context("unable to load elf")?;
+    let image =
+        MemoryImage::new(&program, PAGE_SIZE as u32).context("unable to create memory image")?;
+    Ok(image.compute_id())
+}
+
+/// Resolve a guest given on the command line to the path of its ELF.
+///
+/// `guest` is either a path to an ELF, or the name of a guest binary built by
+/// `cargo risczero build` or by `risc0_build::embed_methods` in the target
+/// directory. If a guest has been built more than once, the most recently
+/// built ELF is used.
+#[cfg(feature = "zkvm")]
+pub fn resolve_guest(guest: &str) -> Result<PathBuf> {
+    let path = Path::new(guest);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+
+    let target_dir: PathBuf = std::env::var_os("CARGO_TARGET_DIR")
+        .unwrap_or_else(|| "target".into())
+        .into();
+    // `cargo risczero build` writes to `riscv-guest/riscv32im-risc0-zkvm-elf/docker/$pkg`,
+    // and `embed_methods` to `$profile/riscv-guest/riscv32im-risc0-zkvm-elf/$profile`.
+    let guest_dirs = [
+        target_dir.join("riscv-guest"),
+        target_dir.join("debug").join("riscv-guest"),
+        target_dir.join("release").join("riscv-guest"),
+    ];
+    let mut candidates = vec![];
+    for guest_dir in guest_dirs {
+        let elf_dir = guest_dir.join("riscv32im-risc0-zkvm-elf");
+        for dir in subdirs(&elf_dir).chain(subdirs(&elf_dir.join("docker"))) {
+            let elf_path = dir.join(guest);
+            if let Ok(modified) = elf_path.metadata().and_then(|meta| meta.modified()) {
+                candidates.push((modified, elf_path));
+            }
+        }
+    }
+
+    let (_, elf_path) = candidates.into_iter().max().with_context(|| {
+        format!(
+            "{guest} is neither an ELF file nor a guest built in {}",
+            target_dir.display()
+        )
+    })?;
+    eprintln!("Using guest ELF {}", elf_path.display());
+    Ok(elf_path)
+}
+
+#[cfg(feature = "zkvm")]
+fn subdirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+}
+
 pub trait CommandExt {
     fn as_command_mut(&mut self) -> &mut Command;
 
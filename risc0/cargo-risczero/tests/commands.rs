@@ -0,0 +1,137 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "zkvm")]
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use risc0_zkvm::{serde::to_vec, sha::Digest};
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ID, MULTI_TEST_PATH};
+use tempfile::TempDir;
+
+fn risczero(subcommand: &str) -> Command {
+    let mut cmd = Command::cargo_bin("cargo-risczero").unwrap();
+    cmd.arg("risczero")
+        .arg(subcommand)
+        .env("RISC0_DEV_MODE", "1");
+    cmd
+}
+
+fn write_input(dir: &TempDir, spec: &MultiTestSpec) -> PathBuf {
+    let path = dir.path().join("input.bin");
+    let input: Vec<u8> = to_vec(spec)
+        .unwrap()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+    std::fs::write(&path, input).unwrap();
+    path
+}
+
+fn prove(input: &Path, receipt: &Path) -> Command {
+    let mut cmd = risczero("prove");
+    cmd.arg(MULTI_TEST_PATH)
+        .arg("--input")
+        .arg(input)
+        .arg("--receipt")
+        .arg(receipt);
+    cmd
+}
+
+#[test]
+fn image_id() {
+    let output = risczero("image-id")
+        .arg(MULTI_TEST_PATH)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let image_id = Digest::from(MULTI_TEST_ID);
+    assert_eq!(output.lines().next(), Some(image_id.to_string().as_str()));
+}
+
+#[test]
+fn execute() {
+    let dir = TempDir::new().unwrap();
+    let input = write_input(&dir, &MultiTestSpec::DoNothing);
+    let output = risczero("execute")
+        .arg(MULTI_TEST_PATH)
+        .arg("--input")
+        .arg(&input)
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("exit code: Halted(0)"));
+}
+
+#[test]
+fn execute_exit_code() {
+    let dir = TempDir::new().unwrap();
+    let input = write_input(&dir, &MultiTestSpec::Halt(3));
+    risczero("execute")
+        .arg(MULTI_TEST_PATH)
+        .arg("--input")
+        .arg(&input)
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn prove_verify_inspect() {
+    let dir = TempDir::new().unwrap();
+    let input = write_input(&dir, &MultiTestSpec::DoNothing);
+    let receipt = dir.path().join("receipt.bin");
+    prove(&input, &receipt).assert().success();
+
+    risczero("verify")
+        .arg("--receipt")
+        .arg(&receipt)
+        .arg("--elf")
+        .arg(MULTI_TEST_PATH)
+        .assert()
+        .success();
+    risczero("verify")
+        .arg("--receipt")
+        .arg(&receipt)
+        .arg("--image-id")
+        .arg(Digest::ZERO.to_string())
+        .assert()
+        .failure();
+
+    let output = risczero("inspect")
+        .arg("--receipt")
+        .arg(&receipt)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Exit code:  Halted(0)"));
+}
+
+#[test]
+fn prove_failed_session() {
+    let dir = TempDir::new().unwrap();
+    let input = write_input(&dir, &MultiTestSpec::Halt(3));
+    let receipt = dir.path().join("receipt.bin");
+    prove(&input, &receipt).assert().code(3);
+    assert!(!receipt.exists());
+}
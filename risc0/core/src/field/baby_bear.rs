@@ -35,10 +35,14 @@ pub struct BabyBear;
 impl field::Field for BabyBear {
     type Elem = Elem;
     type ExtElem = ExtElem;
+
+    fn with_packed<Op: field::packed::PackedOp<Elem>>(op: Op) -> Op::Output {
+        field::packed::with_packed_baby_bear(op)
+    }
 }
 
 // montgomery form constants
-const M: u32 = 0x88000001;
+pub(crate) const M: u32 = 0x88000001;
 const R2: u32 = 1172168163;
 
 /// The BabyBear class is an element of the finite field F_p, where P is the
@@ -80,7 +84,7 @@ impl fmt::Debug for Elem {
 }
 
 /// The modulus of the field.
-const P: u32 = 15 * (1 << 27) + 1;
+pub(crate) const P: u32 = 15 * (1 << 27) + 1;
 
 /// The modulus of the field as a u64.
 const P_U64: u64 = P as u64;
@@ -22,6 +22,7 @@ use core::{cmp, fmt::Debug, ops};
 
 pub mod baby_bear;
 pub mod goldilocks;
+pub mod packed;
 
 /// A pair of fields, one of which is an extension field of the other.
 pub trait Field {
@@ -29,6 +30,15 @@ pub trait Field {
     type Elem: Elem + RootsOfUnity;
     /// An element of the extension field
     type ExtElem: ExtElem<SubElem = Self::Elem>;
+
+    /// Run `op` with the widest [packed::PackedElem] implementation for
+    /// [Field::Elem] that the CPU supports.
+    ///
+    /// The default implementation uses [packed::Portable], which makes no use
+    /// of SIMD instructions.
+    fn with_packed<Op: packed::PackedOp<Self::Elem>>(op: Op) -> Op::Output {
+        op.run::<packed::Portable<Self::Elem, 8>>()
+    }
 }
 
 /// Subfield elements that can be compared, copied, and operated
@@ -0,0 +1,214 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Packed [BabyBear](crate::field::baby_bear::BabyBear) elements using AVX2.
+
+use core::{
+    arch::x86_64::{
+        __m256i, _mm256_add_epi32, _mm256_add_epi64, _mm256_blend_epi32, _mm256_loadu_si256,
+        _mm256_min_epu32, _mm256_mul_epu32, _mm256_set1_epi32, _mm256_setzero_si256,
+        _mm256_srli_epi64, _mm256_storeu_si256, _mm256_sub_epi32,
+    },
+    fmt, ops,
+};
+
+use super::PackedElem;
+use crate::field::baby_bear::{Elem, M, P};
+
+const WIDTH: usize = 8;
+
+/// Eight [BabyBear](crate::field::baby_bear::BabyBear) elements in Montgomery
+/// form, held in an AVX2 register.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct PackedBabyBearAvx2(__m256i);
+
+// SAFETY, for every call to a `#[target_feature]` function in this module:
+// this type is only named by [with_packed_baby_bear](super::with_packed_baby_bear),
+// which uses it after detecting the `avx2` feature at run time.
+
+impl PackedBabyBearAvx2 {
+    fn to_array(self) -> [Elem; WIDTH] {
+        let mut lanes = [Elem::default(); WIDTH];
+        self.store(&mut lanes);
+        lanes
+    }
+}
+
+impl PackedElem for PackedBabyBearAvx2 {
+    type Scalar = Elem;
+
+    const WIDTH: usize = WIDTH;
+
+    #[inline]
+    fn broadcast(value: Elem) -> Self {
+        Self(unsafe { broadcast(value) })
+    }
+
+    #[inline]
+    fn from_fn<F: FnMut(usize) -> Elem>(f: F) -> Self {
+        let lanes: [Elem; WIDTH] = core::array::from_fn(f);
+        Self::load(&lanes)
+    }
+
+    #[inline]
+    fn load(slice: &[Elem]) -> Self {
+        Self(unsafe { load(&slice[..WIDTH]) })
+    }
+
+    #[inline]
+    fn store(self, slice: &mut [Elem]) {
+        unsafe { store(self.0, &mut slice[..WIDTH]) }
+    }
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+fn broadcast(value: Elem) -> __m256i {
+    _mm256_set1_epi32(value.as_u32_montgomery() as i32)
+}
+
+/// Load `WIDTH` elements from `lanes`, which must have exactly that length.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn load(lanes: &[Elem]) -> __m256i {
+    assert_eq!(lanes.len(), WIDTH);
+    // SAFETY: `lanes` holds exactly one register's worth of elements.
+    unsafe { _mm256_loadu_si256(lanes.as_ptr().cast()) }
+}
+
+/// Store `WIDTH` elements to `lanes`, which must have exactly that length.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn store(x: __m256i, lanes: &mut [Elem]) {
+    assert_eq!(lanes.len(), WIDTH);
+    // SAFETY: `lanes` holds exactly one register's worth of elements.
+    unsafe { _mm256_storeu_si256(lanes.as_mut_ptr().cast(), x) }
+}
+
+/// Reduce lanes in `[0, 2P)` to `[0, P)`.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn reduce(x: __m256i) -> __m256i {
+    // Lanes below P wrap around when P is subtracted, so the minimum picks
+    // whichever of `x` and `x - P` is in range.
+    _mm256_min_epu32(x, _mm256_sub_epi32(x, _mm256_set1_epi32(P as i32)))
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+fn add(lhs: __m256i, rhs: __m256i) -> __m256i {
+    reduce(_mm256_add_epi32(lhs, rhs))
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+fn sub(lhs: __m256i, rhs: __m256i) -> __m256i {
+    let diff = _mm256_sub_epi32(lhs, rhs);
+    _mm256_min_epu32(diff, _mm256_add_epi32(diff, _mm256_set1_epi32(P as i32)))
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+fn neg(x: __m256i) -> __m256i {
+    sub(_mm256_setzero_si256(), x)
+}
+
+/// Montgomery multiplication, following the scalar implementation: each
+/// 32x32-bit product `o` is reduced as `(o + (M * -o mod 2^32) * P) >> 32`.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn mul(lhs: __m256i, rhs: __m256i) -> __m256i {
+    let p = _mm256_set1_epi32(P as i32);
+    let m = _mm256_set1_epi32(M as i32);
+    let zero = _mm256_setzero_si256();
+    // `_mm256_mul_epu32` multiplies the even lanes, so the odd lanes are
+    // shifted down to be multiplied separately.
+    let prod_evn = _mm256_mul_epu32(lhs, rhs);
+    let prod_odd = _mm256_mul_epu32(_mm256_srli_epi64::<32>(lhs), _mm256_srli_epi64::<32>(rhs));
+    let red_evn = _mm256_mul_epu32(_mm256_sub_epi32(zero, prod_evn), m);
+    let red_odd = _mm256_mul_epu32(_mm256_sub_epi32(zero, prod_odd), m);
+    let sum_evn = _mm256_add_epi64(prod_evn, _mm256_mul_epu32(red_evn, p));
+    let sum_odd = _mm256_add_epi64(prod_odd, _mm256_mul_epu32(red_odd, p));
+    // The results are in the high halves of each 64-bit sum.
+    reduce(_mm256_blend_epi32::<0b10101010>(
+        _mm256_srli_epi64::<32>(sum_evn),
+        sum_odd,
+    ))
+}
+
+impl ops::Add for PackedBabyBearAvx2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(unsafe { add(self.0, rhs.0) })
+    }
+}
+
+impl ops::AddAssign for PackedBabyBearAvx2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 = unsafe { add(self.0, rhs.0) };
+    }
+}
+
+impl ops::Sub for PackedBabyBearAvx2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(unsafe { sub(self.0, rhs.0) })
+    }
+}
+
+impl ops::SubAssign for PackedBabyBearAvx2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 = unsafe { sub(self.0, rhs.0) };
+    }
+}
+
+impl ops::Mul for PackedBabyBearAvx2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(unsafe { mul(self.0, rhs.0) })
+    }
+}
+
+impl ops::MulAssign for PackedBabyBearAvx2 {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        self.0 = unsafe { mul(self.0, rhs.0) };
+    }
+}
+
+impl ops::Neg for PackedBabyBearAvx2 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self(unsafe { neg(self.0) })
+    }
+}
+
+impl fmt::Debug for PackedBabyBearAvx2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PackedBabyBearAvx2")
+            .field(&self.to_array())
+            .finish()
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Packed [BabyBear](crate::field::baby_bear::BabyBear) elements using AVX-512.
+
+use core::{
+    arch::x86_64::{
+        __m512i, _mm512_add_epi32, _mm512_add_epi64, _mm512_loadu_si512, _mm512_mask_blend_epi32,
+        _mm512_min_epu32, _mm512_mul_epu32, _mm512_set1_epi32, _mm512_setzero_si512,
+        _mm512_srli_epi64, _mm512_storeu_si512, _mm512_sub_epi32,
+    },
+    fmt, ops,
+};
+
+use super::PackedElem;
+use crate::field::baby_bear::{Elem, M, P};
+
+const WIDTH: usize = 16;
+
+/// Sixteen [BabyBear](crate::field::baby_bear::BabyBear) elements in Montgomery
+/// form, held in an AVX-512 register.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct PackedBabyBearAvx512(__m512i);
+
+// SAFETY, for every call to a `#[target_feature]` function in this module:
+// this type is only named by [with_packed_baby_bear](super::with_packed_baby_bear),
+// which uses it after detecting the `avx512f` feature at run time.
+
+impl PackedBabyBearAvx512 {
+    fn to_array(self) -> [Elem; WIDTH] {
+        let mut lanes = [Elem::default(); WIDTH];
+        self.store(&mut lanes);
+        lanes
+    }
+}
+
+impl PackedElem for PackedBabyBearAvx512 {
+    type Scalar = Elem;
+
+    const WIDTH: usize = WIDTH;
+
+    #[inline]
+    fn broadcast(value: Elem) -> Self {
+        Self(unsafe { broadcast(value) })
+    }
+
+    #[inline]
+    fn from_fn<F: FnMut(usize) -> Elem>(f: F) -> Self {
+        let lanes: [Elem; WIDTH] = core::array::from_fn(f);
+        Self::load(&lanes)
+    }
+
+    #[inline]
+    fn load(slice: &[Elem]) -> Self {
+        Self(unsafe { load(&slice[..WIDTH]) })
+    }
+
+    #[inline]
+    fn store(self, slice: &mut [Elem]) {
+        unsafe { store(self.0, &mut slice[..WIDTH]) }
+    }
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn broadcast(value: Elem) -> __m512i {
+    _mm512_set1_epi32(value.as_u32_montgomery() as i32)
+}
+
+/// Load `WIDTH` elements from `lanes`, which must have exactly that length.
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn load(lanes: &[Elem]) -> __m512i {
+    assert_eq!(lanes.len(), WIDTH);
+    // SAFETY: `lanes` holds exactly one register's worth of elements.
+    unsafe { _mm512_loadu_si512(lanes.as_ptr().cast()) }
+}
+
+/// Store `WIDTH` elements to `lanes`, which must have exactly that length.
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn store(x: __m512i, lanes: &mut [Elem]) {
+    assert_eq!(lanes.len(), WIDTH);
+    // SAFETY: `lanes` holds exactly one register's worth of elements.
+    unsafe { _mm512_storeu_si512(lanes.as_mut_ptr().cast(), x) }
+}
+
+/// Reduce lanes in `[0, 2P)` to `[0, P)`.
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn reduce(x: __m512i) -> __m512i {
+    // Lanes below P wrap around when P is subtracted, so the minimum picks
+    // whichever of `x` and `x - P` is in range.
+    _mm512_min_epu32(x, _mm512_sub_epi32(x, _mm512_set1_epi32(P as i32)))
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn add(lhs: __m512i, rhs: __m512i) -> __m512i {
+    reduce(_mm512_add_epi32(lhs, rhs))
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn sub(lhs: __m512i, rhs: __m512i) -> __m512i {
+    let diff = _mm512_sub_epi32(lhs, rhs);
+    _mm512_min_epu32(diff, _mm512_add_epi32(diff, _mm512_set1_epi32(P as i32)))
+}
+
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn neg(x: __m512i) -> __m512i {
+    sub(_mm512_setzero_si512(), x)
+}
+
+/// Montgomery multiplication, following the scalar implementation: each
+/// 32x32-bit product `o` is reduced as `(o + (M * -o mod 2^32) * P) >> 32`.
+#[inline]
+#[target_feature(enable = "avx512f")]
+fn mul(lhs: __m512i, rhs: __m512i) -> __m512i {
+    let p = _mm512_set1_epi32(P as i32);
+    let m = _mm512_set1_epi32(M as i32);
+    let zero = _mm512_setzero_si512();
+    // `_mm512_mul_epu32` multiplies the even lanes, so the odd lanes are
+    // shifted down to be multiplied separately.
+    let prod_evn = _mm512_mul_epu32(lhs, rhs);
+    let prod_odd = _mm512_mul_epu32(_mm512_srli_epi64::<32>(lhs), _mm512_srli_epi64::<32>(rhs));
+    let red_evn = _mm512_mul_epu32(_mm512_sub_epi32(zero, prod_evn), m);
+    let red_odd = _mm512_mul_epu32(_mm512_sub_epi32(zero, prod_odd), m);
+    let sum_evn = _mm512_add_epi64(prod_evn, _mm512_mul_epu32(red_evn, p));
+    let sum_odd = _mm512_add_epi64(prod_odd, _mm512_mul_epu32(red_odd, p));
+    // The results are in the high halves of each 64-bit sum.
+    reduce(_mm512_mask_blend_epi32(
+        0b1010101010101010,
+        _mm512_srli_epi64::<32>(sum_evn),
+        sum_odd,
+    ))
+}
+
+impl ops::Add for PackedBabyBearAvx512 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(unsafe { add(self.0, rhs.0) })
+    }
+}
+
+impl ops::AddAssign for PackedBabyBearAvx512 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 = unsafe { add(self.0, rhs.0) };
+    }
+}
+
+impl ops::Sub for PackedBabyBearAvx512 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(unsafe { sub(self.0, rhs.0) })
+    }
+}
+
+impl ops::SubAssign for PackedBabyBearAvx512 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 = unsafe { sub(self.0, rhs.0) };
+    }
+}
+
+impl ops::Mul for PackedBabyBearAvx512 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(unsafe { mul(self.0, rhs.0) })
+    }
+}
+
+impl ops::MulAssign for PackedBabyBearAvx512 {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        self.0 = unsafe { mul(self.0, rhs.0) };
+    }
+}
+
+impl ops::Neg for PackedBabyBearAvx512 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self(unsafe { neg(self.0) })
+    }
+}
+
+impl fmt::Debug for PackedBabyBearAvx512 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PackedBabyBearAvx512")
+            .field(&self.to_array())
+            .finish()
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Packed field elements
+//!
+//! A [PackedElem] holds a fixed number of base field elements and operates on
+//! all of them at once, so that hot loops over large buffers (such as the NTT
+//! and the CPU HAL kernels) can use SIMD instructions. Each lane of a packed
+//! operation produces exactly the same element as the corresponding scalar
+//! operation.
+//!
+//! The implementation is chosen at run time by
+//! [Field::with_packed](super::Field::with_packed), which runs a [PackedOp]
+//! with the widest implementation the CPU supports. For
+//! [BabyBear](super::baby_bear), this is an AVX-512 implementation on x86_64
+//! CPUs with the `avx512f` feature, an AVX2 implementation on those with the
+//! `avx2` feature, and a portable implementation otherwise. Detecting CPU
+//! features requires the `std` feature of this crate.
+//!
+//! The SIMD arithmetic is only inlined into callers compiled with the same
+//! target features, so building with `RUSTFLAGS="-C target-cpu=native"` is
+//! still faster on CPUs that support them.
+
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+mod avx2;
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+mod avx512;
+
+use core::{array, fmt::Debug, ops};
+
+use super::{baby_bear, Elem};
+
+/// An operation that is generic over the [PackedElem] implementation it uses,
+/// so that [Field::with_packed](super::Field::with_packed) can choose one at
+/// run time.
+pub trait PackedOp<E: Elem> {
+    /// The result of the operation.
+    type Output;
+
+    /// Run the operation using `P` for packed arithmetic.
+    fn run<P: PackedElem<Scalar = E>>(self) -> Self::Output;
+}
+
+/// Run `op` with the widest packed [BabyBear](super::baby_bear::BabyBear)
+/// implementation that the CPU supports.
+pub(crate) fn with_packed_baby_bear<Op: PackedOp<baby_bear::Elem>>(op: Op) -> Op::Output {
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    {
+        if std::is_x86_feature_detected!("avx512f") {
+            return op.run::<avx512::PackedBabyBearAvx512>();
+        }
+        if std::is_x86_feature_detected!("avx2") {
+            return op.run::<avx2::PackedBabyBearAvx2>();
+        }
+    }
+    op.run::<Portable<baby_bear::Elem, 8>>()
+}
+
+/// A vector of [PackedElem::WIDTH] base field elements, with arithmetic
+/// operating lane by lane.
+pub trait PackedElem:
+    ops::Add<Output = Self>
+    + ops::AddAssign
+    + ops::Sub<Output = Self>
+    + ops::SubAssign
+    + ops::Mul<Output = Self>
+    + ops::MulAssign
+    + ops::Neg<Output = Self>
+    + Clone
+    + Copy
+    + Send
+    + Sync
+    + Debug
+    + 'static
+{
+    /// The element held in each lane.
+    type Scalar: Elem;
+
+    /// The number of lanes.
+    const WIDTH: usize;
+
+    /// Return a vector with every lane set to `value`.
+    fn broadcast(value: Self::Scalar) -> Self;
+
+    /// Return a vector whose lane `i` is `f(i)`, calling `f` for each lane in
+    /// order.
+    fn from_fn<F: FnMut(usize) -> Self::Scalar>(f: F) -> Self;
+
+    /// Load the first [PackedElem::WIDTH] elements of `slice`.
+    ///
+    /// Panics if `slice` is shorter than [PackedElem::WIDTH].
+    fn load(slice: &[Self::Scalar]) -> Self;
+
+    /// Store the lanes to the first [PackedElem::WIDTH] elements of `slice`.
+    ///
+    /// Panics if `slice` is shorter than [PackedElem::WIDTH].
+    fn store(self, slice: &mut [Self::Scalar]);
+
+    /// Return the vector `[start, start * step, start * step^2, ...]`.
+    fn powers(start: Self::Scalar, step: Self::Scalar) -> Self {
+        let mut cur = start;
+        Self::from_fn(|_| {
+            let lane = cur;
+            cur *= step;
+            lane
+        })
+    }
+}
+
+/// A [PackedElem] for any field, made of an array of scalar elements.
+///
+/// This makes no use of SIMD instructions, although the compiler may still
+/// vectorize its loops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Portable<E: Elem, const WIDTH: usize>(pub [E; WIDTH]);
+
+impl<E: Elem, const WIDTH: usize> PackedElem for Portable<E, WIDTH> {
+    type Scalar = E;
+
+    const WIDTH: usize = WIDTH;
+
+    fn broadcast(value: E) -> Self {
+        Self([value; WIDTH])
+    }
+
+    fn from_fn<F: FnMut(usize) -> E>(f: F) -> Self {
+        Self(array::from_fn(f))
+    }
+
+    fn load(slice: &[E]) -> Self {
+        Self(slice[..WIDTH].try_into().unwrap())
+    }
+
+    fn store(self, slice: &mut [E]) {
+        slice[..WIDTH].copy_from_slice(&self.0);
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::Add for Portable<E, WIDTH> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::AddAssign for Portable<E, WIDTH> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::Sub for Portable<E, WIDTH> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::SubAssign for Portable<E, WIDTH> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::Mul for Portable<E, WIDTH> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(array::from_fn(|i| self.0[i] * rhs.0[i]))
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::MulAssign for Portable<E, WIDTH> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<E: Elem, const WIDTH: usize> ops::Neg for Portable<E, WIDTH> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.map(|x| E::ZERO - x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::{PackedElem, PackedOp, Portable};
+    use crate::field::{
+        baby_bear::{self, Elem},
+        Elem as _,
+    };
+
+    /// Check every operation of `T` against the scalar operations, lane by
+    /// lane, on edge cases and random elements.
+    pub fn test_packed<T: PackedElem<Scalar = Elem>>() {
+        let p = baby_bear::P;
+        let edges = [0, 1, 2, p / 2, (1 << 27) - 1, 1 << 27, p - 2, p - 1];
+        let mut lhs = Vec::new();
+        let mut rhs = Vec::new();
+        for x in edges {
+            for y in edges {
+                lhs.push(Elem::new(x));
+                rhs.push(Elem::new(y));
+            }
+        }
+        let mut rng = SmallRng::seed_from_u64(2);
+        for _ in 0..10_000 {
+            lhs.push(Elem::random(&mut rng));
+            rhs.push(Elem::random(&mut rng));
+        }
+
+        let mut out = [Elem::ZERO; 16];
+        let mut check = |packed: T, scalar: &dyn Fn(usize) -> Elem| {
+            packed.store(&mut out);
+            for (i, lane) in out[..T::WIDTH].iter().enumerate() {
+                assert_eq!(*lane, scalar(i));
+            }
+        };
+        for (x, y) in lhs.chunks_exact(T::WIDTH).zip(rhs.chunks_exact(T::WIDTH)) {
+            let (a, b) = (T::load(x), T::load(y));
+            check(a + b, &|i| x[i] + y[i]);
+            check(a - b, &|i| x[i] - y[i]);
+            check(a * b, &|i| x[i] * y[i]);
+            check(-a, &|i| -x[i]);
+
+            let mut c = a;
+            c += b;
+            c *= a;
+            c -= b;
+            check(c, &|i| (x[i] + y[i]) * x[i] - y[i]);
+        }
+
+        let start = Elem::new(3);
+        let step = Elem::new(7);
+        check(T::broadcast(step), &|_| step);
+        check(T::from_fn(|i| Elem::new(i as u32)), &|i| {
+            Elem::new(i as u32)
+        });
+        check(T::powers(start, step), &|i| start * step.pow(i));
+    }
+
+    #[test]
+    fn portable() {
+        test_packed::<Portable<Elem, 8>>();
+        test_packed::<Portable<Elem, 1>>();
+    }
+
+    #[test]
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    fn avx2() {
+        if std::is_x86_feature_detected!("avx2") {
+            test_packed::<super::avx2::PackedBabyBearAvx2>();
+        }
+    }
+
+    #[test]
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    fn avx512() {
+        if std::is_x86_feature_detected!("avx512f") {
+            test_packed::<super::avx512::PackedBabyBearAvx512>();
+        }
+    }
+
+    #[test]
+    fn with_packed() {
+        struct Check;
+
+        impl PackedOp<Elem> for Check {
+            type Output = ();
+
+            fn run<P: PackedElem<Scalar = Elem>>(self) {
+                test_packed::<P>();
+            }
+        }
+
+        super::with_packed_baby_bear(Check);
+    }
+}
@@ -16,7 +16,7 @@ use std::{fs, path::PathBuf, rc::Rc};
 
 use clap::{Args, Parser, ValueEnum};
 use risc0_zkvm::{
-    get_prover_server, ApiServer, ExecutorEnv, ExecutorImpl, ProverOpts, ProverServer,
+    get_prover_server, ApiDaemon, ApiServer, ExecutorEnv, ExecutorImpl, ProverOpts, ProverServer,
     VerifierContext,
 };
 
@@ -31,6 +31,10 @@ struct Cli {
     #[arg(long)]
     receipt: Option<PathBuf>,
 
+    /// The kind of receipt to produce.
+    #[arg(long, value_enum, default_value_t = ReceiptKind::Composite)]
+    receipt_kind: ReceiptKind,
+
     /// The hash function to use to produce a proof.
     #[arg(long, value_enum, default_value_t = HashFn::Poseidon)]
     hashfn: HashFn,
@@ -44,10 +48,33 @@ struct Cli {
     #[arg(long)]
     prove_guest_errors: bool,
 
+    /// The number of segments to prove concurrently.
+    #[arg(long, default_value_t = 1)]
+    segment_workers: usize,
+
+    /// Limit, in bytes, on the estimated memory used by segments being proven
+    /// concurrently.
+    #[arg(long)]
+    segment_memory_limit: Option<usize>,
+
+    /// The number of client connections to handle concurrently when running
+    /// with `--listen` or `--listen-unix`.
+    #[arg(long, default_value_t = 1)]
+    max_workers: usize,
+
     /// File to read initial input from.
     #[arg(long)]
     initial_input: Option<PathBuf>,
 
+    /// Print a breakdown of the cycles spent by each segment and by the whole
+    /// session to stderr.
+    #[arg(long)]
+    stats: bool,
+
+    /// Only execute the guest, without proving it.
+    #[arg(long, conflicts_with = "receipt")]
+    execute_only: bool,
+
     /// Display verbose output.
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -62,6 +89,19 @@ struct Cli {
     #[cfg(feature = "profiler")]
     #[arg(long)]
     pprof_out: Option<PathBuf>,
+
+    /// Write the guest's profile as folded stacks to this file, for use with
+    /// flamegraph tools such as inferno or flamegraph.pl.
+    #[cfg(feature = "profiler")]
+    #[arg(long)]
+    folded_out: Option<PathBuf>,
+
+    /// Write a Chrome trace of the guest's run to this file.
+    /// You can use chrome://tracing or Perfetto (<https://ui.perfetto.dev>)
+    /// to read it.
+    #[cfg(feature = "profiler")]
+    #[arg(long)]
+    chrome_trace_out: Option<PathBuf>,
 }
 
 #[derive(Args)]
@@ -70,6 +110,21 @@ struct Mode {
     #[arg(long)]
     port: Option<u16>,
 
+    /// Run as a long-lived server which accepts connections from many
+    /// clients on the specified address, e.g. `127.0.0.1:9000`.
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// Run as a long-lived server which accepts connections from many
+    /// clients on a Unix domain socket at the specified path.
+    #[cfg(unix)]
+    #[arg(long)]
+    listen_unix: Option<PathBuf>,
+
+    /// Serve a single client over stdin and stdout.
+    #[arg(long)]
+    stdio: bool,
+
     /// The ELF to execute
     #[arg(long)]
     elf: Option<PathBuf>,
@@ -85,6 +140,16 @@ enum HashFn {
     Sha256,
     #[value(name = "poseidon")]
     Poseidon,
+    #[value(name = "poseidon2")]
+    Poseidon2,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ReceiptKind {
+    #[value(name = "composite")]
+    Composite,
+    #[value(name = "succinct")]
+    Succinct,
 }
 
 pub fn main() {
@@ -95,11 +160,28 @@ pub fn main() {
         run_server(port);
         return;
     }
+    if args.mode.stdio {
+        ApiServer::new_stdio().run().unwrap();
+        return;
+    }
+    if let Some(ref addr) = args.mode.listen {
+        let daemon = ApiDaemon::bind(addr, args.max_workers).unwrap();
+        eprintln!("Listening on {}", daemon.local_addr().unwrap());
+        run_daemon(daemon);
+        return;
+    }
+    #[cfg(unix)]
+    if let Some(ref path) = args.mode.listen_unix {
+        let daemon = ApiDaemon::bind_unix(path, args.max_workers).unwrap();
+        eprintln!("Listening on {}", path.display());
+        run_daemon(daemon);
+        return;
+    }
 
     #[cfg(feature = "profiler")]
     let mut guest_prof: Option<risc0_zkvm::Profiler> = None;
     #[cfg(feature = "profiler")]
-    if args.pprof_out.is_some() {
+    if args.pprof_out.is_some() || args.folded_out.is_some() || args.chrome_trace_out.is_some() {
         let elf = args.mode.elf.clone().unwrap();
         let elf_contents = fs::read(&elf).unwrap();
         guest_prof = Some(risc0_zkvm::Profiler::new(elf.to_str().unwrap(), &elf_contents).unwrap());
@@ -121,7 +203,9 @@ pub fn main() {
 
         #[cfg(feature = "profiler")]
         if let Some(ref mut profiler) = guest_prof {
-            builder.trace_callback(profiler.make_trace_callback());
+            builder
+                .trace_callback(profiler.make_trace_callback())
+                .trace_cycles(true);
         }
 
         let env = builder.build().unwrap();
@@ -142,9 +226,27 @@ pub fn main() {
     #[cfg(feature = "profiler")]
     if let Some(ref mut profiler) = guest_prof.as_mut() {
         profiler.finalize();
-        let report = profiler.encode_to_vec();
-        fs::write(args.pprof_out.as_ref().unwrap(), &report)
-            .expect("Unable to write profiling output");
+        if let Some(ref path) = args.pprof_out {
+            fs::write(path, profiler.encode_to_vec()).expect("Unable to write profiling output");
+        }
+        if let Some(ref path) = args.folded_out {
+            fs::write(path, profiler.encode_folded()).expect("Unable to write profiling output");
+        }
+        if let Some(ref path) = args.chrome_trace_out {
+            fs::write(path, profiler.encode_chrome_trace())
+                .expect("Unable to write profiling output");
+        }
+    }
+
+    if args.stats {
+        for segment in session.resolve().unwrap() {
+            eprintln!("segment {}:\n{}", segment.index, segment.stats);
+        }
+        eprintln!("session:\n{}", session.stats);
+    }
+
+    if args.execute_only {
+        return;
     }
 
     let prover = args.get_prover();
@@ -170,11 +272,17 @@ impl Cli {
         let hashfn = match self.hashfn {
             HashFn::Sha256 => "sha-256",
             HashFn::Poseidon => "poseidon",
+            HashFn::Poseidon2 => "poseidon2",
         };
-        let opts = ProverOpts {
-            hashfn: hashfn.to_string(),
-            prove_guest_errors: self.prove_guest_errors,
-        };
+        let opts = ProverOpts::default()
+            .with_hashfn(hashfn)
+            .with_prove_guest_errors(self.prove_guest_errors)
+            .with_segment_workers(self.segment_workers)
+            .with_segment_memory_limit(self.segment_memory_limit)
+            .with_receipt_kind(match self.receipt_kind {
+                ReceiptKind::Composite => risc0_zkvm::ReceiptKind::Composite,
+                ReceiptKind::Succinct => risc0_zkvm::ReceiptKind::Succinct,
+            });
 
         get_prover_server(&opts).unwrap()
     }
@@ -185,3 +293,9 @@ fn run_server(port: u16) {
     let server = ApiServer::new_tcp(addr);
     server.run().unwrap()
 }
+
+fn run_daemon(daemon: ApiDaemon) {
+    let handle = daemon.handle().unwrap();
+    ctrlc::set_handler(move || handle.shutdown()).unwrap();
+    daemon.run().unwrap()
+}
@@ -17,12 +17,8 @@ use assert_cmd::cargo::cargo_bin;
 use risc0_zkvm::{ExecutorEnv, ExternalProver, Prover, Receipt};
 use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
 
-fn prove_nothing() -> Result<Receipt> {
-    let env = ExecutorEnv::builder()
-        .write(&MultiTestSpec::DoNothing)
-        .unwrap()
-        .build()
-        .unwrap();
+fn prove_spec(spec: &MultiTestSpec) -> Result<Receipt> {
+    let env = ExecutorEnv::builder().write(spec).unwrap().build().unwrap();
     let r0vm_path = cargo_bin("r0vm");
     let prover = ExternalProver::new("r0vm", r0vm_path);
     prover.prove_elf(env, MULTI_TEST_ELF)
@@ -30,6 +26,14 @@ fn prove_nothing() -> Result<Receipt> {
 
 #[test_log::test]
 fn basic_proof() {
-    let receipt = prove_nothing().unwrap();
+    let receipt = prove_spec(&MultiTestSpec::DoNothing).unwrap();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+}
+
+// Guest logs must not end up in the stdout protocol stream.
+#[test_log::test]
+fn guest_log_over_stdio() {
+    let spec = MultiTestSpec::BusyLoop { cycles: 1 };
+    let receipt = prove_spec(&spec).unwrap();
     receipt.verify(MULTI_TEST_ID).unwrap();
 }
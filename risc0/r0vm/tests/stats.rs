@@ -0,0 +1,35 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use assert_cmd::Command;
+use risc0_zkvm::serde::to_vec;
+use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_PATH};
+
+#[test]
+fn execute_only_stats() {
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+
+    let mut cmd = Command::cargo_bin("r0vm").unwrap();
+    cmd.arg("--elf")
+        .arg(MULTI_TEST_PATH)
+        .arg("--execute-only")
+        .arg("--stats")
+        .write_stdin(bytemuck::cast_slice(&input));
+
+    let output = cmd.assert().success().get_output().clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("segment 0:\n"));
+    assert!(stderr.contains("session:\n"));
+    assert!(stderr.contains("syscall ecall(halt): 1 calls"));
+}
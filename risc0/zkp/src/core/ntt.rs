@@ -17,7 +17,7 @@
 use core::ops::{Add, Mul, Sub};
 
 use paste::paste;
-use risc0_core::field::{Elem, RootsOfUnity};
+use risc0_core::field::{packed::PackedElem, Elem, RootsOfUnity};
 
 use super::log2_ceil;
 
@@ -168,6 +168,92 @@ butterfly!(3, 2);
 butterfly!(2, 1);
 butterfly!(1, 0);
 
+fn rev_butterfly<B, T>(io: &mut [T], n: usize)
+where
+    B: Elem + RootsOfUnity,
+    T: Copy + Mul<B, Output = T> + Add<Output = T> + Sub<Output = T>,
+{
+    match n {
+        0 => rev_butterfly_0::<B, T>(io),
+        1 => rev_butterfly_1(io),
+        2 => rev_butterfly_2(io),
+        3 => rev_butterfly_3(io),
+        4 => rev_butterfly_4(io),
+        5 => rev_butterfly_5(io),
+        6 => rev_butterfly_6(io),
+        7 => rev_butterfly_7(io),
+        8 => rev_butterfly_8(io),
+        9 => rev_butterfly_9(io),
+        10 => rev_butterfly_10(io),
+        11 => rev_butterfly_11(io),
+        12 => rev_butterfly_12(io),
+        13 => rev_butterfly_13(io),
+        14 => rev_butterfly_14(io),
+        15 => rev_butterfly_15(io),
+        16 => rev_butterfly_16(io),
+        17 => rev_butterfly_17(io),
+        18 => rev_butterfly_18(io),
+        19 => rev_butterfly_19(io),
+        20 => rev_butterfly_20(io),
+        21 => rev_butterfly_21(io),
+        22 => rev_butterfly_22(io),
+        23 => rev_butterfly_23(io),
+        24 => rev_butterfly_24(io),
+        25 => rev_butterfly_25(io),
+        26 => rev_butterfly_26(io),
+        27 => rev_butterfly_27(io),
+        28 => rev_butterfly_28(io),
+        29 => rev_butterfly_29(io),
+        30 => rev_butterfly_30(io),
+        31 => rev_butterfly_31(io),
+        32 => rev_butterfly_32(io),
+        _ => unreachable!(),
+    }
+}
+
+fn fwd_butterfly<B, T>(io: &mut [T], n: usize, expand_bits: usize)
+where
+    B: Elem + RootsOfUnity,
+    T: Copy + Mul<B, Output = T> + Add<Output = T> + Sub<Output = T>,
+{
+    match n {
+        0 => fwd_butterfly_0::<B, T>(io, expand_bits),
+        1 => fwd_butterfly_1(io, expand_bits),
+        2 => fwd_butterfly_2(io, expand_bits),
+        3 => fwd_butterfly_3(io, expand_bits),
+        4 => fwd_butterfly_4(io, expand_bits),
+        5 => fwd_butterfly_5(io, expand_bits),
+        6 => fwd_butterfly_6(io, expand_bits),
+        7 => fwd_butterfly_7(io, expand_bits),
+        8 => fwd_butterfly_8(io, expand_bits),
+        9 => fwd_butterfly_9(io, expand_bits),
+        10 => fwd_butterfly_10(io, expand_bits),
+        11 => fwd_butterfly_11(io, expand_bits),
+        12 => fwd_butterfly_12(io, expand_bits),
+        13 => fwd_butterfly_13(io, expand_bits),
+        14 => fwd_butterfly_14(io, expand_bits),
+        15 => fwd_butterfly_15(io, expand_bits),
+        16 => fwd_butterfly_16(io, expand_bits),
+        17 => fwd_butterfly_17(io, expand_bits),
+        18 => fwd_butterfly_18(io, expand_bits),
+        19 => fwd_butterfly_19(io, expand_bits),
+        20 => fwd_butterfly_20(io, expand_bits),
+        21 => fwd_butterfly_21(io, expand_bits),
+        22 => fwd_butterfly_22(io, expand_bits),
+        23 => fwd_butterfly_23(io, expand_bits),
+        24 => fwd_butterfly_24(io, expand_bits),
+        25 => fwd_butterfly_25(io, expand_bits),
+        26 => fwd_butterfly_26(io, expand_bits),
+        27 => fwd_butterfly_27(io, expand_bits),
+        28 => fwd_butterfly_28(io, expand_bits),
+        29 => fwd_butterfly_29(io, expand_bits),
+        30 => fwd_butterfly_30(io, expand_bits),
+        31 => fwd_butterfly_31(io, expand_bits),
+        32 => fwd_butterfly_32(io, expand_bits),
+        _ => unreachable!(),
+    }
+}
+
 /// Perform a reverse butterfly transform of a buffer of (1 << n) numbers.
 /// The result of this computation is a discrete Fourier transform, but with
 /// changed indices. This is described [here](https://en.wikipedia.org/wiki/Cooley%E2%80%93Tukey_FFT_algorithm#Data_reordering,_bit_reversal,_and_in-place_algorithms).
@@ -238,42 +324,7 @@ where
     let size = io.len();
     let n = log2_ceil(size);
     assert_eq!(1 << n, size);
-    match n {
-        0 => rev_butterfly_0::<B, T>(io),
-        1 => rev_butterfly_1(io),
-        2 => rev_butterfly_2(io),
-        3 => rev_butterfly_3(io),
-        4 => rev_butterfly_4(io),
-        5 => rev_butterfly_5(io),
-        6 => rev_butterfly_6(io),
-        7 => rev_butterfly_7(io),
-        8 => rev_butterfly_8(io),
-        9 => rev_butterfly_9(io),
-        10 => rev_butterfly_10(io),
-        11 => rev_butterfly_11(io),
-        12 => rev_butterfly_12(io),
-        13 => rev_butterfly_13(io),
-        14 => rev_butterfly_14(io),
-        15 => rev_butterfly_15(io),
-        16 => rev_butterfly_16(io),
-        17 => rev_butterfly_17(io),
-        18 => rev_butterfly_18(io),
-        19 => rev_butterfly_19(io),
-        20 => rev_butterfly_20(io),
-        21 => rev_butterfly_21(io),
-        22 => rev_butterfly_22(io),
-        23 => rev_butterfly_23(io),
-        24 => rev_butterfly_24(io),
-        25 => rev_butterfly_25(io),
-        26 => rev_butterfly_26(io),
-        27 => rev_butterfly_27(io),
-        28 => rev_butterfly_28(io),
-        29 => rev_butterfly_29(io),
-        30 => rev_butterfly_30(io),
-        31 => rev_butterfly_31(io),
-        32 => rev_butterfly_32(io),
-        _ => unreachable!(),
-    }
+    rev_butterfly::<B, T>(io, n);
     let norm = B::from_u64(size as u64).inv();
     for x in io.iter_mut().take(size) {
         *x = *x * norm;
@@ -287,48 +338,108 @@ where
     B: Elem + RootsOfUnity,
     T: Copy + Mul<B, Output = T> + Add<Output = T> + Sub<Output = T>,
 {
-    // do_ntt::<T, false>(io, expand_bits);
     let size = io.len();
     let n = log2_ceil(size);
     assert_eq!(1 << n, size);
-    match n {
-        0 => fwd_butterfly_0::<B, T>(io, expand_bits),
-        1 => fwd_butterfly_1(io, expand_bits),
-        2 => fwd_butterfly_2(io, expand_bits),
-        3 => fwd_butterfly_3(io, expand_bits),
-        4 => fwd_butterfly_4(io, expand_bits),
-        5 => fwd_butterfly_5(io, expand_bits),
-        6 => fwd_butterfly_6(io, expand_bits),
-        7 => fwd_butterfly_7(io, expand_bits),
-        8 => fwd_butterfly_8(io, expand_bits),
-        9 => fwd_butterfly_9(io, expand_bits),
-        10 => fwd_butterfly_10(io, expand_bits),
-        11 => fwd_butterfly_11(io, expand_bits),
-        12 => fwd_butterfly_12(io, expand_bits),
-        13 => fwd_butterfly_13(io, expand_bits),
-        14 => fwd_butterfly_14(io, expand_bits),
-        15 => fwd_butterfly_15(io, expand_bits),
-        16 => fwd_butterfly_16(io, expand_bits),
-        17 => fwd_butterfly_17(io, expand_bits),
-        18 => fwd_butterfly_18(io, expand_bits),
-        19 => fwd_butterfly_19(io, expand_bits),
-        20 => fwd_butterfly_20(io, expand_bits),
-        21 => fwd_butterfly_21(io, expand_bits),
-        22 => fwd_butterfly_22(io, expand_bits),
-        23 => fwd_butterfly_23(io, expand_bits),
-        24 => fwd_butterfly_24(io, expand_bits),
-        25 => fwd_butterfly_25(io, expand_bits),
-        26 => fwd_butterfly_26(io, expand_bits),
-        27 => fwd_butterfly_27(io, expand_bits),
-        28 => fwd_butterfly_28(io, expand_bits),
-        29 => fwd_butterfly_29(io, expand_bits),
-        30 => fwd_butterfly_30(io, expand_bits),
-        31 => fwd_butterfly_31(io, expand_bits),
-        32 => fwd_butterfly_32(io, expand_bits),
-        _ => unreachable!(),
+    fwd_butterfly::<B, T>(io, n, expand_bits);
+}
+
+/// Perform [interpolate_ntt] on a buffer of (1 << n) base field elements,
+/// using packed arithmetic for every layer of the transform that is at least
+/// [PackedElem::WIDTH] wide. The result is identical to [interpolate_ntt].
+pub fn interpolate_ntt_packed<P>(io: &mut [P::Scalar])
+where
+    P: PackedElem,
+    P::Scalar: RootsOfUnity,
+{
+    let size = io.len();
+    let n = log2_ceil(size);
+    assert_eq!(1 << n, size);
+    rev_butterfly_packed::<P>(io, n);
+    let norm = P::Scalar::from_u64(size as u64).inv();
+    let packed_norm = P::broadcast(norm);
+    let mut chunks = io.chunks_exact_mut(P::WIDTH);
+    for chunk in &mut chunks {
+        (P::load(chunk) * packed_norm).store(chunk);
+    }
+    for x in chunks.into_remainder() {
+        *x *= norm;
+    }
+}
+
+/// Perform [evaluate_ntt] on a buffer of (1 << n) base field elements, using
+/// packed arithmetic for every layer of the transform that is at least
+/// [PackedElem::WIDTH] wide. The result is identical to [evaluate_ntt].
+pub fn evaluate_ntt_packed<P>(io: &mut [P::Scalar], expand_bits: usize)
+where
+    P: PackedElem,
+    P::Scalar: RootsOfUnity,
+{
+    let size = io.len();
+    let n = log2_ceil(size);
+    assert_eq!(1 << n, size);
+    fwd_butterfly_packed::<P>(io, n, expand_bits);
+}
+
+fn fwd_butterfly_packed<P>(io: &mut [P::Scalar], n: usize, expand_bits: usize)
+where
+    P: PackedElem,
+    P::Scalar: RootsOfUnity,
+{
+    let half = (1 << n) / 2;
+    if half < P::WIDTH {
+        fwd_butterfly::<P::Scalar, P::Scalar>(io, n, expand_bits);
+        return;
+    }
+    if n == expand_bits {
+        return;
+    }
+    fwd_butterfly_packed::<P>(&mut io[..half], n - 1, expand_bits);
+    fwd_butterfly_packed::<P>(&mut io[half..], n - 1, expand_bits);
+    let step = <P::Scalar as RootsOfUnity>::ROU_FWD[n];
+    let (lo, hi) = io.split_at_mut(half);
+    let mut cur = P::powers(P::Scalar::ONE, step);
+    let stride = P::broadcast(step.pow(P::WIDTH));
+    for (lo, hi) in lo
+        .chunks_exact_mut(P::WIDTH)
+        .zip(hi.chunks_exact_mut(P::WIDTH))
+    {
+        let a = P::load(lo);
+        let b = P::load(hi) * cur;
+        (a + b).store(lo);
+        (a - b).store(hi);
+        cur *= stride;
     }
 }
 
+fn rev_butterfly_packed<P>(io: &mut [P::Scalar], n: usize)
+where
+    P: PackedElem,
+    P::Scalar: RootsOfUnity,
+{
+    let half = (1 << n) / 2;
+    if half < P::WIDTH {
+        rev_butterfly::<P::Scalar, P::Scalar>(io, n);
+        return;
+    }
+    let step = <P::Scalar as RootsOfUnity>::ROU_REV[n];
+    let (lo, hi) = io.split_at_mut(half);
+    let mut cur = P::powers(P::Scalar::ONE, step);
+    let stride = P::broadcast(step.pow(P::WIDTH));
+    for (lo, hi) in lo
+        .chunks_exact_mut(P::WIDTH)
+        .zip(hi.chunks_exact_mut(P::WIDTH))
+    {
+        let a = P::load(lo);
+        let b = P::load(hi);
+        (a + b).store(lo);
+        ((a - b) * cur).store(hi);
+        cur *= stride;
+    }
+    rev_butterfly_packed::<P>(lo, n - 1);
+    rev_butterfly_packed::<P>(hi, n - 1);
+}
+
 /// Expand the `input` into `output` to support polynomial evaluation on
 /// `input.len() * (1 << expand_bits)` points.
 pub fn expand<T>(output: &mut [T], input: &[T], expand_bits: usize)
@@ -343,12 +454,19 @@ where
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
+
     use rand::thread_rng;
     use risc0_core::field::{
-        baby_bear::BabyBearElem, goldilocks::GoldilocksElem, Elem, RootsOfUnity,
+        baby_bear::{BabyBear, BabyBearElem},
+        goldilocks::GoldilocksElem,
+        packed::{PackedElem, PackedOp, Portable},
+        Elem, Field, RootsOfUnity,
     };
 
-    use crate::core::ntt::{bit_reverse, evaluate_ntt, interpolate_ntt};
+    use crate::core::ntt::{
+        bit_reverse, evaluate_ntt, evaluate_ntt_packed, interpolate_ntt, interpolate_ntt_packed,
+    };
 
     // Compare the complex version to the naive version
     #[test]
@@ -454,4 +572,45 @@ mod tests {
         }
         assert_eq!(goal, buf);
     }
+
+    fn cmp_packed<P: PackedElem<Scalar = BabyBearElem>>() {
+        let mut rng = thread_rng();
+        for n in 0..12 {
+            let orig: Vec<_> = (0..1 << n)
+                .map(|_| BabyBearElem::random(&mut rng))
+                .collect();
+
+            let mut goal = orig.clone();
+            interpolate_ntt::<BabyBearElem, BabyBearElem>(&mut goal);
+            let mut buf = orig.clone();
+            interpolate_ntt_packed::<P>(&mut buf);
+            assert_eq!(goal, buf);
+
+            for expand_bits in 0..=n.min(2) {
+                let mut goal = orig.clone();
+                evaluate_ntt::<BabyBearElem, BabyBearElem>(&mut goal, expand_bits);
+                let mut buf = orig.clone();
+                evaluate_ntt_packed::<P>(&mut buf, expand_bits);
+                assert_eq!(goal, buf);
+            }
+        }
+    }
+
+    // Make sure the packed transforms match the scalar ones
+    #[test]
+    fn packed() {
+        struct CmpPacked;
+
+        impl PackedOp<BabyBearElem> for CmpPacked {
+            type Output = ();
+
+            fn run<P: PackedElem<Scalar = BabyBearElem>>(self) {
+                cmp_packed::<P>();
+            }
+        }
+
+        BabyBear::with_packed(CmpPacked);
+        cmp_packed::<Portable<BabyBearElem, 1>>();
+        cmp_packed::<Portable<BabyBearElem, 4>>();
+    }
 }
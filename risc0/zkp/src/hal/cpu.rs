@@ -13,6 +13,11 @@
 // limitations under the License.
 
 //! CPU implementation of the HAL.
+//!
+//! The NTTs and the arithmetic-heavy kernels operate on
+//! [packed](risc0_core::field::packed) field elements, which use AVX2 or
+//! AVX-512 when the CPU supports them. Each kernel is a [PackedOp], so that
+//! [Field::with_packed] can choose the packed implementation once per call.
 
 use core::{
     cell::{Ref, RefMut},
@@ -23,7 +28,10 @@ use std::{cell::RefCell, fmt::Debug, rc::Rc};
 use bytemuck::Pod;
 use ndarray::{ArrayView, ArrayViewMut, Axis};
 use rayon::prelude::*;
-use risc0_core::field::{Elem, ExtElem, Field};
+use risc0_core::field::{
+    packed::{PackedElem, PackedOp, Portable},
+    Elem, ExtElem, Field, RootsOfUnity,
+};
 
 use super::{Buffer, Hal, TRACKER};
 use crate::{
@@ -31,7 +39,7 @@ use crate::{
         digest::Digest,
         hash::HashSuite,
         log2_ceil,
-        ntt::{bit_rev_32, bit_reverse, evaluate_ntt, expand, interpolate_ntt},
+        ntt::{bit_rev_32, bit_reverse, evaluate_ntt_packed, expand, interpolate_ntt_packed},
     },
     FRI_FOLD,
 };
@@ -323,12 +331,11 @@ impl<F: Field> Hal for CpuHal<F> {
         {
             let row_size = output.size() / count;
             assert_eq!(row_size * count, output.size());
-            output
-                .as_slice_mut()
-                .par_chunks_exact_mut(row_size)
-                .for_each(|row| {
-                    evaluate_ntt::<Self::Elem, Self::Elem>(row, expand_bits);
-                });
+            F::with_packed(EvaluateNtt {
+                io: &mut output.as_slice_mut(),
+                row_size,
+                expand_bits,
+            });
         }
     }
 
@@ -336,11 +343,10 @@ impl<F: Field> Hal for CpuHal<F> {
     fn batch_interpolate_ntt(&self, io: &Self::Buffer<Self::Elem>, count: usize) {
         let row_size = io.size() / count;
         assert_eq!(row_size * count, io.size());
-        io.as_slice_mut()
-            .par_chunks_exact_mut(row_size)
-            .for_each(|row| {
-                interpolate_ntt::<Self::Elem, Self::Elem>(row);
-            });
+        F::with_packed(InterpolateNtt {
+            io: &mut io.as_slice_mut(),
+            row_size,
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -435,20 +441,14 @@ impl<F: Field> Hal for CpuHal<F> {
         let mix_pows: &[Self::ExtElem] = mix_pows.as_slice();
         let input: &[Self::Elem] = &input.as_slice();
 
-        output
-            .as_slice_mut()
-            .par_chunks_exact_mut(count)
-            .enumerate()
-            .for_each(|(id, out_chunk): (usize, &mut [Self::ExtElem])| {
-                for i in 0..input_size {
-                    if combos[i] != id as u32 {
-                        continue;
-                    }
-                    for idx in 0..count {
-                        out_chunk[idx] += mix_pows[i] * input[count * i + idx];
-                    }
-                }
-            });
+        F::with_packed(MixPolyCoeffs::<F> {
+            output: &mut output.as_slice_mut(),
+            mix_pows,
+            input,
+            combos,
+            input_size,
+            count,
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -463,11 +463,11 @@ impl<F: Field> Hal for CpuHal<F> {
         let mut output = output.as_slice_mut();
         let input1 = input1.as_slice();
         let input2 = input2.as_slice();
-        (&mut output[..], &input1[..], &input2[..])
-            .into_par_iter()
-            .for_each(|(o, a, b)| {
-                *o = *a + *b;
-            });
+        F::with_packed(EltwiseAddElem {
+            output: &mut output,
+            input1: &input1,
+            input2: &input2,
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -525,26 +525,40 @@ impl<F: Field> Hal for CpuHal<F> {
         let count = output.size() / Self::ExtElem::EXT_SIZE;
         assert_eq!(output.size(), count * Self::ExtElem::EXT_SIZE);
         assert_eq!(input.size(), output.size() * FRI_FOLD);
-        let mut output = output.as_slice_mut();
-        let input = input.as_slice();
+        let output = output.as_slice_sync();
+        let input: &[Self::Elem] = &input.as_slice();
 
-        // TODO: parallelize
-        for idx in 0..count {
-            let mut tot = Self::ExtElem::ZERO;
-            let mut cur_mix = Self::ExtElem::ONE;
-            for i in 0..FRI_FOLD {
-                let rev_i = bit_rev_32(i as u32) >> (32 - log2_ceil(FRI_FOLD));
-                let rev_idx = rev_i as usize * count + idx;
-                let factor = Self::ExtElem::from_subelems(
-                    (0..Self::ExtElem::EXT_SIZE).map(|i| input[i * count * FRI_FOLD + rev_idx]),
-                );
-                tot += cur_mix * factor;
-                cur_mix *= *mix;
-            }
-            for i in 0..Self::ExtElem::EXT_SIZE {
-                output[count * i + idx] = tot.subelems()[i];
+        // Multiplying by a fixed extension field element is linear over the
+        // base field, so each power of `mix` becomes a matrix of base field
+        // elements that can be applied to packed subelements.
+        let ext_size = Self::ExtElem::EXT_SIZE;
+        let mut cur_mix = Self::ExtElem::ONE;
+        let mut mix_matrices = Vec::with_capacity(FRI_FOLD * ext_size * ext_size);
+        for _ in 0..FRI_FOLD {
+            let columns: Vec<_> = (0..ext_size)
+                .map(|j| {
+                    let basis = Self::ExtElem::from_subelems((0..ext_size).map(|k| {
+                        if k == j {
+                            Self::Elem::ONE
+                        } else {
+                            Self::Elem::ZERO
+                        }
+                    }));
+                    cur_mix * basis
+                })
+                .collect();
+            for k in 0..ext_size {
+                mix_matrices.extend(columns.iter().map(|column| column.subelems()[k]));
             }
+            cur_mix *= *mix;
         }
+
+        F::with_packed(FriFold::<F> {
+            output: &output,
+            input,
+            mix_matrices: &mix_matrices,
+            count,
+        });
     }
 
     #[tracing::instrument(skip_all)]
@@ -600,14 +614,271 @@ impl<F: Field> Hal for CpuHal<F> {
     }
 }
 
+/// Run [evaluate_ntt_packed] on each row of `io`.
+struct EvaluateNtt<'a, E> {
+    io: &'a mut [E],
+    row_size: usize,
+    expand_bits: usize,
+}
+
+impl<E: Elem + RootsOfUnity> PackedOp<E> for EvaluateNtt<'_, E> {
+    type Output = ();
+
+    fn run<P: PackedElem<Scalar = E>>(self) {
+        let expand_bits = self.expand_bits;
+        self.io
+            .par_chunks_exact_mut(self.row_size)
+            .for_each(|row| evaluate_ntt_packed::<P>(row, expand_bits));
+    }
+}
+
+/// Run [interpolate_ntt_packed] on each row of `io`.
+struct InterpolateNtt<'a, E> {
+    io: &'a mut [E],
+    row_size: usize,
+}
+
+impl<E: Elem + RootsOfUnity> PackedOp<E> for InterpolateNtt<'_, E> {
+    type Output = ();
+
+    fn run<P: PackedElem<Scalar = E>>(self) {
+        self.io
+            .par_chunks_exact_mut(self.row_size)
+            .for_each(|row| interpolate_ntt_packed::<P>(row));
+    }
+}
+
+/// The body of [CpuHal::mix_poly_coeffs].
+struct MixPolyCoeffs<'a, F: Field> {
+    output: &'a mut [F::ExtElem],
+    mix_pows: &'a [F::ExtElem],
+    input: &'a [F::Elem],
+    combos: &'a [u32],
+    input_size: usize,
+    count: usize,
+}
+
+impl<F: Field> PackedOp<F::Elem> for MixPolyCoeffs<'_, F> {
+    type Output = ();
+
+    fn run<P: PackedElem<Scalar = F::Elem>>(self) {
+        let Self {
+            output,
+            mix_pows,
+            input,
+            combos,
+            input_size,
+            count,
+        } = self;
+        output
+            .par_chunks_exact_mut(count)
+            .enumerate()
+            .for_each(|(id, out_chunk)| {
+                let rows: Vec<usize> = (0..input_size)
+                    .filter(|&i| combos[i] == id as u32)
+                    .collect();
+                let width = P::WIDTH;
+                let packed_count = count - count % width;
+                let mut scratch = Scratch::<P>::new(F::ExtElem::EXT_SIZE);
+                for idx in (0..packed_count).step_by(width) {
+                    mix_poly_coeffs_block::<P, F>(
+                        &mut scratch,
+                        &mut out_chunk[idx..idx + width],
+                        mix_pows,
+                        input,
+                        &rows,
+                        count,
+                        idx,
+                    );
+                }
+                let mut scratch = Scratch::<Portable<F::Elem, 1>>::new(F::ExtElem::EXT_SIZE);
+                for idx in packed_count..count {
+                    mix_poly_coeffs_block::<Portable<F::Elem, 1>, F>(
+                        &mut scratch,
+                        &mut out_chunk[idx..idx + 1],
+                        mix_pows,
+                        input,
+                        &rows,
+                        count,
+                        idx,
+                    );
+                }
+            });
+    }
+}
+
+/// The body of [CpuHal::eltwise_add_elem].
+struct EltwiseAddElem<'a, E> {
+    output: &'a mut [E],
+    input1: &'a [E],
+    input2: &'a [E],
+}
+
+impl<E: Elem> PackedOp<E> for EltwiseAddElem<'_, E> {
+    type Output = ();
+
+    fn run<P: PackedElem<Scalar = E>>(self) {
+        let width = P::WIDTH;
+        (
+            self.output.par_chunks_mut(width),
+            self.input1.par_chunks(width),
+            self.input2.par_chunks(width),
+        )
+            .into_par_iter()
+            .for_each(|(o, a, b)| {
+                if o.len() == width {
+                    (P::load(a) + P::load(b)).store(o);
+                } else {
+                    for ((o, a), b) in o.iter_mut().zip(a).zip(b) {
+                        *o = *a + *b;
+                    }
+                }
+            });
+    }
+}
+
+/// The body of [CpuHal::fri_fold], after the powers of `mix` have been turned
+/// into `mix_matrices`.
+struct FriFold<'a, F: Field> {
+    output: &'a SyncSlice<'a, F::Elem>,
+    input: &'a [F::Elem],
+    mix_matrices: &'a [F::Elem],
+    count: usize,
+}
+
+impl<F: Field> PackedOp<F::Elem> for FriFold<'_, F> {
+    type Output = ();
+
+    fn run<P: PackedElem<Scalar = F::Elem>>(self) {
+        let Self {
+            output,
+            input,
+            mix_matrices,
+            count,
+        } = self;
+        let ext_size = F::ExtElem::EXT_SIZE;
+        let width = P::WIDTH;
+        let packed_count = count - count % width;
+        (0..packed_count / width).into_par_iter().for_each_init(
+            || Scratch::<P>::new(ext_size),
+            |scratch, block| {
+                fri_fold_block::<P, F>(scratch, output, input, mix_matrices, count, block * width);
+            },
+        );
+        let mut scratch = Scratch::<Portable<F::Elem, 1>>::new(ext_size);
+        for idx in packed_count..count {
+            fri_fold_block::<Portable<F::Elem, 1>, F>(
+                &mut scratch,
+                output,
+                input,
+                mix_matrices,
+                count,
+                idx,
+            );
+        }
+    }
+}
+
+/// Buffers reused across the blocks handled by a packed kernel.
+struct Scratch<P: PackedElem> {
+    acc: Vec<P>,
+    factor: Vec<P>,
+    lanes: Vec<P::Scalar>,
+}
+
+impl<P: PackedElem> Scratch<P> {
+    fn new(ext_size: usize) -> Self {
+        Self {
+            acc: vec![P::broadcast(P::Scalar::ZERO); ext_size],
+            factor: vec![P::broadcast(P::Scalar::ZERO); ext_size],
+            lanes: vec![P::Scalar::ZERO; ext_size * P::WIDTH],
+        }
+    }
+}
+
+/// Add the mixed rows of `input` to the `P::WIDTH` elements of `out`,
+/// starting at column `idx`.
+fn mix_poly_coeffs_block<P, F>(
+    scratch: &mut Scratch<P>,
+    out: &mut [F::ExtElem],
+    mix_pows: &[F::ExtElem],
+    input: &[F::Elem],
+    rows: &[usize],
+    count: usize,
+    idx: usize,
+) where
+    P: PackedElem<Scalar = F::Elem>,
+    F: Field,
+{
+    let width = P::WIDTH;
+    for (k, acc) in scratch.acc.iter_mut().enumerate() {
+        *acc = P::from_fn(|j| out[j].subelems()[k]);
+    }
+    for &i in rows {
+        let x = P::load(&input[count * i + idx..]);
+        for (acc, mix) in scratch.acc.iter_mut().zip(mix_pows[i].subelems()) {
+            *acc += P::broadcast(*mix) * x;
+        }
+    }
+    for (acc, lanes) in scratch
+        .acc
+        .iter()
+        .zip(scratch.lanes.chunks_exact_mut(width))
+    {
+        acc.store(lanes);
+    }
+    let ext_size = F::ExtElem::EXT_SIZE;
+    for (j, out) in out.iter_mut().enumerate() {
+        *out = F::ExtElem::from_subelems((0..ext_size).map(|k| scratch.lanes[k * width + j]));
+    }
+}
+
+/// Fold the `P::WIDTH` columns of `input` starting at column `idx` into
+/// `output`.
+fn fri_fold_block<P, F>(
+    scratch: &mut Scratch<P>,
+    output: &SyncSlice<F::Elem>,
+    input: &[F::Elem],
+    mix_matrices: &[F::Elem],
+    count: usize,
+    idx: usize,
+) where
+    P: PackedElem<Scalar = F::Elem>,
+    F: Field,
+{
+    let ext_size = F::ExtElem::EXT_SIZE;
+    scratch.acc.fill(P::broadcast(F::Elem::ZERO));
+    for (i, matrix) in mix_matrices.chunks_exact(ext_size * ext_size).enumerate() {
+        let rev_i = bit_rev_32(i as u32) >> (32 - log2_ceil(FRI_FOLD));
+        let rev_idx = rev_i as usize * count + idx;
+        for (j, factor) in scratch.factor.iter_mut().enumerate() {
+            *factor = P::load(&input[j * count * FRI_FOLD + rev_idx..]);
+        }
+        for (acc, row) in scratch.acc.iter_mut().zip(matrix.chunks_exact(ext_size)) {
+            for (factor, m) in scratch.factor.iter().zip(row) {
+                *acc += P::broadcast(*m) * *factor;
+            }
+        }
+    }
+    for (k, acc) in scratch.acc.iter().enumerate() {
+        acc.store(&mut scratch.lanes);
+        for (j, lane) in scratch.lanes[..P::WIDTH].iter().enumerate() {
+            output.set(count * k + idx + j, *lane);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use hex::FromHex;
     use rand::thread_rng;
-    use risc0_core::field::baby_bear::BabyBear;
+    use risc0_core::field::baby_bear::{BabyBear, BabyBearElem, BabyBearExtElem};
 
     use super::*;
-    use crate::core::hash::sha::Sha256HashSuite;
+    use crate::core::{
+        hash::sha::Sha256HashSuite,
+        ntt::{evaluate_ntt, interpolate_ntt},
+    };
 
     #[test]
     #[should_panic]
@@ -630,6 +901,122 @@ mod tests {
             |a, b| *a + *b,
             COUNT,
         );
+        // A size that is not a multiple of the packed width.
+        test_binary(
+            &hal,
+            |o, a, b| {
+                hal.eltwise_add_elem(o, a, b);
+            },
+            |a, b| *a + *b,
+            COUNT + 3,
+        );
+    }
+
+    fn random_elems(count: usize) -> Vec<BabyBearElem> {
+        let mut rng = thread_rng();
+        (0..count).map(|_| BabyBearElem::random(&mut rng)).collect()
+    }
+
+    #[test]
+    fn batch_ntt() {
+        let hal: CpuHal<BabyBear> = CpuHal::new(Sha256HashSuite::new_suite());
+        const COUNT: usize = 3;
+        for po2 in [0, 2, 5, 10] {
+            let size = 1 << po2;
+            let coeffs = random_elems(COUNT * size);
+
+            let mut golden = coeffs.clone();
+            for row in golden.chunks_exact_mut(size) {
+                interpolate_ntt::<BabyBearElem, BabyBearElem>(row);
+            }
+            let io = hal.copy_from_elem("io", &coeffs);
+            hal.batch_interpolate_ntt(&io, COUNT);
+            io.view(|io| assert_eq!(io, &golden[..]));
+
+            let expand_bits = 2;
+            let mut golden = vec![BabyBearElem::ZERO; COUNT * (size << expand_bits)];
+            for (output, input) in golden
+                .chunks_exact_mut(size << expand_bits)
+                .zip(coeffs.chunks_exact(size))
+            {
+                expand(output, input, expand_bits);
+                evaluate_ntt::<BabyBearElem, BabyBearElem>(output, expand_bits);
+            }
+            let input = hal.copy_from_elem("input", &coeffs);
+            let output = hal.alloc_elem("output", golden.len());
+            hal.batch_expand_into_evaluate_ntt(&output, &input, COUNT, expand_bits);
+            output.view(|output| assert_eq!(output, &golden[..]));
+        }
+    }
+
+    #[test]
+    fn mix_poly_coeffs() {
+        let hal: CpuHal<BabyBear> = CpuHal::new(Sha256HashSuite::new_suite());
+        let mut rng = thread_rng();
+        let mix_start = BabyBearExtElem::random(&mut rng);
+        let mix = BabyBearExtElem::random(&mut rng);
+        const COMBOS: usize = 3;
+        const INPUT_SIZE: usize = 7;
+        for count in [1, 16, 37] {
+            let input = random_elems(INPUT_SIZE * count);
+            let combos: Vec<u32> = (0..INPUT_SIZE as u32).map(|i| i % COMBOS as u32).collect();
+            let start: Vec<_> = (0..COMBOS * count)
+                .map(|_| BabyBearExtElem::random(&mut rng))
+                .collect();
+
+            let mut golden = start.clone();
+            let mut mix_cur = mix_start;
+            for i in 0..INPUT_SIZE {
+                for idx in 0..count {
+                    golden[combos[i] as usize * count + idx] += mix_cur * input[count * i + idx];
+                }
+                mix_cur *= mix;
+            }
+
+            let output = hal.copy_from_extelem("output", &start);
+            hal.mix_poly_coeffs(
+                &output,
+                &mix_start,
+                &mix,
+                &hal.copy_from_elem("input", &input),
+                &hal.copy_from_u32("combos", &combos),
+                INPUT_SIZE,
+                count,
+            );
+            output.view(|output| assert_eq!(output, &golden[..]));
+        }
+    }
+
+    #[test]
+    fn fri_fold() {
+        let hal: CpuHal<BabyBear> = CpuHal::new(Sha256HashSuite::new_suite());
+        let mix = BabyBearExtElem::random(&mut thread_rng());
+        let ext_size = BabyBearExtElem::EXT_SIZE;
+        for count in [1, 16, 37, 1024] {
+            let input = random_elems(count * ext_size * FRI_FOLD);
+
+            let mut golden = vec![BabyBearElem::ZERO; count * ext_size];
+            for idx in 0..count {
+                let mut tot = BabyBearExtElem::ZERO;
+                let mut cur_mix = BabyBearExtElem::ONE;
+                for i in 0..FRI_FOLD {
+                    let rev_i = bit_rev_32(i as u32) >> (32 - log2_ceil(FRI_FOLD));
+                    let rev_idx = rev_i as usize * count + idx;
+                    let factor = BabyBearExtElem::from_subelems(
+                        (0..ext_size).map(|k| input[k * count * FRI_FOLD + rev_idx]),
+                    );
+                    tot += cur_mix * factor;
+                    cur_mix *= mix;
+                }
+                for k in 0..ext_size {
+                    golden[count * k + idx] = tot.subelems()[k];
+                }
+            }
+
+            let output = hal.alloc_elem("output", golden.len());
+            hal.fri_fold(&output, &hal.copy_from_elem("input", &input), &mix);
+            output.view(|output| assert_eq!(output, &golden[..]));
+        }
     }
 
     fn test_binary<H, HF, CF>(hal: &H, hal_fn: HF, cpu_fn: CF, count: usize)
@@ -28,7 +28,7 @@ struct Args {
     #[arg(short, long)]
     iterations: u32,
 
-    /// Specify the hash function to use.
+    /// Specify the hash function to use: sha-256, poseidon or poseidon2.
     #[arg(short, long)]
     hashfn: Option<String>,
 
@@ -41,7 +41,7 @@ struct Args {
     #[arg(long, short)]
     iterations: Option<u64>,
 
-    /// Specify the hash function to use.
+    /// Specify the hash function to use: sha-256, poseidon or poseidon2.
     #[arg(short = 'f', long)]
     hashfn: Option<String>,
 
@@ -36,6 +36,7 @@ fn main() {
 
     let map = HashMap::from([
         ("risc0-zkvm-methods-guest", GuestOptions::default()),
+        ("risc0-zkvm-methods-heap", GuestOptions::default()),
         (
             "risc0-zkvm-methods-std",
             GuestOptions {
@@ -29,10 +29,13 @@ use risc0_zkvm::{
     sha::{Digest, Sha256},
     ReceiptMetadata,
 };
-use risc0_zkvm_methods::multi_test::{MultiTestSpec, SYS_MULTI_TEST};
+use risc0_zkvm_methods::{
+    heap,
+    multi_test::{MultiTestSpec, SYS_MULTI_TEST},
+};
 use risc0_zkvm_platform::{
     fileno, memory,
-    syscall::{bigint, sys_bigint, sys_read, sys_read_words, sys_write},
+    syscall::{bigint, sys_alloc_words, sys_bigint, sys_read, sys_read_words, sys_write},
 };
 
 risc0_zkvm::entry!(main);
@@ -130,6 +133,15 @@ pub fn main() {
             }
             env::commit(&Digest::try_from(hash).unwrap())
         }
+        MultiTestSpec::ShaStream { data, chunk_size } => {
+            use risc0_zkvm::sha::rust_crypto::{Digest as _, StreamingSha256};
+
+            let mut hasher = StreamingSha256::new();
+            for chunk in data.chunks(chunk_size as usize) {
+                hasher.update(chunk);
+            }
+            env::commit(&Digest::try_from(hasher.finalize().as_slice()).unwrap());
+        }
         MultiTestSpec::Syscall { count } => {
             let mut input: &[u8] = &[];
             let mut input_len: usize = 0;
@@ -216,6 +228,13 @@ pub fn main() {
             }
             env::log("Busy loop complete");
         }
+        MultiTestSpec::HeapChurn { rounds } => {
+            // Allocating no words returns the top of the heap.
+            let start = sys_alloc_words(0) as usize;
+            let sum = heap::churn(rounds);
+            let grown = sys_alloc_words(0) as usize - start;
+            env::commit(&(sum, grown as u32));
+        }
         MultiTestSpec::BigInt { x, y, modulus } => {
             let mut result = [0u32; bigint::WIDTH_WORDS];
             unsafe {
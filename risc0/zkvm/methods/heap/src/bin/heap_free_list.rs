@@ -0,0 +1,56 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs the heap workload with the `heap-free-list` allocator, which is the
+//! same as `MultiTestSpec::HeapChurn` with the default bump allocator.
+
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{
+    alloc::{alloc, dealloc},
+    boxed::Box,
+};
+use core::alloc::Layout;
+
+use risc0_zkvm::guest::env;
+use risc0_zkvm_methods::heap;
+use risc0_zkvm_platform::syscall::sys_alloc_words;
+
+risc0_zkvm::entry!(main);
+
+pub fn main() {
+    let rounds: u32 = env::read();
+
+    // Freed memory is handed out again for the same layout.
+    let layout = Layout::new::<[u32; 4]>();
+    unsafe {
+        let first = alloc(layout);
+        dealloc(first, layout);
+        assert_eq!(alloc(layout), first);
+        dealloc(first, layout);
+    }
+    let page = Box::into_raw(Box::new([0u8; 4096]));
+    drop(unsafe { Box::from_raw(page) });
+    assert_eq!(Box::into_raw(Box::new([1u8; 4096])), page);
+    drop(unsafe { Box::from_raw(page) });
+
+    // Allocating no words returns the top of the heap.
+    let start = sys_alloc_words(0) as usize;
+    let sum = heap::churn(rounds);
+    let grown = sys_alloc_words(0) as usize - start;
+    env::commit(&(sum, grown as u32));
+}
@@ -0,0 +1,42 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Heap workload shared by the "multi_test" and "heap_free_list" guests, so
+// that the bump and free list allocators can be compared.
+extern crate alloc;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// Size of the buffer allocated and freed in each round of [churn].
+pub const CHURN_BUF_SIZE: usize = 16 * 1024;
+
+/// Number of small boxes allocated and freed in each round of [churn].
+pub const CHURN_BOXES: u32 = 64;
+
+/// Allocates and frees a large buffer and many small boxes in each of
+/// `rounds` rounds, returning a checksum of what was allocated.
+pub fn churn(rounds: u32) -> u32 {
+    let mut sum = 0u32;
+    for round in 0..rounds {
+        let buf = vec![round as u8; CHURN_BUF_SIZE];
+        let boxes: Vec<Box<u32>> = (0..CHURN_BOXES).map(|i| Box::new(round ^ i)).collect();
+        sum = buf
+            .iter()
+            .fold(sum, |sum, &byte| sum.wrapping_add(byte as u32));
+        sum = boxes
+            .iter()
+            .fold(sum, |sum, value| sum.wrapping_add(**value));
+    }
+    sum
+}
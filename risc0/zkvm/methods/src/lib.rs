@@ -16,6 +16,7 @@
 #![no_std]
 
 pub mod bench;
+pub mod heap;
 pub mod multi_test;
 
 #[cfg(not(target_os = "zkvm"))]
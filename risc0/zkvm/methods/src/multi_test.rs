@@ -33,6 +33,10 @@ pub enum MultiTestSpec {
         data: Vec<u8>,
         num_iter: u32,
     },
+    ShaStream {
+        data: Vec<u8>,
+        chunk_size: u32,
+    },
     EventTrace,
     Profiler,
     Panic,
@@ -80,6 +84,11 @@ pub enum MultiTestSpec {
         /// Busy loop until the guest has run for at least this number of cycles
         cycles: u32,
     },
+    /// Run [crate::heap::churn], committing its checksum and how far the heap
+    /// grew.
+    HeapChurn {
+        rounds: u32,
+    },
     LibM,
     Oom,
     OutOfBounds,
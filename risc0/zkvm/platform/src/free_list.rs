@@ -0,0 +1,429 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A heap allocator that reuses freed memory.
+//!
+//! Allocations of up to [PAGE_SIZE] bytes are rounded up to a power of two and
+//! served from a free list per size class. New blocks of a size class are
+//! carved lazily out of a page taken from the bump allocator, so that
+//! allocating only touches the page that holds the block.
+//!
+//! Larger allocations are rounded up to whole pages and served first-fit from
+//! an address ordered list of free runs of pages. Adjacent runs are coalesced
+//! when freed, so that a growing `Vec` can reuse the memory it released.
+//!
+//! Memory is never returned from the size classes to the runs of pages.
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ptr,
+};
+
+#[cfg(test)]
+use self::tests::sys_alloc_aligned;
+#[cfg(not(test))]
+use crate::syscall::sys_alloc_aligned;
+use crate::{PAGE_SIZE, WORD_SIZE};
+
+/// The smallest block size, which must be able to hold a [FreeBlock].
+const MIN_BLOCK: usize = 2 * WORD_SIZE;
+
+/// The number of size classes, from [MIN_BLOCK] up to [PAGE_SIZE] bytes.
+const NUM_CLASSES: usize = (PAGE_SIZE / MIN_BLOCK).trailing_zeros() as usize + 1;
+
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+struct FreeRun {
+    size: usize,
+    next: *mut FreeRun,
+}
+
+struct Heap {
+    /// Free blocks of each size class.
+    blocks: [*mut FreeBlock; NUM_CLASSES],
+    /// The not yet used part of the page last carved for each size class.
+    fresh: [(usize, usize); NUM_CLASSES],
+    /// Free runs of pages, ordered by address.
+    runs: *mut FreeRun,
+}
+
+#[cfg_attr(test, allow(dead_code))]
+static mut HEAP: Heap = Heap::new();
+
+/// The size of the block that serves the given [Layout].
+fn block_size(layout: &Layout) -> usize {
+    let size = layout.size().max(layout.align()).max(MIN_BLOCK);
+    if size <= PAGE_SIZE {
+        size.next_power_of_two()
+    } else {
+        (size + PAGE_SIZE - 1) & !(PAGE_SIZE - 1)
+    }
+}
+
+/// The size class of a block of at most [PAGE_SIZE] bytes.
+fn size_class(block_size: usize) -> usize {
+    (block_size / MIN_BLOCK).trailing_zeros() as usize
+}
+
+impl Heap {
+    const fn new() -> Self {
+        Self {
+            blocks: [ptr::null_mut(); NUM_CLASSES],
+            fresh: [(0, 0); NUM_CLASSES],
+            runs: ptr::null_mut(),
+        }
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let size = block_size(&layout);
+        if size <= PAGE_SIZE {
+            self.alloc_block(size_class(size))
+        } else {
+            self.alloc_run(size, layout.align())
+        }
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let size = block_size(&layout);
+        if size <= PAGE_SIZE {
+            self.free_block(ptr, size_class(size))
+        } else {
+            self.free_run(ptr, size)
+        }
+    }
+
+    unsafe fn realloc(&mut self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        // Growing or shrinking within a block needs neither a copy nor a free.
+        if block_size(&layout) == block_size(&new_layout) {
+            return ptr;
+        }
+        let new_ptr = self.alloc(new_layout);
+        ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+        self.dealloc(ptr, layout);
+        new_ptr
+    }
+
+    unsafe fn alloc_block(&mut self, class: usize) -> *mut u8 {
+        let head = self.blocks[class];
+        if !head.is_null() {
+            self.blocks[class] = (*head).next;
+            return head as *mut u8;
+        }
+
+        let (cursor, end) = &mut self.fresh[class];
+        if cursor == end {
+            let page = sys_alloc_aligned(PAGE_SIZE, PAGE_SIZE) as usize;
+            (*cursor, *end) = (page, page + PAGE_SIZE);
+        }
+        let block = *cursor;
+        *cursor += MIN_BLOCK << class;
+        block as *mut u8
+    }
+
+    unsafe fn free_block(&mut self, ptr: *mut u8, class: usize) {
+        let block = ptr as *mut FreeBlock;
+        block.write(FreeBlock {
+            next: self.blocks[class],
+        });
+        self.blocks[class] = block;
+    }
+
+    unsafe fn alloc_run(&mut self, size: usize, align: usize) -> *mut u8 {
+        let mut link = ptr::addr_of_mut!(self.runs);
+        while !(*link).is_null() {
+            let run = *link;
+            if (*run).size >= size && run as usize & (align - 1) == 0 {
+                let rest = (*run).size - size;
+                if rest == 0 {
+                    *link = (*run).next;
+                } else {
+                    // The tail stays in place, which keeps the list ordered.
+                    let tail = (run as usize + size) as *mut FreeRun;
+                    tail.write(FreeRun {
+                        size: rest,
+                        next: (*run).next,
+                    });
+                    *link = tail;
+                }
+                return run as *mut u8;
+            }
+            link = ptr::addr_of_mut!((*run).next);
+        }
+        sys_alloc_aligned(size, align.max(PAGE_SIZE))
+    }
+
+    unsafe fn free_run(&mut self, ptr: *mut u8, mut size: usize) {
+        let addr = ptr as usize;
+        let mut prev: *mut FreeRun = ptr::null_mut();
+        let mut next = self.runs;
+        while !next.is_null() && (next as usize) < addr {
+            prev = next;
+            next = (*next).next;
+        }
+
+        if !next.is_null() && addr + size == next as usize {
+            size += (*next).size;
+            next = (*next).next;
+        }
+
+        if !prev.is_null() && prev as usize + (*prev).size == addr {
+            (*prev).size += size;
+            (*prev).next = next;
+            return;
+        }
+
+        let run = ptr as *mut FreeRun;
+        run.write(FreeRun { size, next });
+        if prev.is_null() {
+            self.runs = run;
+        } else {
+            (*prev).next = run;
+        }
+    }
+}
+
+/// A [GlobalAlloc] that reuses freed memory, see the [module](self)
+/// documentation.
+#[cfg_attr(test, allow(dead_code))]
+pub struct FreeListAlloc;
+
+unsafe impl GlobalAlloc for FreeListAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: Single threaded, so nothing else can touch the heap while we're
+        // working.
+        (*ptr::addr_of_mut!(HEAP)).alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: Single threaded, so nothing else can touch the heap while we're
+        // working.
+        (*ptr::addr_of_mut!(HEAP)).dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: Single threaded, so nothing else can touch the heap while we're
+        // working.
+        (*ptr::addr_of_mut!(HEAP)).realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::{alloc::Layout, cell::Cell, slice};
+    use std::vec;
+
+    use super::{block_size, size_class, Heap, MIN_BLOCK, NUM_CLASSES, PAGE_SIZE};
+
+    const ARENA_PAGES: usize = 32;
+
+    /// The alignment of the start of the arena, so that tests can tell where
+    /// the pages they allocate are placed.
+    const ARENA_ALIGN: usize = 8 * PAGE_SIZE;
+
+    std::thread_local! {
+        /// The next address to hand out from the arena of the current test,
+        /// and the end of that arena.
+        static ARENA: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+    }
+
+    /// Stands in for the bump allocator of the guest, which also panics when
+    /// it runs out of memory.
+    pub unsafe fn sys_alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
+        ARENA.with(|arena| {
+            let (pos, end) = arena.get();
+            let ptr = (pos + align - 1) & !(align - 1);
+            assert!(ptr + bytes <= end, "Out of memory!");
+            arena.set((ptr + bytes, end));
+            ptr as *mut u8
+        })
+    }
+
+    /// Return an empty [Heap], backed by a fresh arena of [ARENA_PAGES] pages.
+    fn new_heap() -> Heap {
+        let mem = vec![0u8; ARENA_PAGES * PAGE_SIZE + ARENA_ALIGN].leak();
+        let start = (mem.as_ptr() as usize + ARENA_ALIGN - 1) & !(ARENA_ALIGN - 1);
+        ARENA.with(|arena| arena.set((start, start + ARENA_PAGES * PAGE_SIZE)));
+        Heap::new()
+    }
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    unsafe fn fill(ptr: *mut u8, len: usize) {
+        for (i, byte) in slice::from_raw_parts_mut(ptr, len).iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+    }
+
+    unsafe fn check(ptr: *const u8, len: usize) {
+        for (i, byte) in slice::from_raw_parts(ptr, len).iter().enumerate() {
+            assert_eq!(*byte, i as u8, "byte {i}");
+        }
+    }
+
+    #[test]
+    fn size_classes() {
+        assert_eq!(block_size(&layout(1, 1)), MIN_BLOCK);
+        assert_eq!(block_size(&layout(MIN_BLOCK + 1, 1)), 2 * MIN_BLOCK);
+        assert_eq!(block_size(&layout(100, 4)), 128);
+        assert_eq!(block_size(&layout(8, 64)), 64);
+        assert_eq!(block_size(&layout(PAGE_SIZE, 1)), PAGE_SIZE);
+        assert_eq!(block_size(&layout(PAGE_SIZE + 1, 1)), 2 * PAGE_SIZE);
+        assert_eq!(block_size(&layout(3 * PAGE_SIZE - 1, 4)), 3 * PAGE_SIZE);
+
+        assert_eq!(size_class(MIN_BLOCK), 0);
+        assert_eq!(size_class(PAGE_SIZE), NUM_CLASSES - 1);
+    }
+
+    #[test]
+    fn large_alignment() {
+        let mut heap = new_heap();
+        unsafe {
+            for align in [64, PAGE_SIZE, 2 * PAGE_SIZE, 4 * PAGE_SIZE] {
+                let ptr = heap.alloc(layout(MIN_BLOCK, align));
+                assert_eq!(ptr as usize % align, 0, "align {align}");
+                fill(ptr, MIN_BLOCK);
+                heap.dealloc(ptr, layout(MIN_BLOCK, align));
+            }
+        }
+    }
+
+    #[test]
+    fn unaligned_run_is_skipped() {
+        let mut heap = new_heap();
+        let run = layout(2 * PAGE_SIZE, 4);
+        unsafe {
+            // The arena starts at an address aligned to 8 pages, so `a` is
+            // aligned to 2 pages but not to 4.
+            heap.alloc(run);
+            let a = heap.alloc(run);
+            assert_eq!(a as usize % (4 * PAGE_SIZE), 2 * PAGE_SIZE);
+            heap.dealloc(a, run);
+
+            let b = heap.alloc(layout(2 * PAGE_SIZE, 4 * PAGE_SIZE));
+            assert_eq!(b as usize % (4 * PAGE_SIZE), 0);
+            assert_ne!(b, a);
+            assert_eq!(heap.alloc(run), a);
+        }
+    }
+
+    #[test]
+    fn free_then_reuse() {
+        let mut heap = new_heap();
+        unsafe {
+            let a = heap.alloc(layout(24, 4));
+            let b = heap.alloc(layout(24, 4));
+            assert_eq!(b as usize - a as usize, 32);
+            heap.dealloc(a, layout(24, 4));
+            // A block of the same size class is reused, the most recently
+            // freed one first.
+            assert_eq!(heap.alloc(layout(32, 4)), a);
+            heap.dealloc(b, layout(24, 4));
+            heap.dealloc(a, layout(24, 4));
+            assert_eq!(heap.alloc(layout(17, 1)), a);
+            assert_eq!(heap.alloc(layout(17, 1)), b);
+            // Blocks of other size classes are not.
+            let c = heap.alloc(layout(16, 4));
+            assert_ne!(c, a);
+            assert_ne!(c, b);
+
+            let run = heap.alloc(layout(3 * PAGE_SIZE, 4));
+            heap.dealloc(run, layout(3 * PAGE_SIZE, 4));
+            assert_eq!(heap.alloc(layout(2 * PAGE_SIZE, 4)), run);
+        }
+    }
+
+    #[test]
+    fn coalesce_runs() {
+        let mut heap = new_heap();
+        let run = layout(2 * PAGE_SIZE, 4);
+        unsafe {
+            let a = heap.alloc(run);
+            let b = heap.alloc(run);
+            let c = heap.alloc(run);
+            let d = heap.alloc(run);
+            assert_eq!(b as usize, a as usize + 2 * PAGE_SIZE);
+            assert_eq!(c as usize, b as usize + 2 * PAGE_SIZE);
+
+            // Free out of order, so that `b` merges with the run after it and
+            // the run before it.
+            heap.dealloc(a, run);
+            heap.dealloc(c, run);
+            heap.dealloc(b, run);
+            assert_eq!((*heap.runs).size, 6 * PAGE_SIZE);
+            assert!((*heap.runs).next.is_null());
+
+            // The merged run serves an allocation none of its parts could.
+            assert_eq!(heap.alloc(layout(6 * PAGE_SIZE, 4)), a);
+            assert!(heap.runs.is_null());
+            heap.dealloc(d, run);
+        }
+    }
+
+    #[test]
+    fn realloc_preserves_contents() {
+        let mut heap = new_heap();
+        unsafe {
+            let ptr = heap.alloc(layout(10, 4));
+            fill(ptr, 10);
+
+            // Within the same block.
+            assert_eq!(heap.realloc(ptr, layout(10, 4), 16), ptr);
+
+            // Into a larger size class, and then into a run of pages.
+            let ptr = heap.realloc(ptr, layout(16, 4), 100);
+            check(ptr, 10);
+            fill(ptr, 100);
+            let ptr = heap.realloc(ptr, layout(100, 4), 5 * PAGE_SIZE);
+            check(ptr, 100);
+            fill(ptr, 5 * PAGE_SIZE);
+
+            // And back down, keeping the prefix that fits.
+            let ptr = heap.realloc(ptr, layout(5 * PAGE_SIZE, 4), 2 * PAGE_SIZE);
+            check(ptr, 2 * PAGE_SIZE);
+            let ptr = heap.realloc(ptr, layout(2 * PAGE_SIZE, 4), 40);
+            check(ptr, 40);
+            heap.dealloc(ptr, layout(40, 4));
+        }
+    }
+
+    #[test]
+    fn reuse_avoids_oom() {
+        let mut heap = new_heap();
+        let big = layout((ARENA_PAGES / 2 + 1) * PAGE_SIZE, 4);
+        unsafe {
+            for _ in 0..10 {
+                let ptr = heap.alloc(big);
+                heap.dealloc(ptr, big);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of memory!")]
+    fn oom() {
+        let mut heap = new_heap();
+        let big = layout((ARENA_PAGES / 2 + 1) * PAGE_SIZE, 4);
+        unsafe {
+            heap.alloc(big);
+            heap.alloc(big);
+        }
+    }
+}
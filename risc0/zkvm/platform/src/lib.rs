@@ -19,6 +19,15 @@
 pub mod memory;
 #[macro_use]
 pub mod syscall;
+#[cfg(any(
+    test,
+    all(
+        feature = "heap-free-list",
+        feature = "rust-runtime",
+        target_os = "zkvm"
+    )
+))]
+mod free_list;
 #[cfg(all(feature = "export-getrandom", target_os = "zkvm"))]
 mod getrandom;
 #[cfg(all(feature = "export-libm", target_os = "zkvm"))]
@@ -0,0 +1,69 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The BN254 curve, also known as alt_bn128, as used by Ethereum precompiles.
+//!
+//! Only the G1 group, defined over the base field, is supported.
+
+use super::{
+    curve,
+    field::{limbs_from_hex, FieldParams, Fp, Limbs},
+    CurveParams,
+};
+
+/// The base field of BN254.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseField;
+
+impl FieldParams for BaseField {
+    const MODULUS: Limbs =
+        limbs_from_hex("30644E72E131A029B85045B68181585D97816A916871CA8D3C208C16D87CFD47");
+}
+
+/// The scalar field of BN254, whose modulus is the order of the G1 group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScalarField;
+
+impl FieldParams for ScalarField {
+    const MODULUS: Limbs =
+        limbs_from_hex("30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000001");
+}
+
+/// The G1 group of the BN254 curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bn254;
+
+impl CurveParams for Bn254 {
+    type Base = BaseField;
+    type Scalar = ScalarField;
+
+    const A: FieldElement = FieldElement::from_limbs_unchecked(limbs_from_hex("0"));
+    const B: FieldElement = FieldElement::from_limbs_unchecked(limbs_from_hex("3"));
+    const GENERATOR: (FieldElement, FieldElement) = (
+        FieldElement::from_limbs_unchecked(limbs_from_hex("1")),
+        FieldElement::from_limbs_unchecked(limbs_from_hex("2")),
+    );
+}
+
+/// An element of the base field of BN254.
+pub type FieldElement = Fp<BaseField>;
+
+/// An element of the scalar field of BN254.
+pub type Scalar = Fp<ScalarField>;
+
+/// A point in the BN254 G1 group, in affine coordinates.
+pub type AffinePoint = curve::AffinePoint<Bn254>;
+
+/// A point in the BN254 G1 group, in Jacobian coordinates.
+pub type ProjectivePoint = curve::ProjectivePoint<Bn254>;
@@ -0,0 +1,343 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Points on short Weierstrass curves.
+
+use core::{
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use risc0_zkvm_platform::syscall::bigint::WIDTH_BYTES;
+
+use super::field::{bits_msb_first, FieldParams, Fp};
+
+/// Parameters of a short Weierstrass curve, `y^2 = x^3 + a*x + b`, with a
+/// prime order group.
+pub trait CurveParams: Copy + Clone + fmt::Debug + Eq + 'static {
+    /// The field of point coordinates.
+    type Base: FieldParams;
+
+    /// The field of scalars, whose modulus is the order of the group.
+    type Scalar: FieldParams;
+
+    /// The `a` coefficient of the curve equation.
+    const A: Fp<Self::Base>;
+
+    /// The `b` coefficient of the curve equation.
+    const B: Fp<Self::Base>;
+
+    /// The coordinates of the generator of the group.
+    const GENERATOR: (Fp<Self::Base>, Fp<Self::Base>);
+}
+
+/// A point on the curve `C`, in affine coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AffinePoint<C: CurveParams> {
+    x: Fp<C::Base>,
+    y: Fp<C::Base>,
+    infinity: bool,
+}
+
+impl<C: CurveParams> AffinePoint<C> {
+    /// The point at infinity, which is the identity of the group.
+    pub const IDENTITY: Self = Self {
+        x: Fp::ZERO,
+        y: Fp::ZERO,
+        infinity: true,
+    };
+
+    /// The generator of the group.
+    pub const GENERATOR: Self = Self {
+        x: C::GENERATOR.0,
+        y: C::GENERATOR.1,
+        infinity: false,
+    };
+
+    /// Creates a point from its coordinates, returning `None` if it is not on
+    /// the curve.
+    pub fn new(x: Fp<C::Base>, y: Fp<C::Base>) -> Option<Self> {
+        let point = Self {
+            x,
+            y,
+            infinity: false,
+        };
+        point.is_on_curve().then_some(point)
+    }
+
+    /// Decodes a point from its SEC1 encoding, either compressed (33 bytes) or
+    /// uncompressed (65 bytes).
+    pub fn from_sec1_bytes(bytes: &[u8]) -> Option<Self> {
+        let coordinate = |bytes: &[u8]| Fp::from_be_bytes(bytes.try_into().unwrap());
+        match (bytes.first()?, bytes.len()) {
+            (0x04, len) if len == 1 + 2 * WIDTH_BYTES => Self::new(
+                coordinate(&bytes[1..1 + WIDTH_BYTES])?,
+                coordinate(&bytes[1 + WIDTH_BYTES..])?,
+            ),
+            (tag @ (0x02 | 0x03), len) if len == 1 + WIDTH_BYTES => {
+                Self::decompress(coordinate(&bytes[1..])?, *tag == 0x03)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the uncompressed SEC1 encoding of this point, or `None` for the
+    /// identity.
+    pub fn to_uncompressed_sec1_bytes(&self) -> Option<[u8; 1 + 2 * WIDTH_BYTES]> {
+        if self.infinity {
+            return None;
+        }
+        let mut bytes = [0x04; 1 + 2 * WIDTH_BYTES];
+        bytes[1..1 + WIDTH_BYTES].copy_from_slice(&self.x.to_be_bytes());
+        bytes[1 + WIDTH_BYTES..].copy_from_slice(&self.y.to_be_bytes());
+        Some(bytes)
+    }
+
+    /// Returns the point with the given x coordinate and a y coordinate with
+    /// the given parity, or `None` if there is no such point.
+    pub fn decompress(x: Fp<C::Base>, y_is_odd: bool) -> Option<Self> {
+        let y = (x.square() * x + C::A * x + C::B).sqrt()?;
+        let y = if y.is_odd() == y_is_odd { y } else { -y };
+        Self::new(x, y)
+    }
+
+    /// Returns the x coordinate, or `None` for the identity.
+    pub fn x(&self) -> Option<Fp<C::Base>> {
+        (!self.infinity).then_some(self.x)
+    }
+
+    /// Returns the y coordinate, or `None` for the identity.
+    pub fn y(&self) -> Option<Fp<C::Base>> {
+        (!self.infinity).then_some(self.y)
+    }
+
+    /// Returns true if this is the identity.
+    pub fn is_identity(&self) -> bool {
+        self.infinity
+    }
+
+    /// Returns true if this point satisfies the curve equation.
+    pub fn is_on_curve(&self) -> bool {
+        self.infinity || self.y.square() == self.x.square() * self.x + C::A * self.x + C::B
+    }
+
+    /// Converts this point to Jacobian coordinates.
+    pub fn to_projective(&self) -> ProjectivePoint<C> {
+        if self.infinity {
+            ProjectivePoint::IDENTITY
+        } else {
+            ProjectivePoint {
+                x: self.x,
+                y: self.y,
+                z: Fp::ONE,
+            }
+        }
+    }
+}
+
+/// A point on the curve `C`, in Jacobian coordinates.
+///
+/// The point `(X, Y, Z)` represents the affine point `(X / Z^2, Y / Z^3)`, and
+/// the identity when `Z` is zero. Arithmetic is not constant time.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectivePoint<C: CurveParams> {
+    x: Fp<C::Base>,
+    y: Fp<C::Base>,
+    z: Fp<C::Base>,
+}
+
+impl<C: CurveParams> ProjectivePoint<C> {
+    /// The point at infinity, which is the identity of the group.
+    pub const IDENTITY: Self = Self {
+        x: Fp::ONE,
+        y: Fp::ONE,
+        z: Fp::ZERO,
+    };
+
+    /// The generator of the group.
+    pub const GENERATOR: Self = Self {
+        x: C::GENERATOR.0,
+        y: C::GENERATOR.1,
+        z: Fp::ONE,
+    };
+
+    /// Returns true if this is the identity.
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    /// Converts this point to affine coordinates.
+    pub fn to_affine(&self) -> AffinePoint<C> {
+        let Some(z_inv) = self.z.invert() else {
+            return AffinePoint::IDENTITY;
+        };
+        let z_inv2 = z_inv.square();
+        AffinePoint {
+            x: self.x * z_inv2,
+            y: self.y * z_inv2 * z_inv,
+            infinity: false,
+        }
+    }
+
+    /// Returns this point added to itself.
+    pub fn double(&self) -> Self {
+        if self.is_identity() || self.y.is_zero() {
+            return Self::IDENTITY;
+        }
+
+        // https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html#doubling-dbl-2007-bl
+        let xx = self.x.square();
+        let yy = self.y.square();
+        let yyyy = yy.square();
+        let zz = self.z.square();
+        let s = ((self.x + yy).square() - xx - yyyy).double();
+        let m = xx.double() + xx + C::A * zz.square();
+        let x3 = m.square() - s.double();
+        let y3 = m * (s - x3) - yyyy.double().double().double();
+        let z3 = (self.y + self.z).square() - yy - zz;
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Returns `a * self + b * other`, sharing the doublings between the two
+    /// scalar multiplications.
+    pub fn lincomb(&self, a: &Fp<C::Scalar>, other: &Self, b: &Fp<C::Scalar>) -> Self {
+        let both = *self + *other;
+        let mut result = Self::IDENTITY;
+        for (a_bit, b_bit) in bits_msb_first(a.as_limbs()).zip(bits_msb_first(b.as_limbs())) {
+            result = result.double();
+            match (a_bit, b_bit) {
+                (true, true) => result = result + both,
+                (true, false) => result = result + *self,
+                (false, true) => result = result + *other,
+                (false, false) => (),
+            }
+        }
+        result
+    }
+}
+
+impl<C: CurveParams> Add for ProjectivePoint<C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        if self.is_identity() {
+            return rhs;
+        }
+        if rhs.is_identity() {
+            return self;
+        }
+
+        // https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html#addition-add-2007-bl
+        let z1z1 = self.z.square();
+        let z2z2 = rhs.z.square();
+        let u1 = self.x * z2z2;
+        let u2 = rhs.x * z1z1;
+        let s1 = self.y * rhs.z * z2z2;
+        let s2 = rhs.y * self.z * z1z1;
+        let h = u2 - u1;
+        let r = (s2 - s1).double();
+        if h.is_zero() {
+            // The points have the same x coordinate, so they are either equal
+            // or inverses of each other.
+            return if r.is_zero() {
+                self.double()
+            } else {
+                Self::IDENTITY
+            };
+        }
+        let i = h.double().square();
+        let j = h * i;
+        let v = u1 * i;
+        let x3 = r.square() - j - v.double();
+        let y3 = r * (v - x3) - (s1 * j).double();
+        let z3 = ((self.z + rhs.z).square() - z1z1 - z2z2) * h;
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+impl<C: CurveParams> Neg for ProjectivePoint<C> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: self.x,
+            y: -self.y,
+            z: self.z,
+        }
+    }
+}
+
+impl<C: CurveParams> Sub for ProjectivePoint<C> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<C: CurveParams> Mul<Fp<C::Scalar>> for ProjectivePoint<C> {
+    type Output = Self;
+
+    // Double-and-add.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, scalar: Fp<C::Scalar>) -> Self {
+        let mut result = Self::IDENTITY;
+        for bit in bits_msb_first(scalar.as_limbs()) {
+            result = result.double();
+            if bit {
+                result = result + self;
+            }
+        }
+        result
+    }
+}
+
+impl<C: CurveParams> PartialEq for ProjectivePoint<C> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.is_identity(), other.is_identity()) {
+            (true, true) => true,
+            (false, false) => {
+                // Compare X1 / Z1^2 = X2 / Z2^2 and Y1 / Z1^3 = Y2 / Z2^3 without
+                // inverting.
+                let z1z1 = self.z.square();
+                let z2z2 = other.z.square();
+                self.x * z2z2 == other.x * z1z1
+                    && self.y * z2z2 * other.z == other.y * z1z1 * self.z
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<C: CurveParams> Eq for ProjectivePoint<C> {}
+
+impl<C: CurveParams> From<AffinePoint<C>> for ProjectivePoint<C> {
+    fn from(point: AffinePoint<C>) -> Self {
+        point.to_projective()
+    }
+}
+
+impl<C: CurveParams> From<ProjectivePoint<C>> for AffinePoint<C> {
+    fn from(point: ProjectivePoint<C>) -> Self {
+        point.to_affine()
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ECDSA signature verification.
+
+use risc0_zkvm_platform::syscall::bigint::{WIDTH_BITS, WIDTH_BYTES};
+
+use super::{
+    curve::{AffinePoint, CurveParams, ProjectivePoint},
+    field::{bit_len, limbs_from_be_bytes, shr, FieldParams, Fp},
+};
+
+/// An ECDSA signature over the curve `C`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature<C: CurveParams> {
+    r: Fp<C::Scalar>,
+    s: Fp<C::Scalar>,
+}
+
+impl<C: CurveParams> Signature<C> {
+    /// Creates a signature from its components, returning `None` if either is
+    /// zero.
+    pub fn from_scalars(r: Fp<C::Scalar>, s: Fp<C::Scalar>) -> Option<Self> {
+        (!r.is_zero() && !s.is_zero()).then_some(Self { r, s })
+    }
+
+    /// Decodes a signature from the big-endian encodings of `r` and `s`,
+    /// concatenated.
+    pub fn from_bytes(bytes: &[u8; 2 * WIDTH_BYTES]) -> Option<Self> {
+        let (r, s) = bytes.split_at(WIDTH_BYTES);
+        Self::from_scalars(
+            Fp::from_be_bytes(r.try_into().unwrap())?,
+            Fp::from_be_bytes(s.try_into().unwrap())?,
+        )
+    }
+
+    /// Returns the `r` component.
+    pub fn r(&self) -> Fp<C::Scalar> {
+        self.r
+    }
+
+    /// Returns the `s` component.
+    pub fn s(&self) -> Fp<C::Scalar> {
+        self.s
+    }
+}
+
+/// Verifies an ECDSA signature of the given message digest.
+///
+/// The digest is converted to a scalar as described in FIPS 186-5, keeping its
+/// leftmost bits if it is longer than the group order. Signatures with a high
+/// `s` are accepted.
+pub fn verify_prehash<C: CurveParams>(
+    public_key: &AffinePoint<C>,
+    prehash: &[u8; WIDTH_BYTES],
+    signature: &Signature<C>,
+) -> bool {
+    if public_key.is_identity() || !public_key.is_on_curve() {
+        return false;
+    }
+
+    let order_bits = bit_len(&C::Scalar::MODULUS);
+    let z = Fp::<C::Scalar>::from_limbs_reduced(shr(
+        &limbs_from_be_bytes(prehash),
+        (WIDTH_BITS - order_bits) as u32,
+    ));
+
+    let Some(s_inv) = signature.s.invert() else {
+        return false;
+    };
+    let u1 = z * s_inv;
+    let u2 = signature.r * s_inv;
+    let point = ProjectivePoint::GENERATOR
+        .lincomb(&u1, &public_key.to_projective(), &u2)
+        .to_affine();
+    match point.x() {
+        Some(x) => Fp::<C::Scalar>::from_limbs_reduced(*x.as_limbs()) == signature.r,
+        None => false,
+    }
+}
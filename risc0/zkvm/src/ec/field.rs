@@ -0,0 +1,386 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prime fields with moduli of up to 256 bits.
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use risc0_zkvm_platform::syscall::bigint::{WIDTH_BYTES, WIDTH_WORDS};
+
+/// A 256-bit unsigned integer, as little-endian 32-bit limbs.
+///
+/// This is the layout used by the BigInt accelerator.
+pub type Limbs = [u32; WIDTH_WORDS];
+
+/// Parameters of a prime field.
+pub trait FieldParams: Copy + Clone + fmt::Debug + Eq + 'static {
+    /// The prime modulus of the field.
+    const MODULUS: Limbs;
+}
+
+/// An element of the prime field described by `P`.
+///
+/// Elements are always kept reduced, i.e. strictly less than the modulus.
+/// Multiplication uses the BigInt accelerator when running in the zkVM guest;
+/// addition and subtraction are done in software.
+///
+/// Operations are not constant time.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Fp<P: FieldParams> {
+    limbs: Limbs,
+    phantom: PhantomData<P>,
+}
+
+impl<P: FieldParams> Fp<P> {
+    /// The additive identity.
+    pub const ZERO: Self = Self::from_limbs_unchecked([0; WIDTH_WORDS]);
+
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::from_limbs_unchecked([1, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Creates an element from limbs without checking that they are less than
+    /// the modulus.
+    ///
+    /// This is intended for constants. Arithmetic on unreduced elements gives
+    /// unspecified results.
+    pub const fn from_limbs_unchecked(limbs: Limbs) -> Self {
+        Self {
+            limbs,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates an element from limbs, returning `None` if they are not less
+    /// than the modulus.
+    pub fn from_limbs(limbs: Limbs) -> Option<Self> {
+        lt(&limbs, &P::MODULUS).then_some(Self::from_limbs_unchecked(limbs))
+    }
+
+    /// Creates an element by reducing the given integer modulo the modulus.
+    pub fn from_limbs_reduced(limbs: Limbs) -> Self {
+        Self::from_limbs_unchecked(modmul(&limbs, &Self::ONE.limbs, &P::MODULUS))
+    }
+
+    /// Creates an element from a big-endian integer, returning `None` if it is
+    /// not less than the modulus.
+    pub fn from_be_bytes(bytes: &[u8; WIDTH_BYTES]) -> Option<Self> {
+        Self::from_limbs(limbs_from_be_bytes(bytes))
+    }
+
+    /// Creates an element by reducing a big-endian integer modulo the
+    /// modulus.
+    pub fn from_be_bytes_reduced(bytes: &[u8; WIDTH_BYTES]) -> Self {
+        Self::from_limbs_reduced(limbs_from_be_bytes(bytes))
+    }
+
+    /// Returns the big-endian encoding of this element.
+    pub fn to_be_bytes(&self) -> [u8; WIDTH_BYTES] {
+        let mut bytes = [0u8; WIDTH_BYTES];
+        for (chunk, limb) in bytes.chunks_exact_mut(4).zip(self.limbs.iter().rev()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Creates an element from a small integer.
+    pub fn from_u32(value: u32) -> Self {
+        Self::from_limbs_reduced([value, 0, 0, 0, 0, 0, 0, 0])
+    }
+
+    /// Returns the limbs of this element.
+    pub fn as_limbs(&self) -> &Limbs {
+        &self.limbs
+    }
+
+    /// Returns true if this element is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs == [0; WIDTH_WORDS]
+    }
+
+    /// Returns true if the integer representing this element is odd.
+    pub fn is_odd(&self) -> bool {
+        self.limbs[0] & 1 == 1
+    }
+
+    /// Returns this element plus itself.
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// Returns this element multiplied by itself.
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Raises this element to the given power, as little-endian limbs.
+    pub fn pow(&self, exp: &Limbs) -> Self {
+        let mut result = Self::ONE;
+        for bit in bits_msb_first(exp) {
+            result = result.square();
+            if bit {
+                result *= *self;
+            }
+        }
+        result
+    }
+
+    /// Returns the multiplicative inverse of this element, or `None` if it is
+    /// zero.
+    pub fn invert(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        // By Fermat's little theorem, a^(p - 2) = a^-1 (mod p).
+        let (exp, _) = sub_limbs(&P::MODULUS, &[2, 0, 0, 0, 0, 0, 0, 0]);
+        Some(self.pow(&exp))
+    }
+
+    /// Returns a square root of this element, or `None` if it is not a
+    /// quadratic residue.
+    ///
+    /// Only supported for moduli that are congruent to 3 modulo 4, which
+    /// includes the base fields of all the curves in this module.
+    pub fn sqrt(&self) -> Option<Self> {
+        assert_eq!(
+            P::MODULUS[0] & 3,
+            3,
+            "sqrt is only supported for moduli congruent to 3 mod 4"
+        );
+        // a^((p + 1) / 4) is a square root of a, if there is one. p + 1 cannot
+        // overflow since p is odd and less than 2^256.
+        let (exp, _) = add_limbs(&P::MODULUS, &Self::ONE.limbs);
+        let root = self.pow(&shr(&exp, 2));
+        (root.square() == *self).then_some(root)
+    }
+}
+
+impl<P: FieldParams> Default for Fp<P> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<P: FieldParams> fmt::Debug for Fp<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.to_be_bytes()))
+    }
+}
+
+impl<P: FieldParams> Add for Fp<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (sum, carry) = add_limbs(&self.limbs, &rhs.limbs);
+        if carry || !lt(&sum, &P::MODULUS) {
+            Self::from_limbs_unchecked(sub_limbs(&sum, &P::MODULUS).0)
+        } else {
+            Self::from_limbs_unchecked(sum)
+        }
+    }
+}
+
+impl<P: FieldParams> Sub for Fp<P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, borrow) = sub_limbs(&self.limbs, &rhs.limbs);
+        if borrow {
+            Self::from_limbs_unchecked(add_limbs(&diff, &P::MODULUS).0)
+        } else {
+            Self::from_limbs_unchecked(diff)
+        }
+    }
+}
+
+impl<P: FieldParams> Mul for Fp<P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_limbs_unchecked(modmul(&self.limbs, &rhs.limbs, &P::MODULUS))
+    }
+}
+
+impl<P: FieldParams> Neg for Fp<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl<P: FieldParams> AddAssign for Fp<P> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<P: FieldParams> SubAssign for Fp<P> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<P: FieldParams> MulAssign for Fp<P> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+/// Parses a big-endian hex string of up to 64 digits into limbs, for use in
+/// constants.
+pub(crate) const fn limbs_from_hex(hex: &str) -> Limbs {
+    let bytes = hex.as_bytes();
+    assert!(bytes.len() <= 2 * WIDTH_BYTES, "hex constant is too long");
+    let mut limbs = [0u32; WIDTH_WORDS];
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = match bytes[bytes.len() - 1 - i] {
+            b @ b'0'..=b'9' => b - b'0',
+            b @ b'a'..=b'f' => b - b'a' + 10,
+            b @ b'A'..=b'F' => b - b'A' + 10,
+            _ => panic!("invalid hex digit"),
+        };
+        limbs[i / 8] |= (digit as u32) << (4 * (i % 8));
+        i += 1;
+    }
+    limbs
+}
+
+pub(crate) fn limbs_from_be_bytes(bytes: &[u8; WIDTH_BYTES]) -> Limbs {
+    let mut limbs = [0u32; WIDTH_WORDS];
+    for (limb, chunk) in limbs.iter_mut().rev().zip(bytes.chunks_exact(4)) {
+        *limb = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+/// Iterates over the bits of the given integer, most significant first.
+pub(crate) fn bits_msb_first(limbs: &Limbs) -> impl Iterator<Item = bool> + '_ {
+    limbs
+        .iter()
+        .rev()
+        .flat_map(|limb| (0..32).rev().map(move |i| (limb >> i) & 1 == 1))
+}
+
+/// Returns the number of significant bits of the given integer.
+pub(crate) fn bit_len(limbs: &Limbs) -> usize {
+    for (i, limb) in limbs.iter().enumerate().rev() {
+        if *limb != 0 {
+            return 32 * i + (32 - limb.leading_zeros() as usize);
+        }
+    }
+    0
+}
+
+/// Shifts the given integer right by `shift` bits, where `shift < 32`.
+pub(crate) fn shr(limbs: &Limbs, shift: u32) -> Limbs {
+    if shift == 0 {
+        return *limbs;
+    }
+    let mut result = [0u32; WIDTH_WORDS];
+    for i in 0..WIDTH_WORDS {
+        let hi = limbs.get(i + 1).map_or(0, |next| next << (32 - shift));
+        result[i] = (limbs[i] >> shift) | hi;
+    }
+    result
+}
+
+fn lt(a: &Limbs, b: &Limbs) -> bool {
+    for (a, b) in a.iter().rev().zip(b.iter().rev()) {
+        if a != b {
+            return a < b;
+        }
+    }
+    false
+}
+
+fn add_limbs(a: &Limbs, b: &Limbs) -> (Limbs, bool) {
+    let mut result = [0u32; WIDTH_WORDS];
+    let mut carry = false;
+    for i in 0..WIDTH_WORDS {
+        let (sum, c1) = a[i].overflowing_add(b[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u32);
+        result[i] = sum;
+        carry = c1 || c2;
+    }
+    (result, carry)
+}
+
+fn sub_limbs(a: &Limbs, b: &Limbs) -> (Limbs, bool) {
+    let mut result = [0u32; WIDTH_WORDS];
+    let mut borrow = false;
+    for i in 0..WIDTH_WORDS {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u32);
+        result[i] = diff;
+        borrow = b1 || b2;
+    }
+    (result, borrow)
+}
+
+/// Computes `x * y mod modulus`, using the BigInt accelerator.
+#[cfg(target_os = "zkvm")]
+fn modmul(x: &Limbs, y: &Limbs, modulus: &Limbs) -> Limbs {
+    use risc0_zkvm_platform::syscall::{bigint, sys_bigint};
+
+    let mut result = [0u32; WIDTH_WORDS];
+    unsafe {
+        sys_bigint(&mut result, bigint::OP_MULTIPLY, x, y, modulus);
+    }
+    result
+}
+
+/// Computes `x * y mod modulus` in software, matching the BigInt accelerator.
+#[cfg(not(target_os = "zkvm"))]
+fn modmul(x: &Limbs, y: &Limbs, modulus: &Limbs) -> Limbs {
+    // Schoolbook multiplication into a 512-bit product.
+    let mut product = [0u32; 2 * WIDTH_WORDS];
+    for i in 0..WIDTH_WORDS {
+        let mut carry = 0u64;
+        for j in 0..WIDTH_WORDS {
+            let acc = product[i + j] as u64 + x[i] as u64 * y[j] as u64 + carry;
+            product[i + j] = acc as u32;
+            carry = acc >> 32;
+        }
+        product[i + WIDTH_WORDS] = carry as u32;
+    }
+
+    // Binary long division, keeping only the remainder.
+    let mut rem = [0u32; WIDTH_WORDS];
+    for limb in product.iter().rev() {
+        for i in (0..32).rev() {
+            let overflow = rem[WIDTH_WORDS - 1] >> 31 == 1;
+            rem = shl1(&rem, (limb >> i) & 1);
+            if overflow || !lt(&rem, modulus) {
+                rem = sub_limbs(&rem, modulus).0;
+            }
+        }
+    }
+    rem
+}
+
+#[cfg(not(target_os = "zkvm"))]
+fn shl1(limbs: &Limbs, low_bit: u32) -> Limbs {
+    let mut result = [0u32; WIDTH_WORDS];
+    let mut carry = low_bit;
+    for i in 0..WIDTH_WORDS {
+        result[i] = (limbs[i] << 1) | carry;
+        carry = limbs[i] >> 31;
+    }
+    result
+}
@@ -0,0 +1,70 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Elliptic curve arithmetic accelerated by the zkVM's BigInt circuit.
+//!
+//! This module provides 256-bit prime fields, short Weierstrass curve points,
+//! and ECDSA and Schnorr signature verification. Field multiplication uses the
+//! `sys_bigint` accelerator when running in the guest, which is much cheaper
+//! than multi-precision arithmetic implemented with RISC-V instructions. On the
+//! host, the same API is implemented in software so that guest code can be
+//! tested natively.
+//!
+//! The following curves are supported:
+//! * [secp256k1], including [BIP-340](schnorr) Schnorr signatures.
+//! * [P-256](p256).
+//! * The G1 group of [BN254](bn254).
+//!
+//! Other curves can be added by implementing [FieldParams] and [CurveParams].
+//!
+//! None of the operations are constant time. This is fine for verifying
+//! signatures in the guest, but they should not be used with secret values
+//! outside of the zkVM.
+//!
+//! # Example
+//!
+//! ```rust
+//! use risc0_zkvm::ec::{
+//!     ecdsa,
+//!     secp256k1::{AffinePoint, ProjectivePoint, Scalar, Signature},
+//! };
+//!
+//! let secret_key = Scalar::from_u32(3);
+//! let public_key: AffinePoint = (ProjectivePoint::GENERATOR * secret_key).to_affine();
+//! let prehash = [7u8; 32];
+//!
+//! // Sign with a fixed nonce, for illustration only.
+//! let nonce = Scalar::from_u32(2);
+//! let nonce_point = (ProjectivePoint::GENERATOR * nonce).to_affine();
+//! let r = Scalar::from_be_bytes_reduced(&nonce_point.x().unwrap().to_be_bytes());
+//! let s = nonce.invert().unwrap() * (Scalar::from_be_bytes_reduced(&prehash) + r * secret_key);
+//! let signature = Signature::from_scalars(r, s).unwrap();
+//!
+//! assert!(ecdsa::verify_prehash(&public_key, &prehash, &signature));
+//! ```
+
+pub mod bn254;
+mod curve;
+pub mod ecdsa;
+mod field;
+pub mod p256;
+pub mod schnorr;
+pub mod secp256k1;
+#[cfg(test)]
+mod tests;
+
+pub use self::{
+    curve::{AffinePoint, CurveParams, ProjectivePoint},
+    field::{FieldParams, Fp, Limbs},
+};
@@ -0,0 +1,78 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The NIST P-256 curve, also known as secp256r1 or prime256v1.
+
+use super::{
+    curve,
+    field::{limbs_from_hex, FieldParams, Fp, Limbs},
+    CurveParams,
+};
+
+/// The base field of P-256.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseField;
+
+impl FieldParams for BaseField {
+    const MODULUS: Limbs =
+        limbs_from_hex("FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF");
+}
+
+/// The scalar field of P-256, whose modulus is the order of the group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScalarField;
+
+impl FieldParams for ScalarField {
+    const MODULUS: Limbs =
+        limbs_from_hex("FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551");
+}
+
+/// The P-256 curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256;
+
+impl CurveParams for P256 {
+    type Base = BaseField;
+    type Scalar = ScalarField;
+
+    const A: FieldElement = FieldElement::from_limbs_unchecked(limbs_from_hex(
+        "FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFC",
+    ));
+    const B: FieldElement = FieldElement::from_limbs_unchecked(limbs_from_hex(
+        "5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B",
+    ));
+    const GENERATOR: (FieldElement, FieldElement) = (
+        FieldElement::from_limbs_unchecked(limbs_from_hex(
+            "6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296",
+        )),
+        FieldElement::from_limbs_unchecked(limbs_from_hex(
+            "4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5",
+        )),
+    );
+}
+
+/// An element of the base field of P-256.
+pub type FieldElement = Fp<BaseField>;
+
+/// An element of the scalar field of P-256.
+pub type Scalar = Fp<ScalarField>;
+
+/// A point on P-256, in affine coordinates.
+pub type AffinePoint = curve::AffinePoint<P256>;
+
+/// A point on P-256, in Jacobian coordinates.
+pub type ProjectivePoint = curve::ProjectivePoint<P256>;
+
+/// An ECDSA signature over P-256.
+pub type Signature = super::ecdsa::Signature<P256>;
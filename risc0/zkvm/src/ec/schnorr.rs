@@ -0,0 +1,66 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [BIP-340] Schnorr signature verification over secp256k1.
+//!
+//! [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+
+use risc0_zkvm_platform::syscall::bigint::WIDTH_BYTES;
+
+use super::secp256k1::{AffinePoint, FieldElement, ProjectivePoint, Scalar};
+use crate::sha::rust_crypto::{Digest as _, Sha256};
+
+/// Verifies a BIP-340 signature of `message` by the x-only `public_key`.
+pub fn verify(
+    public_key: &[u8; WIDTH_BYTES],
+    message: &[u8],
+    signature: &[u8; 2 * WIDTH_BYTES],
+) -> bool {
+    let (r_bytes, s_bytes) = signature.split_at(WIDTH_BYTES);
+    let Some(public_key_point) =
+        FieldElement::from_be_bytes(public_key).and_then(|x| AffinePoint::decompress(x, false))
+    else {
+        return false;
+    };
+    let Some(r) = FieldElement::from_be_bytes(r_bytes.try_into().unwrap()) else {
+        return false;
+    };
+    let Some(s) = Scalar::from_be_bytes(s_bytes.try_into().unwrap()) else {
+        return false;
+    };
+
+    let e = Scalar::from_be_bytes_reduced(&tagged_hash(
+        b"BIP0340/challenge",
+        &[r_bytes, public_key, message],
+    ));
+    let point = ProjectivePoint::GENERATOR
+        .lincomb(&s, &public_key_point.to_projective(), &-e)
+        .to_affine();
+    match (point.x(), point.y()) {
+        (Some(x), Some(y)) => x == r && !y.is_odd(),
+        _ => false,
+    }
+}
+
+/// Computes the BIP-340 tagged hash of the concatenation of `parts`.
+fn tagged_hash(tag: &[u8], parts: &[&[u8]]) -> [u8; WIDTH_BYTES] {
+    let tag_hash = Sha256::digest(tag);
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash);
+    hasher.update(tag_hash);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
@@ -0,0 +1,74 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The secp256k1 curve, as used by Bitcoin and Ethereum.
+
+use super::{
+    curve,
+    field::{limbs_from_hex, FieldParams, Fp, Limbs},
+    CurveParams,
+};
+
+/// The base field of secp256k1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseField;
+
+impl FieldParams for BaseField {
+    const MODULUS: Limbs =
+        limbs_from_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F");
+}
+
+/// The scalar field of secp256k1, whose modulus is the order of the group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScalarField;
+
+impl FieldParams for ScalarField {
+    const MODULUS: Limbs =
+        limbs_from_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141");
+}
+
+/// The secp256k1 curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl CurveParams for Secp256k1 {
+    type Base = BaseField;
+    type Scalar = ScalarField;
+
+    const A: FieldElement = FieldElement::from_limbs_unchecked(limbs_from_hex("0"));
+    const B: FieldElement = FieldElement::from_limbs_unchecked(limbs_from_hex("7"));
+    const GENERATOR: (FieldElement, FieldElement) = (
+        FieldElement::from_limbs_unchecked(limbs_from_hex(
+            "79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+        )),
+        FieldElement::from_limbs_unchecked(limbs_from_hex(
+            "483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8",
+        )),
+    );
+}
+
+/// An element of the base field of secp256k1.
+pub type FieldElement = Fp<BaseField>;
+
+/// An element of the scalar field of secp256k1.
+pub type Scalar = Fp<ScalarField>;
+
+/// A point on secp256k1, in affine coordinates.
+pub type AffinePoint = curve::AffinePoint<Secp256k1>;
+
+/// A point on secp256k1, in Jacobian coordinates.
+pub type ProjectivePoint = curve::ProjectivePoint<Secp256k1>;
+
+/// An ECDSA signature over secp256k1.
+pub type Signature = super::ecdsa::Signature<Secp256k1>;
@@ -0,0 +1,214 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+use rand::{thread_rng, RngCore};
+
+use super::{bn254, ecdsa, p256, schnorr, secp256k1};
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn ark_to_bytes<F: PrimeField>(value: F) -> [u8; 32] {
+    value.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+#[test]
+fn field_arithmetic() {
+    for _ in 0..10 {
+        let a = ark_bn254::Fq::from_be_bytes_mod_order(&random_bytes());
+        let b = ark_bn254::Fq::from_be_bytes_mod_order(&random_bytes());
+        let x = bn254::FieldElement::from_be_bytes(&ark_to_bytes(a)).unwrap();
+        let y = bn254::FieldElement::from_be_bytes(&ark_to_bytes(b)).unwrap();
+
+        assert_eq!((x + y).to_be_bytes(), ark_to_bytes(a + b));
+        assert_eq!((x - y).to_be_bytes(), ark_to_bytes(a - b));
+        assert_eq!((-x).to_be_bytes(), ark_to_bytes(-a));
+        assert_eq!((x * y).to_be_bytes(), ark_to_bytes(a * b));
+        assert_eq!(
+            x.invert().unwrap().to_be_bytes(),
+            ark_to_bytes(a.inverse().unwrap())
+        );
+        assert_eq!(
+            x.square().sqrt().map(|root| root.square()),
+            Some(x.square())
+        );
+    }
+
+    assert_eq!(bn254::FieldElement::ZERO.invert(), None);
+    assert_eq!(bn254::FieldElement::from_be_bytes(&[0xff; 32]), None);
+    assert_eq!(
+        bn254::FieldElement::from_be_bytes_reduced(&[0xff; 32]).to_be_bytes(),
+        ark_to_bytes(ark_bn254::Fq::from_be_bytes_mod_order(&[0xff; 32]))
+    );
+}
+
+#[test]
+fn secp256k1_scalar_mul() {
+    for _ in 0..4 {
+        let k = k256::NonZeroScalar::random(&mut thread_rng());
+        let expected = (k256::ProjectivePoint::GENERATOR * *k).to_affine();
+
+        let scalar = secp256k1::Scalar::from_be_bytes(&k.to_bytes().into()).unwrap();
+        let point = (secp256k1::ProjectivePoint::GENERATOR * scalar).to_affine();
+        assert_eq!(
+            point.to_uncompressed_sec1_bytes().unwrap().as_slice(),
+            expected.to_encoded_point(false).as_bytes()
+        );
+        assert_eq!(
+            secp256k1::AffinePoint::from_sec1_bytes(expected.to_encoded_point(true).as_bytes()),
+            Some(point)
+        );
+    }
+}
+
+#[test]
+fn p256_scalar_mul() {
+    for _ in 0..4 {
+        let k = ::p256::NonZeroScalar::random(&mut thread_rng());
+        let expected = (::p256::ProjectivePoint::GENERATOR * *k).to_affine();
+
+        let scalar = p256::Scalar::from_be_bytes(&k.to_bytes().into()).unwrap();
+        let point = (p256::ProjectivePoint::GENERATOR * scalar).to_affine();
+        assert_eq!(
+            point.to_uncompressed_sec1_bytes().unwrap().as_slice(),
+            expected.to_encoded_point(false).as_bytes()
+        );
+        assert_eq!(
+            p256::AffinePoint::from_sec1_bytes(expected.to_encoded_point(true).as_bytes()),
+            Some(point)
+        );
+    }
+}
+
+#[test]
+fn bn254_scalar_mul() {
+    for _ in 0..4 {
+        let k = ark_bn254::Fr::from_be_bytes_mod_order(&random_bytes());
+        let expected = (ark_bn254::G1Affine::generator() * k).into_affine();
+
+        let scalar = bn254::Scalar::from_be_bytes(&ark_to_bytes(k)).unwrap();
+        let point = (bn254::ProjectivePoint::GENERATOR * scalar).to_affine();
+        assert_eq!(point.x().unwrap().to_be_bytes(), ark_to_bytes(expected.x));
+        assert_eq!(point.y().unwrap().to_be_bytes(), ark_to_bytes(expected.y));
+    }
+}
+
+#[test]
+fn group_laws() {
+    let g = secp256k1::ProjectivePoint::GENERATOR;
+    let identity = secp256k1::ProjectivePoint::IDENTITY;
+    let a = secp256k1::Scalar::from_be_bytes_reduced(&random_bytes());
+    let b = secp256k1::Scalar::from_be_bytes_reduced(&random_bytes());
+
+    assert_eq!(g + identity, g);
+    assert_eq!(identity + g, g);
+    assert_eq!(g - g, identity);
+    assert_eq!(g + g, g.double());
+    assert_eq!(g * secp256k1::Scalar::ZERO, identity);
+    assert_eq!(g * -secp256k1::Scalar::ONE, -g);
+    assert_eq!((g * a) + (g * b), g * (a + b));
+    assert_eq!(g.lincomb(&a, &g.double(), &b), g * (a + b.double()));
+    assert!(identity.to_affine().is_identity());
+    assert!((g * a).to_affine().is_on_curve());
+}
+
+#[test]
+fn ecdsa_secp256k1() {
+    use k256::ecdsa::{signature::hazmat::PrehashSigner, SigningKey};
+
+    let signing_key = SigningKey::random(&mut thread_rng());
+    let public_key = secp256k1::AffinePoint::from_sec1_bytes(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes(),
+    )
+    .unwrap();
+
+    let prehash = random_bytes();
+    let signature: k256::ecdsa::Signature = signing_key.sign_prehash(&prehash).unwrap();
+    let signature = secp256k1::Signature::from_bytes(&signature.to_bytes().into()).unwrap();
+    assert!(ecdsa::verify_prehash(&public_key, &prehash, &signature));
+
+    // The negated signature is also valid.
+    let negated = secp256k1::Signature::from_scalars(signature.r(), -signature.s()).unwrap();
+    assert!(ecdsa::verify_prehash(&public_key, &prehash, &negated));
+
+    let mut wrong_prehash = prehash;
+    wrong_prehash[0] ^= 1;
+    assert!(!ecdsa::verify_prehash(
+        &public_key,
+        &wrong_prehash,
+        &signature
+    ));
+    assert!(!ecdsa::verify_prehash(
+        &secp256k1::AffinePoint::GENERATOR,
+        &prehash,
+        &signature
+    ));
+}
+
+#[test]
+fn ecdsa_p256() {
+    use ::p256::ecdsa::{signature::hazmat::PrehashSigner, SigningKey};
+
+    let signing_key = SigningKey::random(&mut thread_rng());
+    let public_key = p256::AffinePoint::from_sec1_bytes(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes(),
+    )
+    .unwrap();
+
+    let prehash = random_bytes();
+    let signature: ::p256::ecdsa::Signature = signing_key.sign_prehash(&prehash).unwrap();
+    let signature = p256::Signature::from_bytes(&signature.to_bytes().into()).unwrap();
+    assert!(ecdsa::verify_prehash(&public_key, &prehash, &signature));
+
+    let mut wrong_prehash = prehash;
+    wrong_prehash[31] ^= 1;
+    assert!(!ecdsa::verify_prehash(
+        &public_key,
+        &wrong_prehash,
+        &signature
+    ));
+}
+
+#[test]
+fn schnorr_secp256k1() {
+    use k256::schnorr::SigningKey;
+
+    let signing_key = SigningKey::random(&mut thread_rng());
+    let public_key: [u8; 32] = signing_key.verifying_key().to_bytes().into();
+
+    let message = b"attack at dawn";
+    let signature: [u8; 64] = signing_key
+        .sign_raw(message, &random_bytes())
+        .unwrap()
+        .to_bytes();
+    assert!(schnorr::verify(&public_key, message, &signature));
+
+    assert!(!schnorr::verify(&public_key, b"attack at dusk", &signature));
+    let mut wrong_signature = signature;
+    wrong_signature[63] ^= 1;
+    assert!(!schnorr::verify(&public_key, message, &wrong_signature));
+}
@@ -57,7 +57,7 @@ fn compress(
     }
 }
 
-fn compress_slice(out_state: *mut Digest, in_state: *const Digest, blocks: &[Block]) {
+pub(crate) fn compress_slice(out_state: *mut Digest, in_state: *const Digest, blocks: &[Block]) {
     // SAFETY: This is only called from this crate. It's perfectly fine
     // for in_state and out_state to point at the same place, and for
     // out_state to be uninitialized memory, and those preclude us
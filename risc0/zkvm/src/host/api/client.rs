@@ -18,9 +18,11 @@ use anyhow::{anyhow, bail, Result};
 use bytes::Bytes;
 use prost::Message;
 
+#[cfg(unix)]
+use super::UnixConnector;
 use super::{
     malformed_err, pb, Asset, AssetRequest, Binary, ConnectionWrapper, Connector,
-    ParentProcessConnector, SessionInfo,
+    ParentProcessConnector, SessionInfo, TcpConnector,
 };
 use crate::{
     get_version,
@@ -56,6 +58,21 @@ impl Client {
         Ok(Self::with_connector(Box::new(connector)))
     }
 
+    /// Construct a [Client] that connects to a long-lived server, such as
+    /// `r0vm --listen`, at the specified TCP/IP address.
+    pub fn new_tcp<A: AsRef<str>>(addr: A) -> Self {
+        let connector = TcpConnector::new(addr.as_ref());
+        Self::with_connector(Box::new(connector))
+    }
+
+    /// Construct a [Client] that connects to a long-lived server, such as
+    /// `r0vm --listen-unix`, on a Unix domain socket at the specified `path`.
+    #[cfg(unix)]
+    pub fn new_unix<P: AsRef<Path>>(path: P) -> Self {
+        let connector = UnixConnector::new(path);
+        Self::with_connector(Box::new(connector))
+    }
+
     /// Construct a [Client] based on environment variables.
     pub fn from_env() -> Result<Self> {
         Client::new_sub_process(get_r0vm_path())
@@ -253,6 +270,49 @@ impl Client {
         result
     }
 
+    /// Resolve the head assumption of a conditional [SuccinctReceipt] using a
+    /// [SuccinctReceipt] of that assumption.
+    pub fn resolve(
+        &self,
+        opts: ProverOpts,
+        conditional_receipt: Asset,
+        assumption_receipt: Asset,
+        receipt_out: AssetRequest,
+    ) -> Result<SuccinctReceipt> {
+        let mut conn = self.connect()?;
+
+        let request = pb::api::ServerRequest {
+            kind: Some(pb::api::server_request::Kind::Resolve(
+                pb::api::ResolveRequest {
+                    opts: Some(opts.into()),
+                    conditional_receipt: Some(conditional_receipt.try_into()?),
+                    assumption_receipt: Some(assumption_receipt.try_into()?),
+                    receipt_out: Some(receipt_out.try_into()?),
+                },
+            )),
+        };
+        log::debug!("tx: {request:?}");
+        conn.send(request)?;
+
+        let reply: pb::api::ResolveReply = conn.recv()?;
+
+        let result = match reply.kind.ok_or(malformed_err())? {
+            pb::api::resolve_reply::Kind::Ok(result) => {
+                let receipt_bytes = result.receipt.ok_or(malformed_err())?.as_bytes()?;
+                let receipt_pb = pb::core::SuccinctReceipt::decode(receipt_bytes)?;
+                receipt_pb.try_into()
+            }
+            pb::api::resolve_reply::Kind::Error(err) => Err(err.into()),
+        };
+
+        let code = conn.close()?;
+        if code != 0 {
+            bail!("Child finished with: {code}");
+        }
+
+        result
+    }
+
     /// Convert a [SuccinctReceipt] with a poseidon hash function that uses a 254-bit field
     pub fn identity_p254(
         &self,
@@ -342,6 +402,17 @@ impl Client {
             write_fds: env.posix_io.borrow().write_fds.keys().cloned().collect(),
             segment_limit_po2: env.segment_limit_po2,
             session_limit: env.session_limit,
+            assumptions: env
+                .assumptions
+                .borrow()
+                .cached
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect(),
+            trace: env.trace.is_some(),
+            trace_batch_size: env.trace_batch_size.unwrap_or(1),
+            trace_cycles: env.trace_cycles,
         }
     }
 
@@ -379,6 +450,7 @@ impl Client {
                                         let info = SegmentInfo {
                                             po2: segment.po2,
                                             cycles: segment.cycles,
+                                            stats: segment.stats.map(Into::into).unwrap_or_default(),
                                         };
                                         segments.push(info.clone());
                                         callback(info, asset)
@@ -397,6 +469,7 @@ impl Client {
                                         .exit_code
                                         .ok_or(malformed_err())?
                                         .try_into()?,
+                                    stats: session.stats.map(Into::into).unwrap_or_default(),
                                 }),
                                 None => Err(malformed_err()),
                             }
@@ -463,6 +536,12 @@ impl Client {
                 self.on_trace(env, event)?;
                 Ok(Bytes::new())
             }
+            pb::api::on_io_request::Kind::Traces(traces) => {
+                for event in traces.events {
+                    self.on_trace(env, event)?;
+                }
+                Ok(Bytes::new())
+            }
         }
     }
 
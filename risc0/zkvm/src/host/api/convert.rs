@@ -22,11 +22,12 @@ use risc0_zkp::core::digest::Digest;
 use super::{malformed_err, path_to_string, pb, Asset, AssetRequest, Binary, BinaryKind};
 use crate::{
     host::{
-        receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt},
+        receipt::{Assumption, CompositeReceipt, InnerReceipt, SegmentReceipt},
         recursion::SuccinctReceipt,
     },
     receipt_metadata::{Assumptions, MaybePruned, Output},
-    ExitCode, Journal, ProverOpts, Receipt, ReceiptMetadata, TraceEvent,
+    ExecutionStats, ExitCode, Journal, ProverOpts, Receipt, ReceiptKind, ReceiptMetadata,
+    SyscallStats, TraceEvent,
 };
 
 mod ver {
@@ -111,6 +112,90 @@ impl TryFrom<pb::api::TraceEvent> for TraceEvent {
                 addr: event.addr,
                 value: event.value,
             },
+            pb::api::trace_event::Kind::PageIn(event) => TraceEvent::PageIn {
+                page_idx: event.page_idx,
+                cycles: event.cycles,
+            },
+            pb::api::trace_event::Kind::PageOut(event) => TraceEvent::PageOut {
+                page_idx: event.page_idx,
+                cycles: event.cycles,
+            },
+            pb::api::trace_event::Kind::Syscall(event) => TraceEvent::Syscall {
+                name: event.name,
+                cycles: event.cycles,
+            },
+        })
+    }
+}
+
+impl From<TraceEvent> for pb::api::TraceEvent {
+    fn from(event: TraceEvent) -> Self {
+        Self {
+            kind: Some(match event {
+                TraceEvent::InstructionStart { cycle, pc, insn } => {
+                    pb::api::trace_event::Kind::InsnStart(pb::api::trace_event::InstructionStart {
+                        cycle,
+                        pc,
+                        insn,
+                    })
+                }
+                TraceEvent::RegisterSet { idx, value } => {
+                    pb::api::trace_event::Kind::RegisterSet(pb::api::trace_event::RegisterSet {
+                        idx: idx as u32,
+                        value,
+                    })
+                }
+                TraceEvent::MemorySet { addr, value } => {
+                    pb::api::trace_event::Kind::MemorySet(pb::api::trace_event::MemorySet {
+                        addr,
+                        value,
+                    })
+                }
+                TraceEvent::PageIn { page_idx, cycles } => {
+                    pb::api::trace_event::Kind::PageIn(pb::api::trace_event::PageIn {
+                        page_idx,
+                        cycles,
+                    })
+                }
+                TraceEvent::PageOut { page_idx, cycles } => {
+                    pb::api::trace_event::Kind::PageOut(pb::api::trace_event::PageOut {
+                        page_idx,
+                        cycles,
+                    })
+                }
+                TraceEvent::Syscall { name, cycles } => {
+                    pb::api::trace_event::Kind::Syscall(pb::api::trace_event::Syscall {
+                        name,
+                        cycles,
+                    })
+                }
+            }),
+        }
+    }
+}
+
+impl From<Assumption> for pb::api::Assumption {
+    fn from(value: Assumption) -> Self {
+        Self {
+            kind: Some(match value {
+                Assumption::Proven(receipt) => pb::api::assumption::Kind::Proven(receipt.into()),
+                Assumption::Unresolved(metadata) => {
+                    pb::api::assumption::Kind::Unresolved(metadata.into())
+                }
+            }),
+        }
+    }
+}
+
+impl TryFrom<pb::api::Assumption> for Assumption {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::api::Assumption) -> Result<Self> {
+        Ok(match value.kind.ok_or(malformed_err())? {
+            pb::api::assumption::Kind::Proven(receipt) => Self::Proven(receipt.try_into()?),
+            pb::api::assumption::Kind::Unresolved(metadata) => {
+                Self::Unresolved(metadata.try_into()?)
+            }
         })
     }
 }
@@ -168,12 +253,80 @@ impl From<pb::api::GenericError> for anyhow::Error {
     }
 }
 
-impl From<pb::api::ProverOpts> for ProverOpts {
-    fn from(opts: pb::api::ProverOpts) -> Self {
+impl From<ExecutionStats> for pb::api::ExecutionStats {
+    fn from(stats: ExecutionStats) -> Self {
+        Self {
+            segments: stats.segments,
+            total_cycles: stats.total_cycles,
+            user_cycles: stats.user_cycles,
+            page_reads: stats.page_reads,
+            page_read_cycles: stats.page_read_cycles,
+            page_writes: stats.page_writes,
+            page_write_cycles: stats.page_write_cycles,
+            overhead_cycles: stats.overhead_cycles,
+            padding_cycles: stats.padding_cycles,
+            syscalls: stats
+                .syscalls
+                .into_iter()
+                .map(|(name, stats)| {
+                    (
+                        name,
+                        pb::api::SyscallStats {
+                            count: stats.count,
+                            cycles: stats.cycles,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<pb::api::ExecutionStats> for ExecutionStats {
+    fn from(stats: pb::api::ExecutionStats) -> Self {
         Self {
+            segments: stats.segments,
+            total_cycles: stats.total_cycles,
+            user_cycles: stats.user_cycles,
+            page_reads: stats.page_reads,
+            page_read_cycles: stats.page_read_cycles,
+            page_writes: stats.page_writes,
+            page_write_cycles: stats.page_write_cycles,
+            overhead_cycles: stats.overhead_cycles,
+            padding_cycles: stats.padding_cycles,
+            syscalls: stats
+                .syscalls
+                .into_iter()
+                .map(|(name, stats)| {
+                    (
+                        name,
+                        SyscallStats {
+                            count: stats.count,
+                            cycles: stats.cycles,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::api::ProverOpts> for ProverOpts {
+    type Error = anyhow::Error;
+
+    fn try_from(opts: pb::api::ProverOpts) -> Result<Self> {
+        Ok(Self {
+            receipt_kind: opts.receipt_kind().into(),
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
-        }
+            segment_workers: opts.segment_workers as usize,
+            segment_memory_limit: opts.segment_memory_limit.map(|x| x as usize),
+            recursion_control_ids: opts
+                .recursion_control_ids
+                .into_iter()
+                .map(|id| id.try_into())
+                .collect::<Result<_>>()?,
+        })
     }
 }
 
@@ -182,6 +335,32 @@ impl From<ProverOpts> for pb::api::ProverOpts {
         Self {
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
+            segment_workers: opts.segment_workers as u32,
+            segment_memory_limit: opts.segment_memory_limit.map(|x| x as u64),
+            receipt_kind: pb::api::prover_opts::ReceiptKind::from(opts.receipt_kind) as i32,
+            recursion_control_ids: opts
+                .recursion_control_ids
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<pb::api::prover_opts::ReceiptKind> for ReceiptKind {
+    fn from(kind: pb::api::prover_opts::ReceiptKind) -> Self {
+        match kind {
+            pb::api::prover_opts::ReceiptKind::Composite => Self::Composite,
+            pb::api::prover_opts::ReceiptKind::Succinct => Self::Succinct,
+        }
+    }
+}
+
+impl From<ReceiptKind> for pb::api::prover_opts::ReceiptKind {
+    fn from(kind: ReceiptKind) -> Self {
+        match kind {
+            ReceiptKind::Composite => Self::Composite,
+            ReceiptKind::Succinct => Self::Succinct,
         }
     }
 }
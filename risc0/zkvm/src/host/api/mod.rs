@@ -20,23 +20,28 @@ pub(crate) mod server;
 #[cfg(feature = "prove")]
 mod tests;
 
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
 use std::{
+    collections::BTreeMap,
+    fmt,
     io::{Read, Write},
     net::{TcpListener, TcpStream},
     path::{Path, PathBuf},
-    process::{Child, Command},
+    process::{Child, Command, Stdio},
     sync::{
         atomic::{AtomicBool, Ordering},
         mpsc::channel,
-        Arc,
+        Arc, OnceLock,
     },
     thread,
     time::Duration,
 };
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use bytes::{Buf, BufMut, Bytes};
 use prost::Message;
+use serde::{Deserialize, Serialize};
 
 use crate::{ExitCode, Journal};
 
@@ -52,12 +57,16 @@ mod pb {
     }
 }
 
-const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
-
 trait RootMessage: Message {}
 
+/// A bidirectional byte stream that a client and server exchange messages
+/// over.
+pub trait Stream: Read + Write {}
+
+impl<T: Read + Write> Stream for T {}
+
 pub trait Connection {
-    fn stream(&self) -> &TcpStream;
+    fn stream(&mut self) -> &mut dyn Stream;
     fn close(&mut self) -> Result<i32>;
     fn try_clone(&self) -> Result<Box<dyn Connection>>;
 }
@@ -80,6 +89,8 @@ impl RootMessage for pb::api::JoinRequest {}
 impl RootMessage for pb::api::JoinReply {}
 impl RootMessage for pb::api::IdentityP254Request {}
 impl RootMessage for pb::api::IdentityP254Reply {}
+impl RootMessage for pb::api::ResolveRequest {}
+impl RootMessage for pb::api::ResolveReply {}
 
 impl ConnectionWrapper {
     fn new(inner: Box<dyn Connection>) -> Self {
@@ -94,11 +105,13 @@ impl ConnectionWrapper {
         self.buf.clear();
         self.buf.put_u32_le(len as u32);
         msg.encode(&mut self.buf)?;
-        Ok(self.inner.stream().write_all(&self.buf)?)
+        let stream = self.inner.stream();
+        stream.write_all(&self.buf)?;
+        Ok(stream.flush()?)
     }
 
     fn recv<T: Default + RootMessage>(&mut self) -> Result<T> {
-        let mut stream = self.inner.stream();
+        let stream = self.inner.stream();
         self.buf.resize(4, 0);
         stream.read_exact(&mut self.buf)?;
         let len = self.buf.as_slice().get_u32_le() as usize;
@@ -123,16 +136,42 @@ pub trait Connector {
     fn connect(&self) -> Result<ConnectionWrapper>;
 }
 
+/// Joins a reader and a writer, such as the stdin and stdout of a process, into
+/// a single [Stream].
+struct Pipe<R: Read, W: Write> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> Read for Pipe<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl<R: Read, W: Write> Write for Pipe<R, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// How long to wait for a server launched with `--port` to connect back.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
 struct ParentProcessConnector {
     server_path: PathBuf,
-    listener: TcpListener,
+    stdio: OnceLock<bool>,
 }
 
 impl ParentProcessConnector {
     pub fn new<P: AsRef<Path>>(server_path: P) -> Result<Self> {
         Ok(Self {
             server_path: server_path.as_ref().to_path_buf(),
-            listener: TcpListener::bind("127.0.0.1:0")?,
+            stdio: OnceLock::new(),
         })
     }
 
@@ -143,11 +182,47 @@ impl ParentProcessConnector {
             self.server_path.to_string_lossy()
         )
     }
-}
 
-impl Connector for ParentProcessConnector {
-    fn connect(&self) -> Result<ConnectionWrapper> {
-        let addr = self.listener.local_addr()?;
+    /// Return true if the server can be launched with `--stdio`.
+    ///
+    /// Servers that predate `--stdio` report the same version, so this checks
+    /// for the flag itself.
+    fn supports_stdio(&self) -> Result<bool> {
+        if let Some(stdio) = self.stdio.get() {
+            return Ok(*stdio);
+        }
+        let output = Command::new(&self.server_path)
+            .arg("--help")
+            .output()
+            .with_context(|| self.spawn_fail())?;
+        let stdio = String::from_utf8_lossy(&output.stdout).contains("--stdio");
+        Ok(*self.stdio.get_or_init(|| stdio))
+    }
+
+    fn connect_stdio(&self) -> Result<ConnectionWrapper> {
+        // The child speaks the protocol over its stdin and stdout, so unlike
+        // a loopback port, the connection is private to this process.
+        let mut child = Command::new(&self.server_path)
+            .arg("--stdio")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| self.spawn_fail())?;
+        let stream = Pipe {
+            reader: child.stdout.take().ok_or(malformed_err())?,
+            writer: child.stdin.take().ok_or(malformed_err())?,
+        };
+
+        Ok(ConnectionWrapper::new(Box::new(
+            ParentProcessConnection::new(child, Box::new(stream)),
+        )))
+    }
+
+    /// Connect to a server that predates `--stdio`, which connects back to a
+    /// port on the loopback interface.
+    fn connect_port(&self) -> Result<ConnectionWrapper> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
         let child = Command::new(&self.server_path)
             .arg("--port")
             .arg(addr.port().to_string())
@@ -157,7 +232,6 @@ impl Connector for ParentProcessConnector {
         let shutdown = Arc::new(AtomicBool::new(false));
         let server_shutdown = shutdown.clone();
         let (tx, rx) = channel();
-        let listener = self.listener.try_clone()?;
         let handle = thread::spawn(move || {
             let stream = listener.accept();
             if server_shutdown.load(Ordering::Relaxed) {
@@ -177,17 +251,26 @@ impl Connector for ParentProcessConnector {
         })?;
 
         Ok(ConnectionWrapper::new(Box::new(
-            ParentProcessConnection::new(child, stream),
+            ParentProcessConnection::new(child, Box::new(stream)),
         )))
     }
 }
 
+impl Connector for ParentProcessConnector {
+    fn connect(&self) -> Result<ConnectionWrapper> {
+        if self.supports_stdio()? {
+            self.connect_stdio()
+        } else {
+            self.connect_port()
+        }
+    }
+}
+
 struct TcpConnector {
     addr: String,
 }
 
 impl TcpConnector {
-    #[cfg(feature = "prove")]
     pub(crate) fn new(addr: &str) -> Self {
         Self {
             addr: addr.to_string(),
@@ -203,24 +286,73 @@ impl Connector for TcpConnector {
     }
 }
 
+#[cfg(unix)]
+struct UnixConnector {
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl UnixConnector {
+    pub(crate) fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Connector for UnixConnector {
+    fn connect(&self) -> Result<ConnectionWrapper> {
+        log::debug!("connect");
+        let stream = UnixStream::connect(&self.path)?;
+        Ok(ConnectionWrapper::new(Box::new(UnixConnection::new(
+            stream,
+        ))))
+    }
+}
+
+/// Connects a server to the client which launched it, over the stdin and
+/// stdout of the server process.
+#[cfg(feature = "prove")]
+struct StdioConnector;
+
+#[cfg(feature = "prove")]
+impl Connector for StdioConnector {
+    fn connect(&self) -> Result<ConnectionWrapper> {
+        // Guest logs would otherwise be interleaved with protocol messages.
+        crate::host::server::exec::syscall::reserve_stdout();
+        Ok(ConnectionWrapper::new(Box::new(StdioConnection::new())))
+    }
+}
+
 struct ParentProcessConnection {
     child: Child,
-    stream: TcpStream,
+    stream: Box<dyn Stream>,
 }
 
 struct TcpConnection {
     stream: TcpStream,
 }
 
+#[cfg(unix)]
+struct UnixConnection {
+    stream: UnixStream,
+}
+
+#[cfg(feature = "prove")]
+struct StdioConnection {
+    stream: Pipe<std::io::Stdin, std::io::Stdout>,
+}
+
 impl ParentProcessConnection {
-    pub fn new(child: Child, stream: TcpStream) -> Self {
+    pub fn new(child: Child, stream: Box<dyn Stream>) -> Self {
         Self { child, stream }
     }
 }
 
 impl Connection for ParentProcessConnection {
-    fn stream(&self) -> &TcpStream {
-        &self.stream
+    fn stream(&mut self) -> &mut dyn Stream {
+        self.stream.as_mut()
     }
 
     fn close(&mut self) -> Result<i32> {
@@ -229,7 +361,7 @@ impl Connection for ParentProcessConnection {
     }
 
     fn try_clone(&self) -> Result<Box<dyn Connection>> {
-        unimplemented!()
+        bail!("A connection to a child process cannot be cloned")
     }
 }
 
@@ -240,8 +372,30 @@ impl TcpConnection {
 }
 
 impl Connection for TcpConnection {
-    fn stream(&self) -> &TcpStream {
-        &self.stream
+    fn stream(&mut self) -> &mut dyn Stream {
+        &mut self.stream
+    }
+
+    fn close(&mut self) -> Result<i32> {
+        Ok(0)
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Connection>> {
+        Ok(Box::new(Self::new(self.stream.try_clone()?)))
+    }
+}
+
+#[cfg(unix)]
+impl UnixConnection {
+    pub fn new(stream: UnixStream) -> Self {
+        Self { stream }
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixConnection {
+    fn stream(&mut self) -> &mut dyn Stream {
+        &mut self.stream
     }
 
     fn close(&mut self) -> Result<i32> {
@@ -253,6 +407,34 @@ impl Connection for TcpConnection {
     }
 }
 
+#[cfg(feature = "prove")]
+impl StdioConnection {
+    pub fn new() -> Self {
+        Self {
+            stream: Pipe {
+                reader: std::io::stdin(),
+                writer: std::io::stdout(),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "prove")]
+impl Connection for StdioConnection {
+    fn stream(&mut self) -> &mut dyn Stream {
+        &mut self.stream
+    }
+
+    fn close(&mut self) -> Result<i32> {
+        Ok(0)
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn Connection>> {
+        // Stdin and stdout are handles to process-wide streams.
+        Ok(Box::new(Self::new()))
+    }
+}
+
 fn malformed_err() -> anyhow::Error {
     anyhow!("Malformed error")
 }
@@ -309,6 +491,11 @@ pub struct SessionInfo {
 
     /// The [ExitCode] of the session.
     pub exit_code: ExitCode,
+
+    /// The breakdown of the cycles of all segments in the session.
+    ///
+    /// This is empty when the server does not report it.
+    pub stats: ExecutionStats,
 }
 
 /// Provides information about a segment of execution.
@@ -320,6 +507,109 @@ pub struct SegmentInfo {
     /// The number of user cycles without any overhead for continuations or po2
     /// padding.
     pub cycles: u32,
+
+    /// The breakdown of the cycles of the segment.
+    ///
+    /// This is empty when the server does not report it.
+    pub stats: ExecutionStats,
+}
+
+/// A breakdown of where the cycles of an execution are spent.
+///
+/// The cycles of a segment add up as `user_cycles + page_read_cycles +
+/// page_write_cycles + overhead_cycles + padding_cycles == total_cycles`, where
+/// `total_cycles` is the power of two that will be proven.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionStats {
+    /// The number of segments.
+    pub segments: u64,
+
+    /// The number of cycles that will be proven.
+    pub total_cycles: u64,
+
+    /// The number of cycles spent executing instructions, including syscalls.
+    pub user_cycles: u64,
+
+    /// The number of pages read into memory.
+    pub page_reads: u64,
+
+    /// The number of cycles spent reading pages.
+    pub page_read_cycles: u64,
+
+    /// The number of pages written back from memory.
+    pub page_writes: u64,
+
+    /// The number of cycles spent writing pages.
+    pub page_write_cycles: u64,
+
+    /// The number of cycles spent on the fixed cost of each segment, such as
+    /// loading and finalizing the memory image.
+    pub overhead_cycles: u64,
+
+    /// The number of cycles wasted padding segments up to a power of two.
+    pub padding_cycles: u64,
+
+    /// The number and cost of the syscalls made by the guest, by name.
+    pub syscalls: BTreeMap<String, SyscallStats>,
+}
+
+/// The number and cost of calls to a syscall.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyscallStats {
+    /// The number of calls.
+    pub count: u64,
+
+    /// The number of cycles spent in the calls.
+    pub cycles: u64,
+}
+
+impl ExecutionStats {
+    /// Add the stats of another execution, such as a following segment, to
+    /// these.
+    pub fn merge(&mut self, other: &ExecutionStats) {
+        self.segments += other.segments;
+        self.total_cycles += other.total_cycles;
+        self.user_cycles += other.user_cycles;
+        self.page_reads += other.page_reads;
+        self.page_read_cycles += other.page_read_cycles;
+        self.page_writes += other.page_writes;
+        self.page_write_cycles += other.page_write_cycles;
+        self.overhead_cycles += other.overhead_cycles;
+        self.padding_cycles += other.padding_cycles;
+        for (name, stats) in other.syscalls.iter() {
+            let entry = self.syscalls.entry(name.clone()).or_default();
+            entry.count += stats.count;
+            entry.cycles += stats.cycles;
+        }
+    }
+}
+
+impl fmt::Display for ExecutionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "segments:          {}", self.segments)?;
+        writeln!(f, "total cycles:      {}", self.total_cycles)?;
+        writeln!(f, "user cycles:       {}", self.user_cycles)?;
+        writeln!(
+            f,
+            "page reads:        {} ({} cycles)",
+            self.page_reads, self.page_read_cycles
+        )?;
+        writeln!(
+            f,
+            "page writes:       {} ({} cycles)",
+            self.page_writes, self.page_write_cycles
+        )?;
+        writeln!(f, "overhead cycles:   {}", self.overhead_cycles)?;
+        writeln!(f, "padding cycles:    {}", self.padding_cycles)?;
+        for (name, stats) in self.syscalls.iter() {
+            writeln!(
+                f,
+                "syscall {name}: {} calls ({} cycles)",
+                stats.count, stats.cycles
+            )?;
+        }
+        Ok(())
+    }
 }
 
 impl Binary {
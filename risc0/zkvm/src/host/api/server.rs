@@ -12,10 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::{
+    cell::RefCell,
     error::Error as StdError,
     io::{BufReader, Error as IoError, ErrorKind as IoErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
     path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
 };
 
 use anyhow::{anyhow, bail, Result};
@@ -25,11 +36,17 @@ use risc0_binfmt::{MemoryImage, Program};
 use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE};
 use serde::{Deserialize, Serialize};
 
-use super::{malformed_err, path_to_string, pb, ConnectionWrapper, Connector, TcpConnector};
+use super::{
+    malformed_err, path_to_string, pb, Connection, ConnectionWrapper, Connector, StdioConnector,
+    TcpConnection, TcpConnector,
+};
+#[cfg(unix)]
+use super::{UnixConnection, UnixConnector};
 use crate::{
     get_prover_server, get_version,
     host::{client::slice_io::SliceIo, recursion::SuccinctReceipt},
-    ExecutorEnv, ExecutorImpl, ProverOpts, Segment, SegmentReceipt, SegmentRef, VerifierContext,
+    ExecutorEnv, ExecutorImpl, ProverOpts, Segment, SegmentReceipt, SegmentRef, TraceEvent,
+    VerifierContext,
 };
 
 /// A server implementation for handling requests by clients of the zkVM.
@@ -37,6 +54,67 @@ pub struct Server {
     connector: Box<dyn Connector>,
 }
 
+/// A long-lived server that accepts connections from many clients of the zkVM
+/// and handles their requests concurrently.
+///
+/// Each connection is handled on its own worker thread, up to a configurable
+/// limit. Connections accepted while all workers are busy wait for a worker to
+/// become available.
+///
+/// Clients may be on other hosts, so all assets must be sent inline: requests
+/// that refer to an [Asset::Path](super::Asset::Path) or
+/// [AssetRequest::Path](super::AssetRequest::Path) are rejected rather than
+/// reading or writing files on the server.
+pub struct Daemon {
+    listener: Listener,
+    max_workers: usize,
+    shutdown: Arc<AtomicBool>,
+}
+
+/// A handle used to stop a running [Daemon].
+#[derive(Clone)]
+pub struct DaemonHandle {
+    addr: ListenAddr,
+    shutdown: Arc<AtomicBool>,
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+#[derive(Clone)]
+enum ListenAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// How long a [Daemon] waits before accepting again after a failed accept, so
+/// that a persistent error such as running out of file descriptors does not
+/// spin.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The assets that a connection may use.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AssetPolicy {
+    /// Assets may be read from and written to any path the server can access.
+    Any,
+    /// Assets must be sent inline.
+    InlineOnly,
+}
+
+/// Counts the workers of a [Daemon] that are handling a connection.
+struct WorkerSlots {
+    busy: Mutex<usize>,
+    idle: Condvar,
+    max: usize,
+}
+
+/// A busy worker slot, released when dropped, even if the worker panics.
+struct WorkerSlot<'a>(&'a WorkerSlots);
+
 #[derive(Clone, Serialize, Deserialize)]
 struct EmptySegmentRef;
 
@@ -175,6 +253,57 @@ impl SliceIo for SliceIoProxy {
     }
 }
 
+/// Sends [TraceEvent]s to the client in batches.
+struct TraceProxy {
+    conn: ConnectionWrapper,
+    batch_size: usize,
+    events: Vec<pb::api::TraceEvent>,
+}
+
+impl TraceProxy {
+    fn new(conn: ConnectionWrapper, batch_size: u32) -> Self {
+        let batch_size = batch_size.max(1) as usize;
+        Self {
+            conn,
+            batch_size,
+            events: Vec::with_capacity(batch_size),
+        }
+    }
+
+    fn on_event(&mut self, event: TraceEvent) -> Result<()> {
+        self.events.push(event.into());
+        if self.events.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Send any buffered events to the client.
+    fn flush(&mut self) -> Result<()> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
+        let request = pb::api::ServerReply {
+            kind: Some(pb::api::server_reply::Kind::Ok(pb::api::ClientCallback {
+                kind: Some(pb::api::client_callback::Kind::Io(pb::api::OnIoRequest {
+                    kind: Some(pb::api::on_io_request::Kind::Traces(pb::api::TraceEvents {
+                        events: std::mem::take(&mut self.events),
+                    })),
+                })),
+            })),
+        };
+        log::trace!("tx: {request:?}");
+        self.conn.send(request)?;
+
+        let reply: pb::api::OnIoReply = self.conn.recv()?;
+        log::trace!("rx: {reply:?}");
+        match reply.kind.ok_or(malformed_err())? {
+            pb::api::on_io_reply::Kind::Ok(_) => Ok(()),
+            pb::api::on_io_reply::Kind::Error(err) => Err(err.into()),
+        }
+    }
+}
+
 impl Server {
     /// Construct a new [Server] with the specified [Connector].
     pub fn new(connector: Box<dyn Connector>) -> Self {
@@ -188,11 +317,32 @@ impl Server {
         Self::new(Box::new(connector))
     }
 
+    /// Construct a new [Server] which will connect to a Unix domain socket at
+    /// the specified `path`.
+    #[cfg(unix)]
+    pub fn new_unix<P: AsRef<Path>>(path: P) -> Self {
+        let connector = UnixConnector::new(path);
+        Self::new(Box::new(connector))
+    }
+
+    /// Construct a new [Server] which communicates with the client that
+    /// launched this process over stdin and stdout.
+    ///
+    /// Nothing else may write to stdout while the server is running.
+    pub fn new_stdio() -> Self {
+        Self::new(Box::new(StdioConnector))
+    }
+
     /// Start the [Server] and run until all requests are complete.
     pub fn run(&self) -> Result<()> {
         log::debug!("connect");
-        let mut conn = self.connector.connect()?;
+        let conn = self.connector.connect()?;
+        Self::handle(conn, AssetPolicy::Any)
+    }
 
+    /// Handle a single request from the client on the other end of `conn`,
+    /// using only the assets allowed by `policy`.
+    fn handle(mut conn: ConnectionWrapper, policy: AssetPolicy) -> Result<()> {
         let server_version = get_version().map_err(|err| anyhow!(err))?;
 
         let request: pb::api::HelloRequest = conn.recv()?;
@@ -219,37 +369,39 @@ impl Server {
 
         let request: pb::api::ServerRequest = conn.recv()?;
         log::trace!("rx: {request:?}");
+        if policy == AssetPolicy::InlineOnly && request.uses_paths() {
+            bail!("assets must be sent inline to this server");
+        }
         match request.kind.ok_or(malformed_err())? {
             pb::api::server_request::Kind::Prove(request) => {
-                self.on_prove(conn, request)?;
+                Self::on_prove(conn, request)?;
             }
             pb::api::server_request::Kind::Execute(request) => {
-                self.on_execute(conn, request)?;
+                Self::on_execute(conn, request)?;
             }
             pb::api::server_request::Kind::ProveSegment(request) => {
-                self.on_prove_segment(conn, request)?
+                Self::on_prove_segment(conn, request)?
             }
             pb::api::server_request::Kind::Lift(request) => {
-                self.on_lift(conn, request)?;
+                Self::on_lift(conn, request)?;
             }
             pb::api::server_request::Kind::Join(request) => {
-                self.on_join(conn, request)?;
+                Self::on_join(conn, request)?;
             }
             pb::api::server_request::Kind::IdentiyP254(request) => {
-                self.on_identity_p254(conn, request)?;
+                Self::on_identity_p254(conn, request)?;
+            }
+            pb::api::server_request::Kind::Resolve(request) => {
+                Self::on_resolve(conn, request)?;
             }
         };
 
         Ok(())
     }
 
-    fn on_execute(
-        &self,
-        mut conn: ConnectionWrapper,
-        request: pb::api::ExecuteRequest,
-    ) -> Result<()> {
+    fn on_execute(mut conn: ConnectionWrapper, request: pb::api::ExecuteRequest) -> Result<()> {
         let env_request = request.env.ok_or(malformed_err())?;
-        let env = self.build_env(&conn, &env_request)?;
+        let (env, trace) = Self::build_env(&conn, &env_request)?;
 
         let binary = env_request.binary.ok_or(malformed_err())?;
         let image = binary.as_image()?;
@@ -272,6 +424,7 @@ impl Server {
                                 po2: segment.po2,
                                 cycles: segment.cycles,
                                 segment: Some(asset),
+                                stats: Some(segment.stats.into()),
                             }),
                         },
                     )),
@@ -289,6 +442,9 @@ impl Server {
 
             Ok(Box::new(EmptySegmentRef))
         })?;
+        if let Some(trace) = trace {
+            trace.borrow_mut().flush()?;
+        }
 
         let msg = pb::api::ServerReply {
             kind: Some(pb::api::server_reply::Kind::Ok(pb::api::ClientCallback {
@@ -298,6 +454,7 @@ impl Server {
                             segments: session.segments.len().try_into()?,
                             journal: session.journal.unwrap_or_default().bytes,
                             exit_code: Some(session.exit_code.into()),
+                            stats: Some(session.stats.into()),
                         }),
                     },
                 )),
@@ -309,17 +466,20 @@ impl Server {
         Ok(())
     }
 
-    fn on_prove(&self, mut conn: ConnectionWrapper, request: pb::api::ProveRequest) -> Result<()> {
+    fn on_prove(mut conn: ConnectionWrapper, request: pb::api::ProveRequest) -> Result<()> {
         let env_request = request.env.ok_or(malformed_err())?;
-        let env = self.build_env(&conn, &env_request)?;
+        let (env, trace) = Self::build_env(&conn, &env_request)?;
 
         let binary = env_request.binary.ok_or(malformed_err())?;
         let image = binary.as_image()?;
 
-        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
+        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.try_into()?;
         let prover = get_prover_server(&opts)?;
-        let ctx = VerifierContext::default();
+        let ctx = verifier_context(&opts);
         let receipt = prover.prove(env, &ctx, image)?;
+        if let Some(trace) = trace {
+            trace.borrow_mut().flush()?;
+        }
 
         let receipt_pb: pb::core::Receipt = receipt.into();
         let receipt_bytes = receipt_pb.encode_to_vec();
@@ -345,16 +505,15 @@ impl Server {
     }
 
     fn on_prove_segment(
-        &self,
         mut conn: ConnectionWrapper,
         request: pb::api::ProveSegmentRequest,
     ) -> Result<()> {
-        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
+        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.try_into()?;
         let segment_bytes = request.segment.ok_or(malformed_err())?.as_bytes()?;
         let segment: Segment = bincode::deserialize(&segment_bytes)?;
 
         let prover = get_prover_server(&opts)?;
-        let ctx = VerifierContext::default();
+        let ctx = verifier_context(&opts);
         let receipt = prover.prove_segment(&ctx, &segment)?;
 
         let receipt_pb: pb::core::SegmentReceipt = receipt.into();
@@ -378,8 +537,8 @@ impl Server {
         Ok(())
     }
 
-    fn on_lift(&self, mut conn: ConnectionWrapper, request: pb::api::LiftRequest) -> Result<()> {
-        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
+    fn on_lift(mut conn: ConnectionWrapper, request: pb::api::LiftRequest) -> Result<()> {
+        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.try_into()?;
         let receipt_bytes = request.receipt.ok_or(malformed_err())?.as_bytes()?;
         let segment_receipt: SegmentReceipt = bincode::deserialize(&receipt_bytes)?;
 
@@ -405,8 +564,8 @@ impl Server {
         Ok(())
     }
 
-    fn on_join(&self, mut conn: ConnectionWrapper, request: pb::api::JoinRequest) -> Result<()> {
-        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
+    fn on_join(mut conn: ConnectionWrapper, request: pb::api::JoinRequest) -> Result<()> {
+        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.try_into()?;
         let left_receipt_bytes = request.left_receipt.ok_or(malformed_err())?.as_bytes()?;
         let left_succinct_receipt: SuccinctReceipt = bincode::deserialize(&left_receipt_bytes)?;
         let right_receipt_bytes = request.right_receipt.ok_or(malformed_err())?.as_bytes()?;
@@ -435,11 +594,10 @@ impl Server {
     }
 
     fn on_identity_p254(
-        &self,
         mut conn: ConnectionWrapper,
         request: pb::api::IdentityP254Request,
     ) -> Result<()> {
-        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
+        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.try_into()?;
         let receipt_bytes = request.receipt.ok_or(malformed_err())?.as_bytes()?;
         let succinct_receipt: SuccinctReceipt = bincode::deserialize(&receipt_bytes)?;
 
@@ -467,11 +625,46 @@ impl Server {
         Ok(())
     }
 
+    fn on_resolve(mut conn: ConnectionWrapper, request: pb::api::ResolveRequest) -> Result<()> {
+        let opts: ProverOpts = request.opts.ok_or(malformed_err())?.try_into()?;
+        let conditional_receipt_bytes = request
+            .conditional_receipt
+            .ok_or(malformed_err())?
+            .as_bytes()?;
+        let conditional_receipt: SuccinctReceipt =
+            bincode::deserialize(&conditional_receipt_bytes)?;
+        let assumption_receipt_bytes = request
+            .assumption_receipt
+            .ok_or(malformed_err())?
+            .as_bytes()?;
+        let assumption_receipt: SuccinctReceipt = bincode::deserialize(&assumption_receipt_bytes)?;
+
+        let prover = get_prover_server(&opts)?;
+        let receipt = prover.resolve(&conditional_receipt, &assumption_receipt)?;
+
+        let succinct_receipt_pb: pb::core::SuccinctReceipt = receipt.into();
+        let succinct_receipt_bytes = succinct_receipt_pb.encode_to_vec();
+        let asset = pb::api::Asset::from_bytes(
+            &request.receipt_out.ok_or(malformed_err())?,
+            succinct_receipt_bytes.into(),
+            "receipt.zkp",
+        )?;
+
+        let msg = pb::api::ResolveReply {
+            kind: Some(pb::api::resolve_reply::Kind::Ok(pb::api::ResolveResult {
+                receipt: Some(asset),
+            })),
+        };
+        log::debug!("tx: {msg:?}");
+        conn.send(msg)?;
+
+        Ok(())
+    }
+
     fn build_env(
-        &self,
         conn: &ConnectionWrapper,
         request: &pb::api::ExecutorEnv,
-    ) -> Result<ExecutorEnv> {
+    ) -> Result<(ExecutorEnv<'static>, Option<Rc<RefCell<TraceProxy>>>)> {
         let mut env_builder = ExecutorEnv::builder();
         env_builder.env_vars(request.env_vars.clone());
         for fd in request.read_fds.iter() {
@@ -491,8 +684,205 @@ impl Server {
             env_builder.segment_limit_po2(segment_limit_po2);
         }
         env_builder.session_limit(request.session_limit);
-        // TODO: add trace callback proxy
-        env_builder.build()
+        for assumption in request.assumptions.iter() {
+            env_builder.add_assumption(assumption.clone().try_into()?);
+        }
+        let trace = if request.trace {
+            let proxy = Rc::new(RefCell::new(TraceProxy::new(
+                conn.try_clone()?,
+                request.trace_batch_size,
+            )));
+            let callback = proxy.clone();
+            env_builder
+                .trace_callback(move |event| callback.borrow_mut().on_event(event))
+                .trace_cycles(request.trace_cycles);
+            Some(proxy)
+        } else {
+            None
+        };
+        Ok((env_builder.build()?, trace))
+    }
+}
+
+impl Daemon {
+    /// Construct a [Daemon] listening on the specified TCP/IP address which
+    /// handles at most `max_workers` connections concurrently.
+    pub fn bind<A: ToSocketAddrs>(addr: A, max_workers: usize) -> Result<Self> {
+        Ok(Self::new(
+            Listener::Tcp(TcpListener::bind(addr)?),
+            max_workers,
+        ))
+    }
+
+    /// Construct a [Daemon] listening on a Unix domain socket at the specified
+    /// `path` which handles at most `max_workers` connections concurrently.
+    ///
+    /// A socket left at `path` by a previous [Daemon] that was not shut down
+    /// cleanly is replaced. The socket is removed when the [Daemon] is dropped.
+    #[cfg(unix)]
+    pub fn bind_unix<P: AsRef<Path>>(path: P, max_workers: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        remove_stale_socket(&path)?;
+        let listener = UnixListener::bind(&path)?;
+        Ok(Self::new(Listener::Unix(listener, path), max_workers))
+    }
+
+    fn new(listener: Listener, max_workers: usize) -> Self {
+        Self {
+            listener,
+            max_workers: max_workers.max(1),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Return the TCP/IP address that this [Daemon] is listening on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        match self.listener {
+            Listener::Tcp(ref listener) => Ok(listener.local_addr()?),
+            #[cfg(unix)]
+            Listener::Unix(..) => bail!("Daemon is listening on a Unix domain socket"),
+        }
+    }
+
+    /// Return a [DaemonHandle] that can be used to stop this [Daemon] from
+    /// another thread.
+    pub fn handle(&self) -> Result<DaemonHandle> {
+        let addr = match self.listener {
+            Listener::Tcp(ref listener) => ListenAddr::Tcp(listener.local_addr()?),
+            #[cfg(unix)]
+            Listener::Unix(_, ref path) => ListenAddr::Unix(path.clone()),
+        };
+        Ok(DaemonHandle {
+            addr,
+            shutdown: self.shutdown.clone(),
+        })
+    }
+
+    /// Accept and handle connections until [DaemonHandle::shutdown] is called.
+    ///
+    /// Once shut down, no new connections are accepted and this returns after
+    /// the connections in progress have been handled.
+    pub fn run(&self) -> Result<()> {
+        let slots = WorkerSlots::new(self.max_workers);
+        thread::scope(|scope| {
+            loop {
+                let accepted = self.listener.accept();
+                if self.shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+                let (conn, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        log::error!("accept: {err}");
+                        thread::sleep(ACCEPT_BACKOFF);
+                        continue;
+                    }
+                };
+                log::debug!("accepted connection from {peer}");
+                let slot = slots.acquire();
+                scope.spawn(move || {
+                    let _slot = slot;
+                    let conn = ConnectionWrapper::new(conn);
+                    if let Err(err) = Server::handle(conn, AssetPolicy::InlineOnly) {
+                        log::error!("{peer}: {err:?}");
+                    }
+                });
+            }
+            log::debug!("shutting down");
+        });
+        Ok(())
+    }
+}
+
+/// Remove a Unix domain socket at `path` that no server is listening on.
+///
+/// Anything other than a socket is left in place, so that binding fails rather
+/// than deleting an unrelated file.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !metadata.file_type().is_socket() {
+        return Ok(());
+    }
+    if UnixStream::connect(path).is_ok() {
+        bail!("another server is listening on {}", path.display());
+    }
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, ref path) = self.listener {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Listener {
+    fn accept(&self) -> std::io::Result<(Box<dyn Connection + Send>, String)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, addr) = listener.accept()?;
+                Ok((Box::new(TcpConnection::new(stream)), addr.to_string()))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, path) => {
+                let (stream, _) = listener.accept()?;
+                let peer = path.display().to_string();
+                Ok((Box::new(UnixConnection::new(stream)), peer))
+            }
+        }
+    }
+}
+
+impl DaemonHandle {
+    /// Stop the [Daemon] from accepting new connections.
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        // Wake up the listener, which is blocked waiting for a connection.
+        match self.addr {
+            ListenAddr::Tcp(addr) => {
+                let _ = TcpStream::connect(addr);
+            }
+            #[cfg(unix)]
+            ListenAddr::Unix(ref path) => {
+                let _ = UnixStream::connect(path);
+            }
+        }
+    }
+}
+
+impl WorkerSlots {
+    fn new(max: usize) -> Self {
+        Self {
+            busy: Mutex::new(0),
+            idle: Condvar::new(),
+            max,
+        }
+    }
+
+    /// Block until a worker is available and mark it as busy.
+    fn acquire(&self) -> WorkerSlot<'_> {
+        let mut busy = self.busy.lock().unwrap();
+        while *busy >= self.max {
+            busy = self.idle.wait(busy).unwrap();
+        }
+        *busy += 1;
+        WorkerSlot(self)
+    }
+}
+
+impl Drop for WorkerSlot<'_> {
+    fn drop(&mut self) {
+        // Recover from poisoning so that a panicking worker still frees its slot.
+        *self.0.busy.lock().unwrap_or_else(|err| err.into_inner()) -= 1;
+        self.0.idle.notify_one();
     }
 }
 
@@ -512,6 +902,56 @@ impl From<pb::api::GenericError> for IoError {
     }
 }
 
+impl pb::api::ServerRequest {
+    /// Return true if this request refers to any asset by path.
+    fn uses_paths(&self) -> bool {
+        use pb::api::{asset, asset_request, server_request::Kind};
+
+        fn binary(env: &Option<pb::api::ExecutorEnv>) -> Option<&pb::api::Asset> {
+            env.as_ref()?.binary.as_ref()?.asset.as_ref()
+        }
+
+        let (inputs, output) = match &self.kind {
+            None => return false,
+            Some(Kind::Execute(request)) => (vec![binary(&request.env)], &request.segments_out),
+            Some(Kind::Prove(request)) => (vec![binary(&request.env)], &request.receipt_out),
+            Some(Kind::ProveSegment(request)) => {
+                (vec![request.segment.as_ref()], &request.receipt_out)
+            }
+            Some(Kind::Lift(request)) => (vec![request.receipt.as_ref()], &request.receipt_out),
+            Some(Kind::Join(request)) => (
+                vec![request.left_receipt.as_ref(), request.right_receipt.as_ref()],
+                &request.receipt_out,
+            ),
+            Some(Kind::IdentiyP254(request)) => {
+                (vec![request.receipt.as_ref()], &request.receipt_out)
+            }
+            Some(Kind::Resolve(request)) => (
+                vec![
+                    request.conditional_receipt.as_ref(),
+                    request.assumption_receipt.as_ref(),
+                ],
+                &request.receipt_out,
+            ),
+        };
+        let input_is_path = inputs
+            .into_iter()
+            .flatten()
+            .any(|input| matches!(input.kind, Some(asset::Kind::Path(_))));
+        let output_is_path = matches!(
+            output.as_ref().and_then(|output| output.kind.as_ref()),
+            Some(asset_request::Kind::Path(_))
+        );
+        input_is_path || output_is_path
+    }
+}
+
+/// The [VerifierContext] for receipts proven with `opts`, which accepts the
+/// custom recursion control IDs that the client registered.
+fn verifier_context(opts: &ProverOpts) -> VerifierContext {
+    VerifierContext::default().with_recursion_control_ids(opts.recursion_control_ids.clone())
+}
+
 impl pb::api::Asset {
     pub fn from_bytes<P: AsRef<Path>>(
         request: &pb::api::AssetRequest,
@@ -19,6 +19,7 @@ use std::{
 };
 
 use anyhow::Result;
+use bytes::Bytes;
 use risc0_binfmt::{MemoryImage, Program};
 use risc0_zkvm_methods::{
     multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID, MULTI_TEST_PATH,
@@ -29,8 +30,8 @@ use test_log::test;
 
 use super::{Asset, AssetRequest, Binary, ConnectionWrapper, Connector, TcpConnection};
 use crate::{
-    recursion::SuccinctReceipt, ApiClient, ApiServer, ExecutorEnv, InnerReceipt, ProverOpts,
-    Receipt, SegmentReceipt, SessionInfo, VerifierContext,
+    recursion::SuccinctReceipt, ApiClient, ApiDaemon, ApiServer, ExecutorEnv, ExecutorImpl,
+    InnerReceipt, ProverOpts, Receipt, SegmentReceipt, SessionInfo, VerifierContext,
 };
 
 struct TestClientConnector {
@@ -147,6 +148,35 @@ fn execute_elf() {
     TestClient::new().execute(env, binary);
 }
 
+#[test]
+fn execute_stats() {
+    let spec = MultiTestSpec::ShaDigest {
+        data: vec![0u8; 1000],
+    };
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .build()
+        .unwrap();
+    let expected = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap()
+        .stats;
+
+    let env = ExecutorEnv::builder()
+        .write(&spec)
+        .unwrap()
+        .build()
+        .unwrap();
+    let binary = Binary::new_elf_path(MULTI_TEST_PATH);
+    let session = TestClient::new().execute(env, binary);
+    assert_eq!(session.stats, expected);
+    assert_eq!(session.segments.len(), 1);
+    assert_eq!(session.segments[0].stats, expected);
+    assert!(expected.syscalls.contains_key("ecall(sha)"));
+}
+
 #[test]
 fn execute_image() {
     let env = ExecutorEnv::builder()
@@ -160,6 +190,34 @@ fn execute_image() {
     TestClient::new().execute(env, binary);
 }
 
+#[test]
+fn execute_trace() {
+    fn count_events(batch_size: Option<u32>) -> usize {
+        let mut count = 0;
+        {
+            let mut builder = ExecutorEnv::builder();
+            builder
+                .write(&MultiTestSpec::DoNothing)
+                .unwrap()
+                .trace_callback(|_event| {
+                    count += 1;
+                    Ok(())
+                });
+            if let Some(batch_size) = batch_size {
+                builder.trace_batch_size(batch_size);
+            }
+            let env = builder.build().unwrap();
+            let binary = Binary::new_elf_path(MULTI_TEST_PATH);
+            TestClient::new().execute(env, binary);
+        }
+        count
+    }
+
+    let count = count_events(None);
+    assert!(count > 0);
+    assert_eq!(count_events(Some(100)), count);
+}
+
 #[test]
 fn prove_elf() {
     let env = ExecutorEnv::builder()
@@ -173,6 +231,119 @@ fn prove_elf() {
     receipt.verify(MULTI_TEST_ID).unwrap();
 }
 
+#[test]
+#[cfg(not(feature = "cuda"))]
+fn prove_elf_poseidon2() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::DoNothing)
+        .unwrap()
+        .build()
+        .unwrap();
+    let binary = Binary::new_elf_path(MULTI_TEST_PATH);
+    let opts = ProverOpts {
+        hashfn: "poseidon2".to_string(),
+        ..Default::default()
+    };
+    let receipt = TestClient::new().prove(env, opts, binary);
+    for segment in receipt.inner.composite().unwrap().segments.iter() {
+        assert_eq!(segment.hashfn, "poseidon2");
+    }
+    receipt.verify(MULTI_TEST_ID).unwrap();
+}
+
+#[test]
+fn daemon_concurrent_clients() {
+    let daemon = ApiDaemon::bind("127.0.0.1:0", 2).unwrap();
+    let addr = daemon.local_addr().unwrap().to_string();
+    let handle = daemon.handle().unwrap();
+
+    thread::scope(|scope| {
+        let server = scope.spawn(|| daemon.run());
+        let clients: Vec<_> = (0..3)
+            .map(|_| {
+                scope.spawn(|| {
+                    let env = ExecutorEnv::builder()
+                        .write(&MultiTestSpec::DoNothing)
+                        .unwrap()
+                        .build()
+                        .unwrap();
+                    let binary = Binary::new_elf_inline(Bytes::from_static(MULTI_TEST_ELF));
+                    ApiClient::new_tcp(&addr)
+                        .execute(&env, binary, AssetRequest::Inline, |_info, _asset| Ok(()))
+                        .unwrap()
+                })
+            })
+            .collect();
+        for client in clients {
+            client.join().unwrap();
+        }
+
+        handle.shutdown();
+        server.join().unwrap().unwrap();
+    });
+}
+
+#[test]
+fn daemon_rejects_paths() {
+    let daemon = ApiDaemon::bind("127.0.0.1:0", 1).unwrap();
+    let addr = daemon.local_addr().unwrap().to_string();
+    let handle = daemon.handle().unwrap();
+    let work_dir = tempdir().unwrap();
+
+    thread::scope(|scope| {
+        let server = scope.spawn(|| daemon.run());
+        let execute = |binary, segments_out| {
+            let env = ExecutorEnv::builder()
+                .write(&MultiTestSpec::DoNothing)
+                .unwrap()
+                .build()
+                .unwrap();
+            ApiClient::new_tcp(&addr).execute(&env, binary, segments_out, |_info, _asset| Ok(()))
+        };
+        assert!(execute(
+            Binary::new_elf_path(MULTI_TEST_PATH),
+            AssetRequest::Inline
+        )
+        .is_err());
+        assert!(execute(
+            Binary::new_elf_inline(Bytes::from_static(MULTI_TEST_ELF)),
+            AssetRequest::Path(work_dir.path().to_path_buf())
+        )
+        .is_err());
+        assert_eq!(std::fs::read_dir(work_dir.path()).unwrap().count(), 0);
+
+        handle.shutdown();
+        server.join().unwrap().unwrap();
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_unix_socket() {
+    let work_dir = tempdir().unwrap();
+    let path = work_dir.path().join("r0vm.sock");
+    // A socket left behind by a server that did not shut down cleanly.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    let daemon = ApiDaemon::bind_unix(&path, 1).unwrap();
+    let handle = daemon.handle().unwrap();
+
+    thread::scope(|scope| {
+        let server = scope.spawn(|| daemon.run());
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::DoNothing)
+            .unwrap()
+            .build()
+            .unwrap();
+        let binary = Binary::new_elf_inline(Bytes::from_static(MULTI_TEST_ELF));
+        ApiClient::new_unix(&path)
+            .execute(&env, binary, AssetRequest::Inline, |_info, _asset| Ok(()))
+            .unwrap();
+
+        handle.shutdown();
+        server.join().unwrap().unwrap();
+    });
+}
+
 #[test]
 fn prove_segment_elf() {
     let env = ExecutorEnv::builder()
@@ -70,6 +70,8 @@ pub struct ExecutorEnv<'a> {
     pub(crate) slice_io: Rc<RefCell<SliceIoTable<'a>>>,
     pub(crate) input: Vec<u8>,
     pub(crate) trace: Option<Rc<RefCell<TraceCallback<'a>>>>,
+    pub(crate) trace_batch_size: Option<u32>,
+    pub(crate) trace_cycles: bool,
     pub(crate) assumptions: Rc<RefCell<Assumptions>>,
     pub(crate) segment_path: Option<PathBuf>,
 }
@@ -107,10 +109,10 @@ impl<'a> ExecutorEnvBuilder<'a> {
 
         if !inner.input.is_empty() {
             let reader = Cursor::new(inner.input.clone());
-            inner
-                .posix_io
-                .borrow_mut()
-                .with_read_fd(fileno::STDIN, reader);
+            let mut posix_io = inner.posix_io.borrow_mut();
+            posix_io.with_read_fd(fileno::STDIN, reader);
+            // Written input is not a custom stdin provided by the host.
+            posix_io.custom_fds.remove(&fileno::STDIN);
         }
 
         Ok(inner)
@@ -323,6 +325,27 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set the number of [TraceEvent]s sent to the host in each message when
+    /// executing with an external prover.
+    ///
+    /// Larger batches reduce the overhead of tracing at the cost of delivering
+    /// events to the trace callback later. By default, each event is sent on
+    /// its own.
+    pub fn trace_batch_size(&mut self, size: u32) -> &mut Self {
+        self.inner.trace_batch_size = Some(size);
+        self
+    }
+
+    /// Also send [TraceEvent::PageIn], [TraceEvent::PageOut] and
+    /// [TraceEvent::Syscall] to the trace callback, which report the cycles
+    /// spent paging and in syscalls.
+    ///
+    /// These events are not sent by default.
+    pub fn trace_cycles(&mut self, enable: bool) -> &mut Self {
+        self.inner.trace_cycles = enable;
+        self
+    }
+
     /// Set the path where segments will be stored.
     pub fn segment_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         self.inner.segment_path = Some(path.as_ref().to_path_buf());
@@ -42,6 +42,40 @@ pub enum TraceEvent {
         /// Value of word that's been written
         value: u32,
     },
+
+    /// A page has been read into memory
+    ///
+    /// Only sent if enabled with
+    /// [ExecutorEnvBuilder::trace_cycles](crate::ExecutorEnvBuilder::trace_cycles).
+    PageIn {
+        /// Index of the page that's been read
+        page_idx: u32,
+        /// Number of cycles spent reading the page
+        cycles: u32,
+    },
+
+    /// A page has been marked dirty, to be written back from memory at the
+    /// end of the segment
+    ///
+    /// Only sent if enabled with
+    /// [ExecutorEnvBuilder::trace_cycles](crate::ExecutorEnvBuilder::trace_cycles).
+    PageOut {
+        /// Index of the page that will be written
+        page_idx: u32,
+        /// Number of cycles that will be spent writing the page
+        cycles: u32,
+    },
+
+    /// The guest has made a syscall
+    ///
+    /// Only sent if enabled with
+    /// [ExecutorEnvBuilder::trace_cycles](crate::ExecutorEnvBuilder::trace_cycles).
+    Syscall {
+        /// Name of the syscall
+        name: String,
+        /// Number of cycles spent in the syscall
+        cycles: u32,
+    },
 }
 
 impl std::fmt::Debug for TraceEvent {
@@ -52,6 +86,9 @@ impl std::fmt::Debug for TraceEvent {
             }
             Self::RegisterSet { idx, value } => write!(f, "RegisterSet({idx}, 0x{value:08X})"),
             Self::MemorySet { addr, value } => write!(f, "MemorySet(0x{addr:08X}, 0x{value:08X})"),
+            Self::PageIn { page_idx, cycles } => write!(f, "PageIn(0x{page_idx:08X}, {cycles})"),
+            Self::PageOut { page_idx, cycles } => write!(f, "PageOut(0x{page_idx:08X}, {cycles})"),
+            Self::Syscall { name, cycles } => write!(f, "Syscall({name}, {cycles})"),
         }
     }
 }
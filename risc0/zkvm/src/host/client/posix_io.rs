@@ -14,7 +14,7 @@
 
 use std::{
     cell::RefCell,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     io::{stderr, stdin, stdout, BufRead, BufReader, Write},
     rc::Rc,
 };
@@ -26,6 +26,9 @@ use risc0_zkvm_platform::fileno;
 pub struct PosixIo<'a> {
     pub(crate) read_fds: BTreeMap<u32, Rc<RefCell<dyn BufRead + 'a>>>,
     pub(crate) write_fds: BTreeMap<u32, Rc<RefCell<dyn Write + 'a>>>,
+    /// File descriptors that were added or replaced by the host, as opposed
+    /// to the process stdin, stdout and stderr installed by default.
+    pub(crate) custom_fds: BTreeSet<u32>,
 }
 
 impl<'a> Default for PosixIo<'a> {
@@ -33,10 +36,12 @@ impl<'a> Default for PosixIo<'a> {
         let mut new = Self {
             read_fds: Default::default(),
             write_fds: Default::default(),
+            custom_fds: Default::default(),
         };
         new.with_read_fd(fileno::STDIN, BufReader::new(stdin()))
             .with_write_fd(fileno::STDOUT, stdout())
             .with_write_fd(fileno::STDERR, stderr());
+        new.custom_fds.clear();
         new
     }
 }
@@ -44,11 +49,13 @@ impl<'a> Default for PosixIo<'a> {
 impl<'a> PosixIo<'a> {
     pub fn with_read_fd(&mut self, fd: u32, reader: impl BufRead + 'a) -> &mut Self {
         self.read_fds.insert(fd, Rc::new(RefCell::new(reader)));
+        self.custom_fds.insert(fd);
         self
     }
 
     pub fn with_write_fd(&mut self, fd: u32, writer: impl Write + 'a) -> &mut Self {
         self.write_fds.insert(fd, Rc::new(RefCell::new(writer)));
+        self.custom_fds.insert(fd);
         self
     }
 }
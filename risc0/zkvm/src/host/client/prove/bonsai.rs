@@ -12,21 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, ensure, Result};
-use bonsai_sdk::alpha::Client;
+use bonsai_sdk::alpha::{responses::SessionStatus, Client};
 use risc0_binfmt::MemoryImage;
 
 use super::Prover;
 use crate::{sha::Digestible, ExecutorEnv, ProverOpts, Receipt, VerifierContext};
 
+/// The default interval between two session status requests.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 /// An implementation of a [Prover] that runs proof workloads via Bonsai.
 ///
 /// Requires `BONSAI_API_URL` and `BONSAI_API_KEY` environment variables to
 /// submit proving sessions to Bonsai.
+///
+/// Only the guest input of the [ExecutorEnv] is sent to Bonsai. Any other
+/// setting that would change how the guest runs, such as environment
+/// variables, arguments, segment and session limits, assumptions, slice I/O
+/// channels, custom file descriptors and trace callbacks, is rejected. Guest
+/// writes to stdout and stderr are not returned.
 pub struct BonsaiProver {
     name: String,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
 }
 
 impl BonsaiProver {
@@ -34,8 +45,74 @@ impl BonsaiProver {
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: None,
         }
     }
+
+    /// Set the interval between two session status requests.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Set the maximum time to wait for a session to complete.
+    ///
+    /// By default, [BonsaiProver::prove] waits until the session completes. A
+    /// session that is still running at the deadline is cancelled.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Reject the settings of the [ExecutorEnv] that cannot be sent to Bonsai.
+fn check_env(env: &ExecutorEnv<'_>) -> Result<()> {
+    let slice_io = env.slice_io.borrow();
+    if !slice_io.inner.is_empty() {
+        let channels: Vec<_> = slice_io.inner.keys().cloned().collect();
+        bail!("Bonsai does not support slice I/O channels: {channels:?}");
+    }
+
+    // This includes a custom stdin, stdout or stderr, whose data would
+    // otherwise be silently dropped.
+    let custom_fds = &env.posix_io.borrow().custom_fds;
+    ensure!(
+        custom_fds.is_empty(),
+        "Bonsai does not support host file descriptors: {custom_fds:?}"
+    );
+
+    ensure!(
+        env.trace.is_none(),
+        "Bonsai does not support trace callbacks"
+    );
+
+    ensure!(
+        env.env_vars.is_empty(),
+        "Bonsai does not support environment variables: {:?}",
+        env.env_vars.keys().collect::<Vec<_>>()
+    );
+    ensure!(
+        env.args.is_empty(),
+        "Bonsai does not support guest arguments: {:?}",
+        env.args
+    );
+    ensure!(
+        env.segment_limit_po2.is_none(),
+        "Bonsai does not support a custom segment limit"
+    );
+    ensure!(
+        env.session_limit.is_none(),
+        "Bonsai does not support a session limit"
+    );
+    ensure!(
+        env.assumptions.borrow().cached.is_empty(),
+        "Bonsai does not support assumptions"
+    );
+
+    Ok(())
 }
 
 impl Prover for BonsaiProver {
@@ -50,6 +127,9 @@ impl Prover for BonsaiProver {
         opts: &ProverOpts,
         image: MemoryImage,
     ) -> Result<Receipt> {
+        // Reject unsupported settings before uploading anything.
+        check_env(&env)?;
+
         let client = Client::from_env(crate::VERSION)?;
 
         // upload the image
@@ -69,15 +149,28 @@ impl Prover for BonsaiProver {
         let session = client.create_session(image_id_hex, input_id)?;
         log::debug!("Bonsai proving SessionID: {}", session.uuid);
 
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
         loop {
             // The session has already been started in the executor. Poll bonsai to check if
             // the proof request succeeded.
             let res = session.status(&client)?;
-            if res.status == "RUNNING" {
-                std::thread::sleep(Duration::from_secs(5));
+            if res.session_status() == SessionStatus::Running {
+                let mut interval = self.poll_interval;
+                if let Some(deadline) = deadline {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        // Stop the session rather than leave it running on Bonsai.
+                        if let Err(err) = session.cancel(&client) {
+                            log::warn!("Failed to cancel Bonsai session {}: {err}", session.uuid);
+                        }
+                        bail!("Bonsai session {} did not complete in time", session.uuid);
+                    }
+                    interval = interval.min(remaining);
+                }
+                std::thread::sleep(interval);
                 continue;
             }
-            if res.status == "SUCCEEDED" {
+            if res.session_status() == SessionStatus::Succeeded {
                 // Download the receipt, containing the output
                 let receipt_url = res
                     .receipt_url
@@ -99,7 +192,11 @@ impl Prover for BonsaiProver {
                 }
                 return Ok(receipt);
             } else {
-                bail!("Bonsai prover workflow exited: {}", res.status);
+                bail!(
+                    "Bonsai prover workflow exited: {} {}",
+                    res.status,
+                    res.error_msg.unwrap_or_default()
+                );
             }
         }
     }
@@ -62,12 +62,14 @@ impl Executor for LocalProver {
             segments.push(SegmentInfo {
                 po2: segment.po2,
                 cycles: segment.cycles,
+                stats: segment.stats,
             })
         }
         Ok(SessionInfo {
             segments,
             journal: session.journal.unwrap_or_default().into(),
             exit_code: session.exit_code,
+            stats: session.stats,
         })
     }
 }
@@ -21,6 +21,7 @@ use std::{path::PathBuf, rc::Rc};
 
 use anyhow::Result;
 use risc0_binfmt::{MemoryImage, Program};
+use risc0_zkp::core::digest::Digest;
 use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE};
 use serde::{Deserialize, Serialize};
 
@@ -128,25 +129,122 @@ pub trait Executor {
     }
 }
 
+/// The kind of [Receipt] a [Prover] should produce.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiptKind {
+    /// A [crate::CompositeReceipt] holding one [crate::SegmentReceipt] per
+    /// segment. This is the fastest to produce, but its size grows with the
+    /// length of the execution.
+    #[default]
+    Composite,
+
+    /// A single [crate::SuccinctReceipt], produced by lifting each segment
+    /// through the recursion circuit and joining the results. Its size does not
+    /// depend on the length of the execution. Requires the `"poseidon"` hash
+    /// function.
+    Succinct,
+}
+
 /// Options to configure a [Prover].
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProverOpts {
-    /// The hash function to use.
+    /// The hash function to use: one of `"sha-256"`, `"poseidon"` or
+    /// `"poseidon2"`.
     pub hashfn: String,
     /// When false, only prove execution sessions that end in a successful
     /// [crate::ExitCode] (i.e. `Halted(0)` or `Paused(0)`).
     /// When set to true, any completed execution session will be proven, including indicated
     /// errors (e.g. `Halted(1)`) and sessions ending in `Fault`.
     pub prove_guest_errors: bool,
+    /// The number of segments to prove concurrently, each on its own worker
+    /// thread with its own prover. Values of 0 and 1 both prove segments one
+    /// at a time on the calling thread.
+    ///
+    /// With more than one worker, a local prover also starts proving segments
+    /// while the executor is still running, instead of waiting for the whole
+    /// session to be executed first.
+    pub segment_workers: usize,
+    /// An upper bound, in bytes, on the estimated memory used by segments
+    /// being proven concurrently. When `None`, only `segment_workers` limits
+    /// how many segments are in flight.
+    pub segment_memory_limit: Option<usize>,
+    /// The kind of [Receipt] to produce.
+    pub receipt_kind: ReceiptKind,
+    /// Control IDs of custom recursion programs whose receipts may be lifted,
+    /// joined or resolved by the prover, in addition to the built-in ones.
+    ///
+    /// This must be the same list, in the same order, that is given to the
+    /// [VerifierContext] used to verify the resulting receipts.
+    #[serde(default)]
+    pub recursion_control_ids: Vec<Digest>,
 }
 
 impl Default for ProverOpts {
-    /// Return [ProverOpts] with the SHA-256 hash function and
-    /// `prove_guest_errors` set to false.
+    /// Return [ProverOpts] with the SHA-256 hash function,
+    /// `prove_guest_errors` set to false, segments proven sequentially and a
+    /// [ReceiptKind::Composite] receipt.
     fn default() -> Self {
         Self {
             hashfn: "poseidon".to_string(),
             prove_guest_errors: false,
+            segment_workers: 1,
+            segment_memory_limit: None,
+            receipt_kind: ReceiptKind::Composite,
+            recursion_control_ids: Vec::new(),
+        }
+    }
+}
+
+impl ProverOpts {
+    /// Set the hash function to use: one of `"sha-256"`, `"poseidon"` or
+    /// `"poseidon2"`.
+    pub fn with_hashfn(self, hashfn: impl Into<String>) -> Self {
+        Self {
+            hashfn: hashfn.into(),
+            ..self
+        }
+    }
+
+    /// Set whether to prove sessions that do not end in a successful
+    /// [crate::ExitCode].
+    pub fn with_prove_guest_errors(self, prove_guest_errors: bool) -> Self {
+        Self {
+            prove_guest_errors,
+            ..self
+        }
+    }
+
+    /// Set the number of segments to prove concurrently.
+    pub fn with_segment_workers(self, segment_workers: usize) -> Self {
+        Self {
+            segment_workers,
+            ..self
+        }
+    }
+
+    /// Set an upper bound, in bytes, on the estimated memory used by segments
+    /// being proven concurrently.
+    pub fn with_segment_memory_limit(self, segment_memory_limit: Option<usize>) -> Self {
+        Self {
+            segment_memory_limit,
+            ..self
+        }
+    }
+
+    /// Set the kind of [Receipt] to produce.
+    pub fn with_receipt_kind(self, receipt_kind: ReceiptKind) -> Self {
+        Self {
+            receipt_kind,
+            ..self
+        }
+    }
+
+    /// Set the control IDs of custom recursion programs accepted by the
+    /// prover.
+    pub fn with_recursion_control_ids(self, recursion_control_ids: Vec<Digest>) -> Self {
+        Self {
+            recursion_control_ids,
+            ..self
         }
     }
 }
@@ -25,8 +25,8 @@ use risc0_zkp::{
     core::{
         digest::Digest,
         hash::{
-            blake2b::Blake2bCpuHashSuite, poseidon::PoseidonHashSuite, sha::Sha256HashSuite,
-            HashSuite,
+            blake2b::Blake2bCpuHashSuite, poseidon::PoseidonHashSuite,
+            poseidon2::Poseidon2HashSuite, sha::Sha256HashSuite, HashSuite,
         },
     },
     layout::Buffer,
@@ -35,7 +35,9 @@ use risc0_zkp::{
 use risc0_zkvm_platform::WORD_SIZE;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use super::control_id::{BLAKE2B_CONTROL_ID, POSEIDON_CONTROL_ID, SHA256_CONTROL_ID};
+use super::control_id::{
+    BLAKE2B_CONTROL_ID, POSEIDON2_CONTROL_ID, POSEIDON_CONTROL_ID, SHA256_CONTROL_ID,
+};
 // Make succinct receipt available through this `receipt` module.
 pub use super::recursion::SuccinctReceipt;
 use crate::{
@@ -576,6 +578,7 @@ impl SegmentReceipt {
         let check_code = |_, control_id: &Digest| -> Result<(), VerificationError> {
             POSEIDON_CONTROL_ID
                 .into_iter()
+                .chain(POSEIDON2_CONTROL_ID)
                 .chain(SHA256_CONTROL_ID)
                 .chain(BLAKE2B_CONTROL_ID)
                 .find(|x| Digest::from_hex(x).unwrap() == *control_id)
@@ -663,6 +666,24 @@ impl From<ReceiptMetadata> for Assumption {
 pub struct VerifierContext {
     /// A registry of hash functions to be used by the verification process.
     pub suites: BTreeMap<String, HashSuite<BabyBear>>,
+
+    /// Control IDs of custom recursion programs to accept in succinct receipts,
+    /// in addition to the built-in ones.
+    pub(crate) recursion_control_ids: Vec<Digest>,
+}
+
+impl VerifierContext {
+    /// Accept succinct receipts produced with the given control IDs of custom
+    /// recursion programs, in addition to the built-in ones.
+    ///
+    /// This must be the same list, in the same order, that was given to the
+    /// recursion prover.
+    pub fn with_recursion_control_ids(self, recursion_control_ids: Vec<Digest>) -> Self {
+        Self {
+            recursion_control_ids,
+            ..self
+        }
+    }
 }
 
 fn decode_system_state_from_io(
@@ -727,8 +748,10 @@ impl Default for VerifierContext {
             suites: BTreeMap::from([
                 ("blake2b".into(), Blake2bCpuHashSuite::new_suite()),
                 ("poseidon".into(), PoseidonHashSuite::new_suite()),
+                ("poseidon2".into(), Poseidon2HashSuite::new_suite()),
                 ("sha-256".into(), Sha256HashSuite::new_suite()),
             ]),
+            recursion_control_ids: Vec::new(),
         }
     }
 }
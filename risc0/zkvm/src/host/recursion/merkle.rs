@@ -12,26 +12,58 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{bail, Result};
+//! Merkle trees of control IDs, used to commit to the set of recursion
+//! programs whose seals a recursion program will accept.
+
+// Proofs of inclusion are only needed by the prover.
+#![cfg_attr(not(feature = "prove"), allow(dead_code))]
+
+use alloc::vec::Vec;
+
+use anyhow::{bail, ensure, Result};
 use risc0_core::field::baby_bear::BabyBear;
 use risc0_zkp::core::{
     digest::{Digest, DIGEST_WORDS},
     hash::HashFn,
 };
+use serde::{Deserialize, Serialize};
 
 static EMPTY_DIGEST: Digest = Digest::new([0; DIGEST_WORDS]);
 
+/// A Merkle tree of fixed depth over a list of leaves, padded with zero
+/// digests.
 pub struct MerkleGroup {
+    /// The depth of the tree, which holds up to `2^depth` leaves.
     pub depth: usize,
+
+    /// The leaves of the tree, in order.
     pub leaves: Vec<Digest>,
 }
 
+/// A proof that a leaf is included in a [MerkleGroup].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MerkleProof {
+    /// The index of the leaf in the tree.
     pub index: usize,
+
+    /// The sibling digests on the path from the leaf to the root, starting
+    /// at the leaf.
     pub digests: Vec<Digest>,
 }
 
 impl MerkleGroup {
+    /// Construct a tree of the given depth, failing if there are more leaves
+    /// than fit in it.
+    pub fn new(depth: usize, leaves: Vec<Digest>) -> Result<Self> {
+        ensure!(
+            leaves.len() <= 1 << depth,
+            "{} leaves do not fit in a merkle group of depth {depth}",
+            leaves.len()
+        );
+        Ok(Self { depth, leaves })
+    }
+
+    /// Compute the root of the tree.
     pub fn calc_root(&self, hashfn: &dyn HashFn<BabyBear>) -> Digest {
         self.calc_range_root(0, 1 << self.depth, hashfn)
     }
@@ -42,7 +74,7 @@ impl MerkleGroup {
 
     fn calc_range_root(&self, start: usize, end: usize, hashfn: &dyn HashFn<BabyBear>) -> Digest {
         assert!(start < end);
-        let res = if start + 1 == end {
+        if start + 1 == end {
             *self.leaf_or_empty(start)
         } else {
             let mid = (start + end) / 2;
@@ -51,10 +83,11 @@ impl MerkleGroup {
             let left = self.calc_range_root(start, mid, hashfn);
             let right = self.calc_range_root(mid, end, hashfn);
             *hashfn.hash_pair(&left, &right)
-        };
-        res
+        }
     }
 
+    /// Return the proof of inclusion of the given control ID, which must be
+    /// one of the leaves.
     pub fn get_proof(
         &self,
         control_id: &Digest,
@@ -69,6 +102,8 @@ impl MerkleGroup {
         })
     }
 
+    /// Return the sibling digests on the path from the leaf at `index` to the
+    /// root.
     pub fn get_proof_by_index(
         &self,
         mut index: usize,
@@ -94,11 +129,35 @@ impl MerkleGroup {
     }
 }
 
+impl MerkleProof {
+    /// Compute the root of the tree that this proof commits `leaf` to.
+    pub fn root(&self, leaf: &Digest, hashfn: &dyn HashFn<BabyBear>) -> Digest {
+        let mut index = self.index;
+        let mut cur = *leaf;
+        for sibling in self.digests.iter() {
+            cur = if index & 1 == 0 {
+                *hashfn.hash_pair(&cur, sibling)
+            } else {
+                *hashfn.hash_pair(sibling, &cur)
+            };
+            index >>= 1;
+        }
+        cur
+    }
+
+    /// Check that this proof commits `leaf` to a tree with the given `root`.
+    pub fn verify(&self, leaf: &Digest, root: &Digest, hashfn: &dyn HashFn<BabyBear>) -> bool {
+        self.root(leaf, hashfn) == *root
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use hex::FromHex;
     use risc0_zkp::core::hash::poseidon::PoseidonHashSuite;
 
     use super::*;
+    use crate::{host::recursion::allowed_tree, ALLOWED_IDS_ROOT};
 
     fn shared_levels(a: &[Digest], b: &[Digest]) -> usize {
         a.iter()
@@ -136,4 +195,33 @@ mod tests {
         assert_eq!(shared_levels(&proof2, &proof3), 2);
         assert_eq!(shared_levels(&proof1, &proof3), 2);
     }
+
+    #[test]
+    fn proof_root() {
+        let leaves: Vec<Digest> = (0..5u32).map(|i| Digest::new([i; DIGEST_WORDS])).collect();
+
+        let suite = PoseidonHashSuite::new_suite();
+        let hashfn = suite.hashfn.as_ref();
+
+        let grp = MerkleGroup::new(3, leaves.clone()).unwrap();
+        let root = grp.calc_root(hashfn);
+        for leaf in leaves.iter() {
+            let proof = grp.get_proof(leaf, hashfn).unwrap();
+            assert_eq!(proof.digests.len(), grp.depth);
+            assert!(proof.verify(leaf, &root, hashfn));
+        }
+
+        let proof = grp.get_proof(&leaves[1], hashfn).unwrap();
+        assert!(!proof.verify(&leaves[2], &root, hashfn));
+        let missing = Digest::new([7; DIGEST_WORDS]);
+        assert!(grp.get_proof(&missing, hashfn).is_err());
+        assert!(MerkleGroup::new(2, leaves).is_err());
+    }
+
+    #[test]
+    fn allowed_ids_root() {
+        let suite = PoseidonHashSuite::new_suite();
+        let root = allowed_tree(&[]).unwrap().calc_root(suite.hashfn.as_ref());
+        assert_eq!(root, Digest::from_hex(ALLOWED_IDS_ROOT).unwrap());
+    }
 }
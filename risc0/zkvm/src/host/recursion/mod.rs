@@ -17,6 +17,7 @@
 //! This module implements receipts that are generated from the recursion
 //! circuit as well as verification functions for each type of receipt.
 
+pub mod merkle;
 #[cfg(feature = "prove")]
 mod prove;
 mod receipt;
@@ -27,7 +28,12 @@ mod tests;
 pub use risc0_circuit_recursion::control_id::ALLOWED_IDS_ROOT;
 
 #[cfg(feature = "prove")]
-pub use self::prove::{identity_p254, join, lift, poseidon_hal_pair, Program, Prover, ProverOpts};
-pub use self::receipt::{valid_control_ids, SuccinctReceipt};
+pub use self::prove::{
+    identity_p254, identity_with_hal, join, join_with_hal, lift, lift_with_hal,
+    poseidon254_hal_pair, poseidon_hal_pair, resolve, resolve_with_hal, Program, Prover,
+    ProverOpts,
+};
+pub use self::receipt::{allowed_tree, valid_control_ids, SuccinctReceipt};
 
-const CIRCUIT: risc0_circuit_recursion::CircuitImpl = risc0_circuit_recursion::CircuitImpl::new();
+pub(crate) const CIRCUIT: risc0_circuit_recursion::CircuitImpl =
+    risc0_circuit_recursion::CircuitImpl::new();
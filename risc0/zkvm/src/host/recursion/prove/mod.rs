@@ -13,7 +13,6 @@
 // limitations under the License.
 
 mod exec;
-pub mod merkle;
 mod plonk;
 pub mod preflight;
 mod program;
@@ -21,9 +20,8 @@ pub mod zkr;
 
 use std::{collections::VecDeque, mem::take, rc::Rc};
 
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
 use hex::FromHex;
-use merkle::MerkleGroup;
 use risc0_circuit_recursion::{
     cpu::CpuCircuitHal, CircuitImpl, REGISTER_GROUP_ACCUM, REGISTER_GROUP_CODE, REGISTER_GROUP_DATA,
 };
@@ -45,14 +43,15 @@ use risc0_zkp::{
 use serde::{Deserialize, Serialize};
 
 pub use self::program::Program;
-use super::CIRCUIT;
+use super::{merkle::MerkleGroup, CIRCUIT};
 use crate::{
-    recursion::{valid_control_ids, SuccinctReceipt},
+    receipt_metadata::Assumptions,
+    recursion::{allowed_tree, SuccinctReceipt},
+    sha::Digestible,
     HalPair, ReceiptMetadata, SegmentReceipt, POSEIDON_CONTROL_ID,
 };
 
 const RECURSION_PO2: usize = 18;
-const ALLOWED_CODE_MERKLE_DEPTH: usize = 8;
 // TODO: Automatically generate this from the circuit somehow without
 // messing up bootstrap dependencies.
 const RECURSION_CODE_SIZE: usize = 21;
@@ -68,25 +67,98 @@ pub struct RecursionReceipt {
 
 /// TODO
 pub fn lift(receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
-    let mut prover = Prover::new_lift(&receipt.seal, ProverOpts::default())?;
-    let receipt = prover.run()?;
-    let mut out_stream = VecDeque::<u32>::new();
-    out_stream.extend(receipt.output.iter());
-    let meta = ReceiptMetadata::decode(&mut out_stream)?;
-    Ok(SuccinctReceipt {
-        seal: receipt.seal,
-        control_id: receipt.control_id,
-        meta,
-    })
+    lift_with_hal(receipt, ProverOpts::default(), &poseidon_hal_pair())
+}
+
+/// Run the lift program on a [SegmentReceipt] with the given [ProverOpts],
+/// proving on the given [HalPair].
+pub fn lift_with_hal<H, C>(
+    receipt: &SegmentReceipt,
+    opts: ProverOpts,
+    hal_pair: &HalPair<H, C>,
+) -> Result<SuccinctReceipt>
+where
+    H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
+    C: CircuitHal<H>,
+{
+    check_hash_suite(&opts, hal_pair)?;
+    Prover::new_lift(&receipt.seal, opts)?.run_succinct_with_hal(hal_pair)
 }
 
 /// TODO
 pub fn join(a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-    let mut prover = Prover::new_join(a, b, ProverOpts::default())?;
-    let receipt = prover.run()?;
+    join_with_hal(a, b, ProverOpts::default(), &poseidon_hal_pair())
+}
+
+/// Run the join program on two [SuccinctReceipt]s with the given
+/// [ProverOpts], proving on the given [HalPair].
+pub fn join_with_hal<H, C>(
+    a: &SuccinctReceipt,
+    b: &SuccinctReceipt,
+    opts: ProverOpts,
+    hal_pair: &HalPair<H, C>,
+) -> Result<SuccinctReceipt>
+where
+    H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
+    C: CircuitHal<H>,
+{
+    check_hash_suite(&opts, hal_pair)?;
+    Prover::new_join(a, b, opts)?.run_succinct_with_hal(hal_pair)
+}
+
+/// Resolve the head assumption of a conditional [SuccinctReceipt].
+///
+/// The `conditional` receipt must have an unpruned output and assumptions
+/// list, and `assumption` must be a receipt for the first assumption in that
+/// list. The returned receipt has the same metadata as `conditional` with the
+/// resolved assumption removed.
+pub fn resolve(
+    conditional: &SuccinctReceipt,
+    assumption: &SuccinctReceipt,
+) -> Result<SuccinctReceipt> {
+    resolve_with_hal(
+        conditional,
+        assumption,
+        ProverOpts::default(),
+        &poseidon_hal_pair(),
+    )
+}
+
+/// Run the resolve program, as in [resolve], with the given [ProverOpts],
+/// proving on the given [HalPair].
+pub fn resolve_with_hal<H, C>(
+    conditional: &SuccinctReceipt,
+    assumption: &SuccinctReceipt,
+    opts: ProverOpts,
+    hal_pair: &HalPair<H, C>,
+) -> Result<SuccinctReceipt>
+where
+    H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
+    C: CircuitHal<H>,
+{
+    check_hash_suite(&opts, hal_pair)?;
+    let mut meta = conditional.meta.clone();
+    let output = meta
+        .output
+        .as_value_mut()?
+        .as_mut()
+        .ok_or_else(|| anyhow!("conditional receipt has no output to resolve"))?;
+    output
+        .assumptions
+        .as_value_mut()?
+        .resolve(&assumption.meta.digest())?;
+
+    let mut prover = Prover::new_resolve(conditional, assumption, opts)?;
+    let receipt = prover.run_with_hal(hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref())?;
     let mut out_stream = VecDeque::<u32>::new();
     out_stream.extend(receipt.output.iter());
-    let meta = ReceiptMetadata::decode(&mut out_stream)?;
+    let decoded = ReceiptMetadata::decode(&mut out_stream)?;
+    ensure!(
+        decoded.digest() == meta.digest(),
+        "resolve program produced unexpected metadata: expected {}, got {}",
+        meta.digest(),
+        decoded.digest()
+    );
     Ok(SuccinctReceipt {
         seal: receipt.seal,
         control_id: receipt.control_id,
@@ -96,10 +168,44 @@ pub fn join(a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt>
 
 /// TODO
 pub fn identity_p254(a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-    let hal_pair = poseidon254_hal_pair();
-    let (hal, circuit_hal) = (hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref());
-    let mut prover = Prover::new_identity(a, ProverOpts::default())?;
-    let receipt = prover.run_with_hal(hal, circuit_hal)?;
+    identity_with_hal(a, ProverOpts::default(), &poseidon254_hal_pair())
+}
+
+/// Run the identity program on a [SuccinctReceipt] with the given
+/// [ProverOpts], proving on the given [HalPair].
+///
+/// The hash function of the [HalPair] is that of the resulting seal, so
+/// [identity_p254] uses Poseidon over the 254-bit field to prepare a receipt
+/// for conversion to a SNARK.
+pub fn identity_with_hal<H, C>(
+    a: &SuccinctReceipt,
+    opts: ProverOpts,
+    hal_pair: &HalPair<H, C>,
+) -> Result<SuccinctReceipt>
+where
+    H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
+    C: CircuitHal<H>,
+{
+    Prover::new_identity(a, opts)?.run_succinct_with_hal(hal_pair)
+}
+
+/// Check that the [HalPair] proves with the hash suite of the [ProverOpts], so
+/// that the seal it produces commits to the allowed tree it was given.
+fn check_hash_suite<H, C>(opts: &ProverOpts, hal_pair: &HalPair<H, C>) -> Result<()>
+where
+    H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
+    C: CircuitHal<H>,
+{
+    let hal_suite = &hal_pair.hal.get_hash_suite().name;
+    ensure!(
+        *hal_suite == opts.suite.name,
+        "HAL hash suite {hal_suite} does not match the prover's hash suite {}",
+        opts.suite.name
+    );
+    Ok(())
+}
+
+fn decode_succinct(receipt: RecursionReceipt) -> Result<SuccinctReceipt> {
     let mut out_stream = VecDeque::<u32>::new();
     out_stream.extend(receipt.output.iter());
     let meta = ReceiptMetadata::decode(&mut out_stream)?;
@@ -111,9 +217,11 @@ pub fn identity_p254(a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
 }
 
 /// Options available to modify the prover's behavior.
+#[derive(Clone)]
 pub struct ProverOpts {
     pub(crate) skip_seal: bool,
     suite: HashSuite<BabyBear>,
+    control_ids: Vec<Digest>,
 }
 
 impl ProverOpts {
@@ -124,6 +232,28 @@ impl ProverOpts {
     pub fn with_skip_seal(self, skip_seal: bool) -> Self {
         Self { skip_seal, ..self }
     }
+
+    /// Set the hash suite used to read the seals given to the recursion
+    /// programs and to build the allowed tree. Defaults to Poseidon.
+    ///
+    /// [lift_with_hal], [join_with_hal] and [resolve_with_hal] require the
+    /// [HalPair] to prove with the same hash suite.
+    pub fn with_hash_suite(self, suite: HashSuite<BabyBear>) -> Self {
+        Self { suite, ..self }
+    }
+
+    /// Register the control IDs of custom recursion programs, so that their
+    /// seals are accepted alongside those of the built-in programs.
+    ///
+    /// These IDs are appended to the allowed tree committed to by every
+    /// receipt this prover produces, so the same list must be given to the
+    /// [crate::VerifierContext] used to verify them.
+    pub fn with_control_ids(self, control_ids: Vec<Digest>) -> Self {
+        Self {
+            control_ids,
+            ..self
+        }
+    }
 }
 
 impl Default for ProverOpts {
@@ -131,6 +261,7 @@ impl Default for ProverOpts {
         ProverOpts {
             skip_seal: false,
             suite: PoseidonHashSuite::new_suite(),
+            control_ids: Vec::new(),
         }
     }
 }
@@ -316,12 +447,38 @@ impl Prover {
         }
     }
 
-    /// TODO
+    /// Build the tree of the control IDs of the built-in recursion programs.
     pub fn make_allowed_tree() -> MerkleGroup {
-        MerkleGroup {
-            depth: ALLOWED_CODE_MERKLE_DEPTH,
-            leaves: valid_control_ids(),
-        }
+        allowed_tree(&[]).unwrap()
+    }
+
+    /// Build the tree of control IDs whose seals this prover accepts, including
+    /// those registered with [ProverOpts::with_control_ids].
+    pub fn allowed_tree(&self) -> Result<MerkleGroup> {
+        allowed_tree(&self.opts.control_ids)
+    }
+
+    /// Construct a prover for a custom recursion program, such as an
+    /// application-specific aggregation circuit loaded with
+    /// [Program::from_encoded].
+    ///
+    /// Like the built-in programs, the program first reads the root of the
+    /// allowed tree, which is added here, and must write it to its output
+    /// before the digest of its [ReceiptMetadata]. Further inputs, such as the
+    /// receipts to aggregate, are added with [Prover::add_succinct_receipt]
+    /// and [Prover::add_input_digest]. To verify the resulting receipts, the
+    /// control ID of the program, as computed by [Program::compute_control_id]
+    /// with the hash suite of `opts`, must be registered with
+    /// [ProverOpts::with_control_ids] and
+    /// [crate::VerifierContext::with_recursion_control_ids].
+    pub fn new_custom(program: Program, opts: ProverOpts) -> Result<Self> {
+        let control_id = program.compute_control_id(opts.suite.clone());
+        let hashfn = opts.suite.hashfn.as_ref();
+        let merkle_root = allowed_tree(&opts.control_ids)?.calc_root(hashfn);
+
+        let mut prover = Prover::new(program, control_id, opts);
+        prover.add_input_digest(&merkle_root);
+        Ok(prover)
     }
 
     /// TODO
@@ -364,7 +521,7 @@ impl Prover {
     /// TODO
     pub fn new_lift(seal: &[u32], opts: ProverOpts) -> Result<Self> {
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = allowed_tree(&opts.control_ids)?;
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         let mut iop = ReadIOP::new(seal, opts.suite.rng.as_ref());
@@ -383,12 +540,19 @@ impl Prover {
         Ok(prover)
     }
 
+    /// Add the seal and metadata of a [SuccinctReceipt] to the input, along
+    /// with the proof that its control ID is in the allowed tree.
+    pub fn add_succinct_receipt(&mut self, a: &SuccinctReceipt) -> Result<()> {
+        let allowed_ids = self.allowed_tree()?;
+        self.add_segment_receipt(a, &allowed_ids)
+    }
+
     fn add_segment_receipt(
         &mut self,
         a: &SuccinctReceipt,
         allowed_ids: &MerkleGroup,
     ) -> Result<()> {
-        self.add_seal(&a.seal, &a.control_id, &allowed_ids)?;
+        self.add_seal(&a.seal, &a.control_id, allowed_ids)?;
         let mut data = Vec::<u32>::new();
         a.meta.encode(&mut data)?;
         let data_fp: Vec<BabyBearElem> = data.iter().map(|x| BabyBearElem::new(*x)).collect();
@@ -399,7 +563,7 @@ impl Prover {
     /// TODO
     pub fn new_join(a: &SuccinctReceipt, b: &SuccinctReceipt, opts: ProverOpts) -> Result<Self> {
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = allowed_tree(&opts.control_ids)?;
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         let (program, control_id) = zkr::join()?;
@@ -411,10 +575,53 @@ impl Prover {
         Ok(prover)
     }
 
+    /// Construct a prover for the resolve program, which removes the head
+    /// assumption from the `conditional` receipt using a receipt for that
+    /// `assumption`.
+    pub fn new_resolve(
+        conditional: &SuccinctReceipt,
+        assumption: &SuccinctReceipt,
+        opts: ProverOpts,
+    ) -> Result<Self> {
+        let output = conditional
+            .meta
+            .output
+            .as_value()?
+            .as_ref()
+            .ok_or_else(|| anyhow!("conditional receipt has no output to resolve"))?;
+        let assumptions = output.assumptions.as_value()?;
+        let head = assumptions
+            .first()
+            .ok_or_else(|| anyhow!("conditional receipt has no assumptions to resolve"))?;
+        ensure!(
+            head.digest() == assumption.meta.digest(),
+            "assumption receipt does not match the head of the assumptions list: expected {}, got {}",
+            head.digest(),
+            assumption.meta.digest()
+        );
+        let rest = Assumptions(assumptions[1..].to_vec());
+
+        let hashfn = opts.suite.hashfn.as_ref();
+        let allowed_ids = allowed_tree(&opts.control_ids)?;
+        let merkle_root = allowed_ids.calc_root(hashfn);
+
+        let (program, control_id) = zkr::resolve()?;
+        let mut prover = Prover::new(program, control_id, opts);
+
+        prover.add_input_digest(&merkle_root);
+        prover.add_segment_receipt(conditional, &allowed_ids)?;
+        prover.add_segment_receipt(assumption, &allowed_ids)?;
+        // The program opens the conditional output digest using the digests of
+        // the journal and of the assumptions list after the head is removed.
+        prover.add_input_digest(&rest.digest());
+        prover.add_input_digest(&output.journal.digest());
+        Ok(prover)
+    }
+
     /// TODO
     pub fn new_identity(a: &SuccinctReceipt, opts: ProverOpts) -> Result<Self> {
         let hashfn = opts.suite.hashfn.as_ref();
-        let allowed_ids = Self::make_allowed_tree();
+        let allowed_ids = allowed_tree(&opts.control_ids)?;
         let merkle_root = allowed_ids.calc_root(hashfn);
 
         let (program, control_id) = zkr::identity()?;
@@ -425,14 +632,37 @@ impl Prover {
         Ok(prover)
     }
 
-    fn add_input(&mut self, input: &[u32]) {
+    /// Add raw words to the input of the program.
+    pub fn add_input(&mut self, input: &[u32]) {
         self.input.extend(input);
     }
 
-    fn add_input_digest(&mut self, digest: &Digest) {
+    /// Add a digest to the input of the program.
+    pub fn add_input_digest(&mut self, digest: &Digest) {
         self.add_input(digest.as_words())
     }
 
+    /// Run the prover, and decode the [ReceiptMetadata] written by the program
+    /// to produce a [SuccinctReceipt].
+    pub fn run_succinct(&mut self) -> Result<SuccinctReceipt> {
+        decode_succinct(self.run()?)
+    }
+
+    /// Run the prover on the given [HalPair], and decode the
+    /// [ReceiptMetadata] written by the program to produce a
+    /// [SuccinctReceipt].
+    pub fn run_succinct_with_hal<H, C>(
+        &mut self,
+        hal_pair: &HalPair<H, C>,
+    ) -> Result<SuccinctReceipt>
+    where
+        H: Hal<Field = BabyBear, Elem = BabyBearElem, ExtElem = BabyBearExtElem>,
+        C: CircuitHal<H>,
+    {
+        let (hal, circuit_hal) = (hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref());
+        decode_succinct(self.run_with_hal(hal, circuit_hal)?)
+    }
+
     /// TODO
     #[tracing::instrument(skip_all)]
     pub fn run(&mut self) -> Result<RecursionReceipt> {
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use anyhow::{ensure, Result};
 use risc0_zkp::{
     core::{digest::Digest, hash::HashSuite},
     field::baby_bear::{BabyBear, BabyBearElem},
@@ -19,7 +20,7 @@ use risc0_zkp::{
     prove::poly_group::PolyGroup,
 };
 
-use super::RECURSION_PO2;
+use super::{RECURSION_CODE_SIZE, RECURSION_PO2};
 
 /// TODO
 #[derive(Clone)]
@@ -32,6 +33,20 @@ pub struct Program {
 }
 
 impl Program {
+    /// Construct a program from the words of an encoded `.zkr` file, as
+    /// produced by the recursion program compiler.
+    pub fn from_encoded(encoded: &[u32]) -> Result<Self> {
+        ensure!(
+            encoded.len() % RECURSION_CODE_SIZE == 0,
+            "encoded program of {} words is not a whole number of {RECURSION_CODE_SIZE} word rows",
+            encoded.len()
+        );
+        Ok(Self {
+            code: encoded.iter().cloned().map(BabyBearElem::from).collect(),
+            code_size: RECURSION_CODE_SIZE,
+        })
+    }
+
     /// TODO
     pub fn code_rows(&self) -> usize {
         self.code.len() / self.code_size
@@ -12,12 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use risc0_circuit_recursion::REGISTER_GROUP_CODE;
-use risc0_zkp::{
-    adapter::TapsProvider, core::digest::Digest, field::baby_bear::BabyBearElem, MAX_CYCLES_PO2,
-    MIN_CYCLES_PO2,
-};
+use risc0_zkp::{adapter::TapsProvider, core::digest::Digest, MAX_CYCLES_PO2, MIN_CYCLES_PO2};
 
 use super::{Program, CIRCUIT, RECURSION_CODE_SIZE};
 
@@ -27,10 +24,7 @@ fn get_zkr(name: &str) -> Result<(Program, Digest)> {
     assert_eq!(code_size, RECURSION_CODE_SIZE);
 
     Ok((
-        Program {
-            code: u32s.iter().cloned().map(BabyBearElem::from).collect(),
-            code_size,
-        },
+        Program::from_encoded(&u32s)?,
         risc0_circuit_recursion::zkr::get_control_id(name)?,
     ))
 }
@@ -51,6 +45,13 @@ pub fn join() -> Result<(Program, Digest)> {
     get_zkr("join.zkr")
 }
 
+pub fn resolve() -> Result<(Program, Digest)> {
+    get_zkr("resolve.zkr").context(
+        "the recursion bundle has no resolve program; \
+         it must be rebuilt with resolve.zkr and pinned in risc0-circuit-recursion",
+    )
+}
+
 pub fn identity() -> Result<(Program, Digest)> {
     get_zkr("identity.zkr")
 }
@@ -20,7 +20,7 @@ use risc0_core::field::baby_bear::BabyBearElem;
 use risc0_zkp::{adapter::CircuitInfo, core::digest::Digest, verify::VerificationError};
 use serde::{Deserialize, Serialize};
 
-use super::CIRCUIT;
+use super::{merkle::MerkleGroup, CIRCUIT};
 use crate::{
     host::{control_id::POSEIDON_CONTROL_ID, receipt::VerifierContext},
     sha::Digestible,
@@ -41,6 +41,21 @@ pub fn valid_control_ids() -> Vec<Digest> {
     all_ids
 }
 
+/// Depth of the tree of allowed control IDs, which holds up to 256 IDs.
+pub(crate) const ALLOWED_CODE_MERKLE_DEPTH: usize = 8;
+
+/// Build the tree of control IDs whose seals the recursion programs accept.
+///
+/// The leaves are the [valid_control_ids] followed by `custom_ids`, the
+/// control IDs of any custom recursion programs. The prover and the verifier
+/// must use the same list, in the same order, since the root of this tree is
+/// committed to in the output of every recursion receipt.
+pub fn allowed_tree(custom_ids: &[Digest]) -> anyhow::Result<MerkleGroup> {
+    let mut leaves = valid_control_ids();
+    leaves.extend_from_slice(custom_ids);
+    MerkleGroup::new(ALLOWED_CODE_MERKLE_DEPTH, leaves)
+}
+
 /// This struct represents a receipt for one or more [crate::SegmentReceipt]s
 /// joined through recursion.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -66,9 +81,11 @@ impl SuccinctReceipt {
     ) -> Result<(), VerificationError> {
         // Assemble the list of control IDs, and therefore circuit variants, we will
         // accept.
-        let valid_ids = valid_control_ids();
+        let allowed_ids = allowed_tree(&ctx.recursion_control_ids)
+            .map_err(|_| VerificationError::ControlVerificationError)?;
         let check_code = |_, control_id: &Digest| -> Result<(), VerificationError> {
-            valid_ids
+            allowed_ids
+                .leaves
                 .iter()
                 .find(|x| *x == control_id)
                 .map(|_| ())
@@ -94,8 +111,12 @@ impl SuccinctReceipt {
             seal_meta.push_back(elem.as_u32())
         }
 
-        // TODO: Read root hash
-        seal_meta.drain(0..16);
+        // Verify that any seals verified by the recursion program were checked
+        // against the same set of allowed control IDs.
+        let allowed_root = read_sha_halfs(&mut seal_meta);
+        if allowed_root != allowed_ids.calc_root(suite.hashfn.as_ref()) {
+            return Err(VerificationError::ControlVerificationError);
+        }
         // Verify the output hash matches that data
         let output_hash = read_sha_halfs(&mut seal_meta);
         if output_hash != self.meta.digest() {
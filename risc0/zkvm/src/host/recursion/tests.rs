@@ -12,14 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::rc::Rc;
+
+use risc0_circuit_recursion::{
+    cpu::CpuCircuitHal,
+    zkr::{get_all_zkrs, get_control_id},
+};
+use risc0_zkp::{
+    core::{
+        digest::Digest,
+        hash::{poseidon::PoseidonHashSuite, sha::Sha256HashSuite},
+    },
+    hal::{
+        cpu::CpuHal,
+        dual::{DualCircuitHal, DualHal},
+    },
+};
 use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
 use serial_test::serial;
 use test_log::test;
 
-use super::{identity_p254, join, lift, prove::poseidon254_hal_pair, Prover, ProverOpts};
+use super::{
+    identity_p254, join, join_with_hal, lift, lift_with_hal, poseidon_hal_pair,
+    prove::poseidon254_hal_pair, valid_control_ids, Program, Prover, ProverOpts, CIRCUIT,
+};
 use crate::{
-    get_prover_server, ExecutorEnv, ExecutorImpl, InnerReceipt, Receipt, SegmentReceipt, Session,
-    VerifierContext,
+    get_prover_server, ExecutorEnv, ExecutorImpl, HalPair, InnerReceipt, Receipt, SegmentReceipt,
+    Session, VerifierContext,
 };
 
 fn generate_segments(hashfn: &str) -> (Session, Vec<SegmentReceipt>) {
@@ -38,6 +57,7 @@ fn generate_segments(hashfn: &str) -> (Session, Vec<SegmentReceipt>) {
     let opts = crate::ProverOpts {
         hashfn: hashfn.to_string(),
         prove_guest_errors: false,
+        ..Default::default()
     };
     let prover = get_prover_server(&opts).unwrap();
     log::info!("Proving rv32im");
@@ -58,8 +78,6 @@ fn generate_segments(hashfn: &str) -> (Session, Vec<SegmentReceipt>) {
 )]
 #[serial]
 fn test_recursion() {
-    use risc0_zkp::core::{digest::Digest, hash::poseidon::PoseidonHashSuite};
-
     let suite = PoseidonHashSuite::new_suite();
     let hal_pair = poseidon254_hal_pair();
     let (hal, circuit_hal) = (hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref());
@@ -120,3 +138,139 @@ fn test_recursion_e2e() {
     );
     rollup_receipt.verify(MULTI_TEST_ID).unwrap();
 }
+
+#[cfg_attr(
+    not(all(feature = "metal", target_os = "macos", target_arch = "x86_64")),
+    test
+)]
+#[serial]
+fn test_recursion_custom_program() {
+    use risc0_zkp::verify::VerificationError;
+
+    // Load the join program as if it were an application-specific aggregation
+    // program, and register its control ID.
+    let encoded = risc0_circuit_recursion::zkr::get_zkr("join.zkr").unwrap();
+    let program = Program::from_encoded(&encoded).unwrap();
+    let control_id = program.compute_control_id(PoseidonHashSuite::new_suite());
+    assert_eq!(
+        control_id,
+        risc0_circuit_recursion::zkr::get_control_id("join.zkr").unwrap()
+    );
+    let opts = || ProverOpts::default().with_control_ids(vec![control_id]);
+
+    let (_, segments) = generate_segments("poseidon");
+    let a = Prover::new_lift(&segments[0].seal, opts())
+        .unwrap()
+        .run_succinct()
+        .unwrap();
+    let b = Prover::new_lift(&segments[1].seal, opts())
+        .unwrap()
+        .run_succinct()
+        .unwrap();
+
+    let mut prover = Prover::new_custom(program, opts()).unwrap();
+    prover.add_succinct_receipt(&a).unwrap();
+    prover.add_succinct_receipt(&b).unwrap();
+    let receipt = prover.run_succinct().unwrap();
+    assert_eq!(receipt.control_id, control_id);
+
+    // The receipts commit to an allowed tree that includes the custom control
+    // ID, so they only verify when it is registered with the verifier.
+    let ctx = VerifierContext::default();
+    assert_eq!(
+        receipt.verify_integrity_with_context(&ctx),
+        Err(VerificationError::ControlVerificationError)
+    );
+
+    let ctx = VerifierContext::default().with_recursion_control_ids(vec![control_id]);
+    a.verify_integrity_with_context(&ctx).unwrap();
+    receipt.verify_integrity_with_context(&ctx).unwrap();
+}
+
+#[cfg_attr(
+    not(all(feature = "metal", target_os = "macos", target_arch = "x86_64")),
+    test
+)]
+#[serial]
+fn test_recursion_dual_hal() {
+    // Cross-check the recursion HAL selected by the enabled features against
+    // the CPU HAL while lifting and joining.
+    let lhs = poseidon_hal_pair();
+    let rhs = HalPair {
+        hal: Rc::new(CpuHal::new(PoseidonHashSuite::new_suite())),
+        circuit_hal: Rc::new(CpuCircuitHal::new(&CIRCUIT)),
+    };
+    let hal_pair = HalPair {
+        hal: Rc::new(DualHal::new(lhs.hal, rhs.hal)),
+        circuit_hal: Rc::new(DualCircuitHal::new(lhs.circuit_hal, rhs.circuit_hal)),
+    };
+
+    let (_, segments) = generate_segments("poseidon");
+    let a = lift_with_hal(&segments[0], ProverOpts::default(), &hal_pair).unwrap();
+    let b = lift_with_hal(&segments[1], ProverOpts::default(), &hal_pair).unwrap();
+    let receipt = join_with_hal(&a, &b, ProverOpts::default(), &hal_pair).unwrap();
+    receipt
+        .verify_integrity_with_context(&VerifierContext::default())
+        .unwrap();
+}
+
+#[test]
+#[serial]
+fn test_recursion_sha256_hal() {
+    // Prove the test recursion circuit with SHA-256 as the hash suite of both
+    // the prover and the HAL, and verify the seal with the same suite.
+    let suite = Sha256HashSuite::new_suite();
+    let hal_pair = HalPair {
+        hal: Rc::new(CpuHal::new(suite.clone())),
+        circuit_hal: Rc::new(CpuCircuitHal::new(&CIRCUIT)),
+    };
+
+    let digest1 = Digest::from([0, 1, 2, 3, 4, 5, 6, 7]);
+    let digest2 = Digest::from([8, 9, 10, 11, 12, 13, 14, 15]);
+    let expected = PoseidonHashSuite::new_suite()
+        .hashfn
+        .hash_pair(&digest1, &digest2);
+    let opts = ProverOpts::default().with_hash_suite(suite.clone());
+    let receipt = Prover::new_test_recursion_circuit([&digest1, &digest2], opts)
+        .unwrap()
+        .run_with_hal(hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref())
+        .unwrap();
+    assert_eq!(receipt.output_digest, *expected);
+    risc0_zkp::verify::verify(&CIRCUIT, &suite, &receipt.seal, |_, _| Ok(())).unwrap();
+}
+
+#[cfg_attr(
+    not(all(feature = "metal", target_os = "macos", target_arch = "x86_64")),
+    test
+)]
+#[serial]
+fn test_recursion_hash_suite_mismatch() {
+    // A HAL that proves with a different hash suite than the one the prover
+    // uses for the allowed tree is rejected before proving.
+    let hal_pair = HalPair {
+        hal: Rc::new(CpuHal::new(Sha256HashSuite::new_suite())),
+        circuit_hal: Rc::new(CpuCircuitHal::new(&CIRCUIT)),
+    };
+
+    let (_, segments) = generate_segments("poseidon");
+    let err = lift_with_hal(&segments[0], ProverOpts::default(), &hal_pair).unwrap_err();
+    assert!(err.to_string().contains("does not match"), "{err}");
+}
+
+#[test]
+fn bundled_programs_are_allowed() {
+    // The seals of every program in the recursion bundle, resolve included,
+    // must be accepted by the tree behind ALLOWED_IDS_ROOT. Run
+    // `cargo xtask bootstrap-recursion` to regenerate the control IDs when the
+    // bundle changes.
+    let allowed = valid_control_ids();
+    let resolve = get_control_id("resolve.zkr").unwrap();
+    assert!(allowed.contains(&resolve), "resolve.zkr is not allowed");
+    for (name, _) in get_all_zkrs().unwrap() {
+        let control_id = get_control_id(&name).unwrap();
+        assert!(
+            allowed.contains(&control_id),
+            "{name} ({control_id}) is not in RECURSION_CONTROL_IDS"
+        );
+    }
+}
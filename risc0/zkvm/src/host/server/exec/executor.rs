@@ -14,7 +14,9 @@
 
 //! This module implements the Executor.
 
-use std::{cell::RefCell, fmt::Debug, io::Write, mem, rc::Rc};
+use std::{
+    cell::RefCell, collections::BTreeMap, fmt::Debug, io::Write, mem, path::PathBuf, rc::Rc,
+};
 
 use addr2line::{
     fallible_iterator::FallibleIterator,
@@ -53,7 +55,8 @@ use crate::{
         server::opcode::{MajorType, OpCode},
     },
     sha::Digest,
-    ExecutorEnv, ExitCode, FileSegmentRef, Loader, Segment, SegmentRef, Session,
+    Assumption, ExecutionStats, ExecutorEnv, ExitCode, FileSegmentRef, Loader, Segment, SegmentRef,
+    Session, SyscallStats,
 };
 
 /// The number of cycles required to compress a SHA-256 block.
@@ -105,6 +108,25 @@ pub struct SyscallRecord {
     pub regs: (u32, u32),
 }
 
+/// A snapshot of a paused session, from which a new [ExecutorImpl] can
+/// continue the execution, possibly in another process.
+///
+/// A [Checkpoint] is taken with [ExecutorImpl::checkpoint] after the executor
+/// returns with [ExitCode::Paused], and is restored with
+/// [ExecutorImpl::from_checkpoint].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The [MemoryImage] to resume from, with the pc past the pause.
+    pub pre_image: MemoryImage,
+
+    /// The number of [Segment]s produced before the pause, used as the index
+    /// of the next [Segment].
+    pub segment_count: u32,
+
+    /// The assumptions added to the [ExecutorEnv] of the paused session.
+    pub assumptions: Vec<Assumption>,
+}
+
 /// The Executor provides an implementation for the execution phase.
 ///
 /// The proving phase uses an execution trace generated by the Executor.
@@ -119,11 +141,14 @@ pub struct ExecutorImpl<'a> {
     segment_limit: usize,
     segment_cycle: usize,
     segments: Vec<Box<dyn SegmentRef>>,
+    segment_count: u32,
     insn_counter: u32,
     split_insn: Option<u32>,
     const_cycles: usize,
     pending_syscall: Option<SyscallRecord>,
     syscalls: Vec<SyscallRecord>,
+    pending_ecall: Option<String>,
+    syscall_stats: BTreeMap<String, SyscallStats>,
     exit_code: Option<ExitCode>,
     obj_ctx: Option<ObjectContext>,
     output_digest: Option<Digest>,
@@ -153,7 +178,8 @@ impl<'a> ExecutorImpl<'a> {
         }
 
         let pc = image.pc;
-        let monitor = MemoryMonitor::new(image.clone(), env.trace.is_some());
+        let mut monitor = MemoryMonitor::new(image.clone(), env.trace.is_some());
+        monitor.trace_cycles = env.trace_cycles;
         let loader = Loader::new();
         let init_cycles = loader.init_cycles();
         let fini_cycles = loader.fini_cycles();
@@ -171,11 +197,14 @@ impl<'a> ExecutorImpl<'a> {
             segment_limit: 1 << segment_limit_po2,
             segment_cycle: init_cycles,
             segments: Vec::new(),
+            segment_count: 0,
             insn_counter: 0,
             split_insn: None,
             const_cycles,
             pending_syscall: None,
             syscalls: Vec::new(),
+            pending_ecall: None,
+            syscall_stats: BTreeMap::new(),
             exit_code: None,
             obj_ctx,
             output_digest: None,
@@ -210,19 +239,66 @@ impl<'a> ExecutorImpl<'a> {
         Self::with_obj_ctx(env, image, obj_ctx)
     }
 
+    /// Construct a new [ExecutorImpl] that continues the paused session
+    /// captured by a [Checkpoint].
+    ///
+    /// The assumptions of the [Checkpoint] are added to the given
+    /// [ExecutorEnv], which otherwise replaces the environment of the paused
+    /// session.
+    pub fn from_checkpoint(env: ExecutorEnv<'a>, checkpoint: Checkpoint) -> Result<Self> {
+        env.assumptions
+            .borrow_mut()
+            .cached
+            .extend(checkpoint.assumptions);
+        let mut exec = Self::with_obj_ctx(env, checkpoint.pre_image, None)?;
+        exec.segment_count = checkpoint.segment_count;
+        Ok(exec)
+    }
+
+    /// Capture a [Checkpoint] of a session that returned with
+    /// [ExitCode::Paused], so that it can be continued by
+    /// [ExecutorImpl::from_checkpoint].
+    pub fn checkpoint(&self) -> Result<Checkpoint> {
+        let Some(ExitCode::Paused(_)) = self.exit_code else {
+            bail!(
+                "cannot checkpoint an execution which exited with {:?}",
+                self.exit_code
+            );
+        };
+        let pre_image = self
+            .pre_image
+            .as_deref()
+            .ok_or_else(|| anyhow!("attempted to checkpoint the executor with no pre_image"))?;
+        Ok(Checkpoint {
+            pre_image: pre_image.clone(),
+            segment_count: self.segment_count,
+            assumptions: self.env.assumptions.borrow().cached.clone(),
+        })
+    }
+
     /// This will run the executor to get a [Session] which contain the results
     /// of the execution.
     pub fn run(&mut self) -> Result<Session> {
+        let path = self.segment_path()?;
+        self.run_with_callback(|segment| Ok(Box::new(FileSegmentRef::new(&segment, &path)?)))
+    }
+
+    /// Return the directory that [Segment]s are written to, creating a
+    /// temporary one if the [ExecutorEnv] does not specify it.
+    pub(crate) fn segment_path(&mut self) -> Result<PathBuf> {
         if self.env.segment_path.is_none() {
             self.env.segment_path = Some(tempdir()?.into_path());
         }
 
-        let path = self.env.segment_path.clone().unwrap();
-        self.run_with_callback(|segment| Ok(Box::new(FileSegmentRef::new(&segment, &path)?)))
+        Ok(self.env.segment_path.clone().unwrap())
     }
 
     /// Run the executor until [ExitCode::Halted], [ExitCode::Paused], or
     /// [ExitCode::Fault] is reached, producing a [Session] as a result.
+    ///
+    /// After [ExitCode::Paused], calling this again resumes the execution. The
+    /// [Session] of each run only holds the [Segment]s of that run, but their
+    /// indices continue from the previous run.
     pub fn run_with_callback<F>(&mut self, mut callback: F) -> Result<Session>
     where
         F: FnMut(Segment) -> Result<Box<dyn SegmentRef>>,
@@ -248,6 +324,7 @@ impl<'a> ExecutorImpl<'a> {
             .borrow_mut()
             .with_write_fd(fileno::JOURNAL, journal.clone());
 
+        let mut session_stats = ExecutionStats::default();
         let mut run_loop = || -> Result<(ExitCode, MemoryImage)> {
             loop {
                 if let Some(exit_code) = self.step()? {
@@ -261,8 +338,22 @@ impl<'a> ExecutorImpl<'a> {
                     let post_image_id = post_image.compute_id();
                     let syscalls = mem::take(&mut self.syscalls);
                     let faults = mem::take(&mut self.monitor.faults);
-                    let po2 = log2_ceil(total_cycles.next_power_of_two()).try_into()?;
+                    let padded_cycles = total_cycles.next_power_of_two();
+                    let po2 = log2_ceil(padded_cycles).try_into()?;
                     let cycles = self.body_cycles.try_into()?;
+                    let stats = ExecutionStats {
+                        segments: 1,
+                        total_cycles: padded_cycles as u64,
+                        user_cycles: self.body_cycles as u64,
+                        page_reads: faults.reads.len() as u64,
+                        page_read_cycles: self.monitor.page_read_cycles as u64,
+                        page_writes: faults.writes.len() as u64,
+                        page_write_cycles: self.monitor.page_write_cycles as u64,
+                        overhead_cycles: self.const_cycles as u64,
+                        padding_cycles: (padded_cycles - total_cycles) as u64,
+                        syscalls: mem::take(&mut self.syscall_stats),
+                    };
+                    session_stats.merge(&stats);
                     let segment = Segment::new(
                         pre_image,
                         post_image_id,
@@ -271,14 +362,16 @@ impl<'a> ExecutorImpl<'a> {
                         exit_code,
                         self.split_insn,
                         po2,
-                        self.segments
-                            .len()
-                            .try_into()
-                            .context("Too many segments to fit in u32")?,
+                        self.segment_count,
                         cycles,
+                        stats,
                     );
                     let segment_ref = callback(segment)?;
                     self.segments.push(segment_ref);
+                    self.segment_count = self
+                        .segment_count
+                        .checked_add(1)
+                        .context("Too many segments to fit in u32")?;
                     match exit_code {
                         ExitCode::SystemSplit => self.split(Some(post_image.into()))?,
                         ExitCode::SessionLimit => bail!("Session limit exceeded"),
@@ -329,6 +422,7 @@ impl<'a> ExecutorImpl<'a> {
             exit_code,
             post_image,
             assumptions,
+            session_stats,
         ))
     }
 
@@ -337,6 +431,7 @@ impl<'a> ExecutorImpl<'a> {
         self.body_cycles = 0;
         self.split_insn = None;
         self.insn_counter = 0;
+        self.pending_ecall = None;
         self.segment_cycle = self.init_cycles;
         self.monitor.clear_segment()
     }
@@ -460,6 +555,9 @@ impl<'a> ExecutorImpl<'a> {
     }
 
     fn advance(&mut self, opcode: OpCode, op_result: OpCodeResult) -> Option<ExitCode> {
+        let cycles = opcode.cycles + op_result.extra_cycles;
+        let ecall = self.pending_ecall.take();
+
         if let Some(ref trace) = self.env.trace {
             trace.borrow_mut()(TraceEvent::InstructionStart {
                 cycle: self.session_cycle() as u32,
@@ -471,11 +569,25 @@ impl<'a> ExecutorImpl<'a> {
             for event in self.monitor.trace_events.iter() {
                 trace.borrow_mut()(event.clone()).unwrap();
             }
+
+            if let (true, Some(name)) = (self.env.trace_cycles, ecall.as_ref()) {
+                trace.borrow_mut()(TraceEvent::Syscall {
+                    name: name.clone(),
+                    cycles: cycles as u32,
+                })
+                .unwrap();
+            }
+        }
+
+        if let Some(name) = ecall {
+            let stats = self.syscall_stats.entry(name).or_default();
+            stats.count += 1;
+            stats.cycles += cycles as u64;
         }
 
         self.pc = op_result.pc;
         self.insn_counter += 1;
-        self.body_cycles += opcode.cycles + op_result.extra_cycles;
+        self.body_cycles += cycles;
         let page_read_cycles = self.monitor.page_read_cycles;
         // log::debug!("page_read_cycles: {page_read_cycles}");
         self.segment_cycle = self.init_cycles + page_read_cycles + self.body_cycles;
@@ -498,14 +610,22 @@ impl<'a> ExecutorImpl<'a> {
     }
 
     fn ecall(&mut self) -> Result<OpCodeResult> {
-        match self.monitor.load_register(REG_T0) {
-            ecall::HALT => self.ecall_halt(),
-            ecall::INPUT => self.ecall_input(),
-            ecall::SOFTWARE => self.ecall_software(),
-            ecall::SHA => self.ecall_sha(),
-            ecall::BIGINT => self.ecall_bigint(),
+        // The cost of an ecall is recorded under this name once the
+        // instruction is committed. Software ecalls are recorded under the
+        // name of the syscall instead.
+        self.pending_ecall = None;
+        let (name, result) = match self.monitor.load_register(REG_T0) {
+            ecall::HALT => ("ecall(halt)", self.ecall_halt()),
+            ecall::INPUT => ("ecall(input)", self.ecall_input()),
+            ecall::SOFTWARE => ("ecall(software)", self.ecall_software()),
+            ecall::SHA => ("ecall(sha)", self.ecall_sha()),
+            ecall::BIGINT => ("ecall(bigint)", self.ecall_bigint()),
             ecall => bail!("Unknown ecall {ecall:?}"),
+        };
+        if self.pending_ecall.is_none() {
+            self.pending_ecall = Some(name.to_string());
         }
+        result
     }
 
     fn ecall_halt(&mut self) -> Result<OpCodeResult> {
@@ -656,6 +776,7 @@ impl<'a> ExecutorImpl<'a> {
         let to_guest_words = self.monitor.load_register(REG_A1);
         let name_ptr = self.monitor.load_guest_addr_from_register(REG_A2)?;
         let syscall_name = self.monitor.load_string_from_guest_memory(name_ptr)?;
+        self.pending_ecall = Some(syscall_name.clone());
         log::trace!("Guest called syscall {syscall_name:?} requesting {to_guest_words} words back");
 
         let chunks = align_up(to_guest_words as usize, WORD_SIZE);
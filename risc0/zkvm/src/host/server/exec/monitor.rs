@@ -77,6 +77,8 @@ pub struct MemoryMonitor {
     pub page_read_cycles: usize,
     pub page_write_cycles: usize,
     enable_trace: bool,
+    // Whether to trace PageIn and PageOut events.
+    pub trace_cycles: bool,
     pages: Vec<Option<Page>>,
     registers: [u32; REG_MAX],
 }
@@ -99,6 +101,7 @@ impl MemoryMonitor {
             page_read_cycles: 0,
             page_write_cycles: 0,
             enable_trace,
+            trace_cycles: false,
             pages,
             registers: [0; REG_MAX],
         }
@@ -184,6 +187,12 @@ impl MemoryMonitor {
             .push(Action::PageRead(page_idx, page_cycles));
         self.page_read_cycles += page_cycles;
         self.faults.reads.insert(page_idx);
+        if self.enable_trace && self.trace_cycles {
+            self.trace_events.insert(TraceEvent::PageIn {
+                page_idx,
+                cycles: page_cycles as u32,
+            });
+        }
         Ok(())
     }
 
@@ -209,6 +218,12 @@ impl MemoryMonitor {
             .push(Action::PageWrite(page_idx, page_cycles));
         self.page_write_cycles += page_cycles;
         self.faults.writes.insert(page_idx);
+        if self.enable_trace && self.trace_cycles {
+            self.trace_events.insert(TraceEvent::PageOut {
+                page_idx,
+                cycles: page_cycles as u32,
+            });
+        }
     }
 
     pub fn load_array<const N: usize>(&mut self, addr: u32) -> Result<[u8; N]> {
@@ -387,6 +402,9 @@ impl MemoryMonitor {
     }
 
     pub fn undo(&mut self) -> Result<()> {
+        if self.enable_trace {
+            self.trace_events.clear();
+        }
         let pending_actions = take(&mut self.pending_actions);
         for action in pending_actions.iter().rev() {
             match action {
@@ -18,12 +18,22 @@
 //! guest.  It does not trace full stack traces, but only provides the
 //! top level stack frame.  (More than one stack frame may show up
 //! in the case of inlined functions).
+//!
+//! If the executor also traces cycle events, enabled with
+//! [ExecutorEnvBuilder::trace_cycles](crate::ExecutorEnvBuilder::trace_cycles),
+//! cycles spent paging memory in and out, and in syscalls, are attributed to
+//! the pseudo-frames `[paging]` and `[syscall <name>]` below the frame that
+//! caused them.
+//!
+//! The profile can be exported as a pprof protobuf, as folded stacks for
+//! flamegraph tools, or as a Chrome trace.
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::Write,
     hash::{Hash, Hasher},
+    mem,
     rc::Rc,
 };
 
@@ -99,6 +109,10 @@ struct CallNode {
 
     /// Nodes representing further calls from this context.
     pub(crate) calls: HashMap<u32, Rc<RefCell<CallNode>>>,
+
+    /// Counter by program counter and pseudo-frame name, for cycles spent
+    /// paging or in syscalls with the current call stack.
+    pub(crate) pseudo_counts: HashMap<(u32, String), usize>,
 }
 
 impl CallNode {
@@ -154,11 +168,48 @@ pub struct Profiler {
     // Current CallNode key in the stack
     current_key: u32,
 
+    // Cycles spent paging in during the instruction at self.pc
+    page_in_cycles: u32,
+
+    // Cycles to be spent paging out because of the instruction at self.pc
+    page_out_cycles: u32,
+
+    // Syscall made by the instruction at self.pc, and its cycles
+    syscall: Option<(String, u32)>,
+
+    // Calls, returns, paging and syscalls in the order they happened, used for
+    // the Chrome trace.
+    timeline: Vec<TimelineEvent>,
+
     ctx: Context<EndianRcSlice<RunTimeEndian>>,
 
     profile: ProfileBuilder,
 }
 
+/// An event on the timeline of the Chrome trace.
+#[derive(Debug)]
+enum TimelineEvent {
+    /// A function at the given pc has been called.
+    Enter { cycle: u32, pc: u32 },
+
+    /// The innermost function has returned.
+    Exit { cycle: u32 },
+
+    /// Cycles have been spent paging or in a syscall.
+    Span {
+        cycle: u32,
+        cycles: u32,
+        name: String,
+    },
+}
+
+/// The name of the pseudo-frame for cycles spent paging.
+const PAGING_FRAME: &str = "[paging]";
+
+fn syscall_frame(name: &str) -> String {
+    format!("[syscall {name}]")
+}
+
 /// Represents a frame. Prefer to export the whole profiler proto using
 /// profiler.as_protobuf().
 #[derive(Clone, Debug)]
@@ -219,6 +270,10 @@ impl Profiler {
             current_node: Some(root),
             current_key: 0,
             call_stack_path: Vec::new(),
+            page_in_cycles: 0,
+            page_out_cycles: 0,
+            syscall: None,
+            timeline: Vec::new(),
             ctx,
             profile: ProfileBuilder::new(),
         };
@@ -301,6 +356,9 @@ impl Profiler {
 
     /// Returns a callback to populate this profiler, suitable for
     /// passing to ProverOpts::with_trace_callback.
+    ///
+    /// Enable [ExecutorEnvBuilder::trace_cycles](crate::ExecutorEnvBuilder::trace_cycles)
+    /// to attribute paging and syscall cycles.
     pub fn make_trace_callback(&mut self) -> impl FnMut(TraceEvent) -> anyhow::Result<()> + '_ {
         |event| {
             match event {
@@ -309,6 +367,17 @@ impl Profiler {
                     let orig_pc = self.pc;
                     let orig_insn = self.insn;
 
+                    // Split the cycles of the last instruction between itself, paging and the
+                    // syscall it made. Paging out happens at the end of the segment, so those
+                    // cycles come on top.
+                    let page_in_cycles = mem::take(&mut self.page_in_cycles).min(cycles);
+                    let page_out_cycles = mem::take(&mut self.page_out_cycles);
+                    let syscall = self.syscall.take().map(|(name, syscall_cycles)| {
+                        (name, syscall_cycles.min(cycles - page_in_cycles))
+                    });
+                    let syscall_cycles = syscall.as_ref().map_or(0, |(_, cycles)| *cycles);
+                    let insn_cycles = cycles - page_in_cycles - syscall_cycles;
+
                     if self.call_stack_path.len() > 0 {
                         let current_node = self
                             .current_node
@@ -318,8 +387,36 @@ impl Profiler {
                         current_node_borrowed
                             .counts
                             .entry(self.current_key)
-                            .and_modify(|e| *e += cycles as usize)
-                            .or_insert(cycles as usize);
+                            .and_modify(|e| *e += insn_cycles as usize)
+                            .or_insert(insn_cycles as usize);
+                        let paging_cycles = page_in_cycles + page_out_cycles;
+                        if paging_cycles > 0 {
+                            *current_node_borrowed
+                                .pseudo_counts
+                                .entry((self.current_key, PAGING_FRAME.to_string()))
+                                .or_default() += paging_cycles as usize;
+                        }
+                        if let Some((ref name, syscall_cycles)) = syscall {
+                            *current_node_borrowed
+                                .pseudo_counts
+                                .entry((self.current_key, syscall_frame(name)))
+                                .or_default() += syscall_cycles as usize;
+                        }
+                    }
+
+                    if page_in_cycles > 0 {
+                        self.timeline.push(TimelineEvent::Span {
+                            cycle: self.cycle,
+                            cycles: page_in_cycles,
+                            name: PAGING_FRAME.to_string(),
+                        });
+                    }
+                    if let Some((name, syscall_cycles)) = syscall {
+                        self.timeline.push(TimelineEvent::Span {
+                            cycle: self.cycle + page_in_cycles,
+                            cycles: syscall_cycles,
+                            name: syscall_frame(&name),
+                        });
                     }
 
                     if let Some(op) = extract_call_stack_op(orig_insn) {
@@ -327,6 +424,7 @@ impl Profiler {
                             CallStackOp::Push => {
                                 self.call_stack_path.push(pc);
                                 self.pop_stack.push(orig_pc);
+                                self.timeline.push(TimelineEvent::Enter { cycle, pc });
                             }
                             CallStackOp::Pop => loop {
                                 self.call_stack_path.pop().ok_or_else(|| {
@@ -335,6 +433,7 @@ impl Profiler {
                                 let popped = self.pop_stack.pop().ok_or_else(|| {
                                     anyhow!("attempted to follow a return with an empty call stack")
                                 })?;
+                                self.timeline.push(TimelineEvent::Exit { cycle });
                                 if pc - 4 == popped {
                                     break;
                                 }
@@ -351,12 +450,14 @@ impl Profiler {
                                             "attempted to follow a return with an empty call stack"
                                         )
                                     })?;
+                                    self.timeline.push(TimelineEvent::Exit { cycle });
                                     if pc - 4 == popped {
                                         break;
                                     }
                                 }
                                 self.call_stack_path.push(pc);
                                 self.pop_stack.push(orig_pc);
+                                self.timeline.push(TimelineEvent::Enter { cycle, pc });
                             }
                         }
 
@@ -386,6 +487,9 @@ impl Profiler {
                     self.insn = insn;
                     self.cycle = cycle;
                 }
+                TraceEvent::PageIn { cycles, .. } => self.page_in_cycles += cycles,
+                TraceEvent::PageOut { cycles, .. } => self.page_out_cycles += cycles,
+                TraceEvent::Syscall { name, cycles } => self.syscall = Some((name, cycles)),
                 _ => (),
             }
             Ok(())
@@ -401,36 +505,53 @@ impl Profiler {
                 new_stack.extend(frames);
             }
 
-            let location_ids: Vec<_> = new_stack
-                .iter()
-                .rev()
-                .map(|fr| {
-                    let func_id = self.profile.get_function(&fr.name, &fr.filename);
-                    let loc = proto::Location {
-                        address: pc as u64,
-                        line: vec![proto::Line {
-                            function_id: func_id,
-                            line: fr.lineno,
-                        }],
-                        ..Default::default()
-                    };
-                    self.profile.get_location(loc)
-                })
-                .collect();
-            let sample = proto::Sample {
-                location_id: location_ids,
-                value: vec![*count as i64],
-                ..Default::default()
-            };
-
-            if !sample.location_id.is_empty() {
-                self.profile.add_sample(sample);
-            }
+            self.add_sample(&new_stack, pc as u64, *count);
 
             if let Some(next_node_ref) = node.calls.get(&pc) {
                 self.walk_stack(next_node_ref.clone(), new_stack);
             }
         }
+
+        for ((pc, name), count) in &node.pseudo_counts {
+            let mut new_stack = stack.clone();
+            new_stack.extend(self.lookup_pc((*pc).into()));
+            new_stack.push(Frame {
+                name: name.clone(),
+                lineno: 0,
+                filename: "unknown".to_string(),
+            });
+
+            // These cycles are not spent by any single instruction.
+            self.add_sample(&new_stack, 0, *count);
+        }
+    }
+
+    fn add_sample(&mut self, stack: &[Frame], address: u64, count: usize) {
+        let location_ids: Vec<_> = stack
+            .iter()
+            .rev()
+            .map(|fr| {
+                let func_id = self.profile.get_function(&fr.name, &fr.filename);
+                let loc = proto::Location {
+                    address,
+                    line: vec![proto::Line {
+                        function_id: func_id,
+                        line: fr.lineno,
+                    }],
+                    ..Default::default()
+                };
+                self.profile.get_location(loc)
+            })
+            .collect();
+        let sample = proto::Sample {
+            location_id: location_ids,
+            value: vec![count as i64],
+            ..Default::default()
+        };
+
+        if !sample.location_id.is_empty() {
+            self.profile.add_sample(sample);
+        }
     }
 
     /// Count and save the profiling samples, consuming the profiler and
@@ -455,6 +576,95 @@ impl Profiler {
     pub fn encode_to_vec(&mut self) -> Vec<u8> {
         self.as_protobuf().encode_to_vec()
     }
+
+    /// Returns the result of this profiling run as folded stacks, one line per
+    /// stack with its cycle count, as read by flamegraph tools such as
+    /// inferno or flamegraph.pl.
+    pub fn encode_folded(&self) -> String {
+        let profile = self.as_protobuf();
+        let mut folded: BTreeMap<String, i64> = BTreeMap::new();
+        for sample in profile.sample.iter() {
+            let stack: Vec<_> = sample
+                .location_id
+                .iter()
+                .rev()
+                .flat_map(|id| profile.location[*id as usize - 1].line.iter())
+                .map(|line| {
+                    let func = &profile.function[line.function_id as usize - 1];
+                    profile.string_table[func.name as usize].replace(';', ":")
+                })
+                .collect();
+            *folded.entry(stack.join(";")).or_default() += sample.value[0];
+        }
+
+        let mut output = String::new();
+        for (stack, count) in folded {
+            writeln!(output, "{stack} {count}").unwrap();
+        }
+        output
+    }
+
+    /// Returns the result of this profiling run as a Chrome trace, in the JSON
+    /// format read by chrome://tracing and Perfetto.
+    ///
+    /// Timestamps are in cycles, so each cycle is shown as one microsecond.
+    pub fn encode_chrome_trace(&self) -> String {
+        let mut events = Vec::new();
+        let mut depth = 0;
+        for event in self.timeline.iter() {
+            events.push(match event {
+                TimelineEvent::Enter { cycle, pc } => {
+                    depth += 1;
+                    let name = self
+                        .lookup_pc((*pc).into())
+                        .first()
+                        .map_or_else(|| format!("0x{pc:08x}"), |frame| frame.name.clone());
+                    format!(
+                        r#"{{"name":{},"ph":"B","ts":{cycle},"pid":1,"tid":1}}"#,
+                        json_string(&name)
+                    )
+                }
+                TimelineEvent::Exit { cycle } => {
+                    depth -= 1;
+                    format!(r#"{{"ph":"E","ts":{cycle},"pid":1,"tid":1}}"#)
+                }
+                TimelineEvent::Span {
+                    cycle,
+                    cycles,
+                    name,
+                } => format!(
+                    r#"{{"name":{},"ph":"X","ts":{cycle},"dur":{cycles},"pid":1,"tid":1}}"#,
+                    json_string(name)
+                ),
+            });
+        }
+
+        // Close the calls that had not returned when the guest stopped.
+        for _ in 0..depth {
+            events.push(format!(
+                r#"{{"ph":"E","ts":{},"pid":1,"tid":1}}"#,
+                self.cycle
+            ));
+        }
+
+        format!(r#"{{"traceEvents":[{}]}}"#, events.join(","))
+    }
+}
+
+/// Encodes a string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut output = String::with_capacity(s.len() + 2);
+    output.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            ch if (ch as u32) < 0x20 => write!(output, "\\u{:04x}", ch as u32).unwrap(),
+            ch => output.push(ch),
+        }
+    }
+    output.push('"');
+    output
 }
 
 struct ProfileBuilder {
@@ -14,7 +14,14 @@
 
 //! Handlers for two-way private I/O between host and guest.
 
-use std::{cell::RefCell, cmp::min, collections::HashMap, rc::Rc, str::from_utf8};
+use std::{
+    cell::RefCell,
+    cmp::min,
+    collections::HashMap,
+    rc::Rc,
+    str::from_utf8,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use anyhow::{anyhow, bail, Result};
 use bytes::Bytes;
@@ -177,6 +184,15 @@ impl Syscall for SysGetenv {
     }
 }
 
+/// Set once stdout carries something other than human readable output, such
+/// as the `r0vm --stdio` protocol stream.
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+/// Send guest logs to stderr from now on, leaving stdout untouched.
+pub(crate) fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
 pub(crate) struct SysLog;
 impl Syscall for SysLog {
     fn syscall(
@@ -189,7 +205,11 @@ impl Syscall for SysLog {
         let buf_len = ctx.load_register(REG_A4);
         let from_guest = ctx.load_region(buf_ptr, buf_len)?;
         let msg = from_utf8(&from_guest)?;
-        println!("R0VM[{}] {}", ctx.get_cycle(), msg);
+        if STDOUT_RESERVED.load(Ordering::Relaxed) {
+            eprintln!("R0VM[{}] {}", ctx.get_cycle(), msg);
+        } else {
+            println!("R0VM[{}] {}", ctx.get_cycle(), msg);
+        }
         Ok((0, 0))
     }
 }
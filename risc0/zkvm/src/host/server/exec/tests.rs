@@ -22,8 +22,9 @@ use std::{
 use anyhow::Result;
 use bytes::Bytes;
 use risc0_zkvm_methods::{
+    heap,
     multi_test::{MultiTestSpec, SYS_MULTI_TEST},
-    HELLO_COMMIT_ELF, MULTI_TEST_ELF, SLICE_IO_ELF, STANDARD_LIB_ELF,
+    HEAP_FREE_LIST_ELF, HELLO_COMMIT_ELF, MULTI_TEST_ELF, SLICE_IO_ELF, STANDARD_LIB_ELF,
 };
 use risc0_zkvm_platform::{fileno, syscall::nr::SYS_RANDOM, PAGE_SIZE, WORD_SIZE};
 use sha2::{Digest as _, Sha256};
@@ -36,7 +37,7 @@ use crate::{
     },
     serde::to_vec,
     sha::Digest,
-    ExecutorEnv, ExecutorImpl, ExitCode, MemoryImage, Program,
+    Checkpoint, ExecutionStats, ExecutorEnv, ExecutorImpl, ExitCode, MemoryImage, Program, Session,
 };
 
 fn run_test(spec: MultiTestSpec) {
@@ -122,6 +123,48 @@ fn system_split() {
     assert_eq!(segments[1].index, 1);
 }
 
+#[test]
+fn execution_stats() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(16)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let segments = session.resolve().unwrap();
+    assert!(segments.len() > 1);
+
+    let mut expected = ExecutionStats::default();
+    for segment in segments.iter() {
+        let stats = &segment.stats;
+        assert_eq!(stats.segments, 1);
+        assert_eq!(stats.total_cycles, 1 << segment.po2);
+        assert_eq!(stats.user_cycles, segment.cycles as u64);
+        assert_eq!(
+            stats.user_cycles
+                + stats.page_read_cycles
+                + stats.page_write_cycles
+                + stats.overhead_cycles
+                + stats.padding_cycles,
+            stats.total_cycles
+        );
+        assert!(stats.page_reads > 0);
+        assert!(stats.syscalls.values().all(|x| x.count > 0));
+        expected.merge(stats);
+    }
+    assert_eq!(session.stats, expected);
+    assert_eq!(
+        session.get_cycles().unwrap(),
+        (expected.total_cycles, expected.user_cycles)
+    );
+    assert_eq!(expected.syscalls["ecall(halt)"].count, 1);
+}
+
 #[test]
 fn libm_build() {
     run_test(MultiTestSpec::LibM);
@@ -679,6 +722,51 @@ mod sys_verify {
     }
 }
 
+/// Runs [heap::churn] for the given rounds with the bump allocator in
+/// multi_test and with the free list allocator, returning the [Session]s.
+pub(crate) fn exec_heap_churn(rounds: u32) -> (Session, Session) {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::HeapChurn { rounds })
+        .unwrap()
+        .build()
+        .unwrap();
+    let bump = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let env = ExecutorEnv::builder()
+        .write(&rounds)
+        .unwrap()
+        .build()
+        .unwrap();
+    let free_list = ExecutorImpl::from_elf(env, HEAP_FREE_LIST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+
+    (bump, free_list)
+}
+
+#[test]
+fn heap_free_list() {
+    const ROUNDS: u32 = 16;
+    let (bump, free_list) = exec_heap_churn(ROUNDS);
+    assert_eq!(bump.exit_code, ExitCode::Halted(0));
+    assert_eq!(free_list.exit_code, ExitCode::Halted(0));
+
+    let (bump_sum, bump_grown): (u32, u32) = bump.journal.unwrap().decode().unwrap();
+    let (free_list_sum, free_list_grown): (u32, u32) = free_list.journal.unwrap().decode().unwrap();
+    assert_eq!(bump_sum, heap::churn(ROUNDS));
+    assert_eq!(free_list_sum, heap::churn(ROUNDS));
+
+    // The bump allocator never reuses memory, while the free list allocator
+    // reuses the memory of each round in the next.
+    let buf_size = heap::CHURN_BUF_SIZE as u32;
+    assert!(bump_grown >= ROUNDS * buf_size, "{bump_grown}");
+    assert!(free_list_grown < 2 * buf_size, "{free_list_grown}");
+}
+
 #[test]
 fn large_sha() {
     let data = vec![0u8; 100_000];
@@ -694,6 +782,26 @@ fn large_sha() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn streaming_sha() {
+    let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    let expected = hex::encode(Sha256::digest(&data));
+    for chunk_size in [7, 64, 1000] {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::ShaStream {
+                data: data.clone(),
+                chunk_size,
+            })
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+        let session = exec.run().unwrap();
+        let actual = hex::encode(Digest::try_from(session.journal.unwrap().bytes).unwrap());
+        assert_eq!(expected, actual, "chunk size {chunk_size}");
+    }
+}
+
 #[test]
 fn std_stdio() {
     const STDIN: &str = "Hello world from stdin!\n";
@@ -838,6 +946,60 @@ fn fault() {
     assert_eq!(session.exit_code, ExitCode::Fault);
 }
 
+#[test]
+fn pause_resume_segment_index() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::PauseContinue(0))
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+
+    // Run until sys_pause
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Paused(0));
+    let segments = session.resolve().unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].index, 0);
+
+    // Run until sys_halt. The numbering continues from before the pause.
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let segments = session.resolve().unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].index, 1);
+}
+
+#[test]
+fn pause_resume_from_checkpoint() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::PauseContinue(0))
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+
+    // A running or halted executor has nothing to checkpoint.
+    assert!(exec.checkpoint().is_err());
+
+    // Run until sys_pause
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Paused(0));
+    let checkpoint = bincode::serialize(&exec.checkpoint().unwrap()).unwrap();
+    drop(exec);
+
+    // Run until sys_halt from a fresh executor.
+    let checkpoint: Checkpoint = bincode::deserialize(&checkpoint).unwrap();
+    let env = ExecutorEnv::builder().build().unwrap();
+    let mut exec = ExecutorImpl::from_checkpoint(env, checkpoint).unwrap();
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let segments = session.resolve().unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].index, 1);
+    assert!(exec.checkpoint().is_err());
+}
+
 #[cfg(feature = "profiler")]
 #[test]
 fn profiler() {
@@ -932,6 +1094,45 @@ fn profiler() {
     assert!(check(&fr, addr), "{fr:#?} {addr}");
 }
 
+#[cfg(feature = "profiler")]
+#[test]
+fn profiler_trace_cycles() {
+    use crate::host::server::exec::profiler::Profiler;
+
+    let mut prof = Profiler::new("multi_test.elf", MULTI_TEST_ELF).unwrap();
+    {
+        let env = ExecutorEnv::builder()
+            .write(&MultiTestSpec::Profiler)
+            .unwrap()
+            .trace_callback(prof.make_trace_callback())
+            .trace_cycles(true)
+            .build()
+            .unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+            .unwrap()
+            .run()
+            .unwrap();
+    }
+
+    prof.finalize();
+
+    let folded = prof.encode_folded();
+    assert!(
+        folded
+            .lines()
+            .any(|line| line.contains("profile_test_func1;profile_test_func2 ")),
+        "{folded}"
+    );
+    assert!(
+        folded.lines().any(|line| line.contains("[paging]")),
+        "{folded}"
+    );
+
+    let chrome_trace = prof.encode_chrome_trace();
+    assert!(chrome_trace.starts_with(r#"{"traceEvents":["#));
+    assert!(chrome_trace.contains(r#""name":"profile_test_func1","ph":"B""#));
+}
+
 #[test]
 fn oom() {
     let env = ExecutorEnv::builder()
@@ -1045,6 +1246,26 @@ mod docker {
 
     use crate::{ExecutorEnv, ExecutorImpl, Session, TraceEvent};
 
+    #[test]
+    fn heap_free_list_cycles() {
+        let (bump, free_list) = super::exec_heap_churn(16);
+        let bump_paging = bump.stats.page_read_cycles + bump.stats.page_write_cycles;
+        let free_list_paging = free_list.stats.page_read_cycles + free_list.stats.page_write_cycles;
+        println!(
+            "bump: {} user cycles, {bump_paging} paging cycles",
+            bump.stats.user_cycles
+        );
+        println!(
+            "free list: {} user cycles, {free_list_paging} paging cycles",
+            free_list.stats.user_cycles
+        );
+
+        // Reusing memory costs some cycles in the allocator, but saves paging
+        // in fresh memory on every round.
+        assert!(free_list.stats.user_cycles < bump.stats.user_cycles * 3 / 2);
+        assert!(free_list_paging < bump_paging);
+    }
+
     #[test]
     fn trace() {
         let mut events: Vec<TraceEvent> = Vec::new();
@@ -79,4 +79,12 @@ impl ProverServer for DevModeProver {
     fn identity_p254(&self, _a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
         unimplemented!("This is unsupported for dev mode.")
     }
+
+    fn resolve(
+        &self,
+        _conditional: &SuccinctReceipt,
+        _assumption: &SuccinctReceipt,
+    ) -> Result<SuccinctReceipt> {
+        unimplemented!("This is unsupported for dev mode.")
+    }
 }
@@ -21,10 +21,11 @@ mod plonk;
 mod prover_impl;
 #[cfg(test)]
 mod tests;
+mod workers;
 
-use std::rc::Rc;
+use std::{rc::Rc, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, ensure, Result};
 use cfg_if::cfg_if;
 use risc0_binfmt::{MemoryImage, Program};
 use risc0_circuit_rv32im::CircuitImpl;
@@ -41,8 +42,11 @@ use risc0_zkvm_platform::{memory::GUEST_MAX_MEM, PAGE_SIZE, WORD_SIZE};
 
 use self::{dev_mode::DevModeProver, prover_impl::ProverImpl};
 use crate::{
-    host::receipt::{SegmentReceipt, SuccinctReceipt},
-    is_dev_mode, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment, Session, VerifierContext,
+    host::receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
+    is_dev_mode,
+    receipt_metadata::{Assumptions, MaybePruned, Output},
+    sha::Digestible,
+    ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, ReceiptKind, Segment, Session, VerifierContext,
 };
 
 /// A ProverServer can execute a given [MemoryImage] and produce a [Receipt]
@@ -94,6 +98,83 @@ pub trait ProverServer {
 
     /// Convert a [SuccinctReceipt] with a poseidon hash function that uses a 254-bit field
     fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt>;
+
+    /// Resolve the head assumption of a conditional [SuccinctReceipt] using a
+    /// [SuccinctReceipt] of that assumption.
+    fn resolve(
+        &self,
+        conditional: &SuccinctReceipt,
+        assumption: &SuccinctReceipt,
+    ) -> Result<SuccinctReceipt>;
+
+    /// Compress a [CompositeReceipt] into a single [SuccinctReceipt].
+    ///
+    /// Each [SegmentReceipt] is lifted and the results are joined pairwise,
+    /// forming a balanced tree of joins. Any assumptions are then compressed
+    /// and resolved in order, so that the result is unconditional.
+    fn compress(&self, composite: &CompositeReceipt) -> Result<SuccinctReceipt> {
+        if let Some(segment) = composite.segments.iter().find(|x| x.hashfn != "poseidon") {
+            bail!(
+                "cannot compress a segment receipt with hashfn: {}",
+                segment.hashfn
+            );
+        }
+
+        let mut receipts = composite
+            .segments
+            .iter()
+            .map(|segment| self.lift(segment))
+            .collect::<Result<Vec<_>>>()?;
+        while receipts.len() > 1 {
+            receipts = receipts
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => self.join(a, b),
+                    [a] => Ok(a.clone()),
+                    _ => unreachable!(),
+                })
+                .collect::<Result<_>>()?;
+        }
+        let mut conditional = receipts
+            .pop()
+            .ok_or_else(|| anyhow!("cannot compress a receipt with no segments"))?;
+        if composite.assumptions.is_empty() {
+            return Ok(conditional);
+        }
+
+        // Open the output of the conditional receipt so that the resolve
+        // program can be given the assumptions list.
+        let output = Output {
+            journal: MaybePruned::Pruned(
+                composite
+                    .journal_digest
+                    .ok_or_else(|| anyhow!("receipt with assumptions has no journal"))?,
+            ),
+            assumptions: Assumptions(
+                composite
+                    .assumptions
+                    .iter()
+                    .map(|a| Ok(a.get_metadata()?.into()))
+                    .collect::<Result<Vec<_>>>()?,
+            )
+            .into(),
+        };
+        ensure!(
+            conditional.meta.output.digest() == Some(output.clone()).digest(),
+            "composite receipt output does not match its assumptions"
+        );
+        conditional.meta.output = Some(output).into();
+
+        for assumption in composite.assumptions.iter() {
+            let assumption = match assumption {
+                InnerReceipt::Composite(inner) => self.compress(inner)?,
+                InnerReceipt::Succinct(inner) => inner.clone(),
+                _ => bail!("cannot compress a receipt with an assumption of this kind"),
+            };
+            conditional = self.resolve(&conditional, &assumption)?;
+        }
+        Ok(conditional)
+    }
 }
 
 /// A pair of [Hal] and [CircuitHal].
@@ -110,6 +191,12 @@ where
     pub circuit_hal: Rc<C>,
 }
 
+/// Constructs a new [HalPair].
+///
+/// A [HalPair] cannot be shared between threads, so each worker that proves
+/// segments concurrently constructs its own.
+pub(crate) type HalFactory<H, C> = Arc<dyn Fn() -> HalPair<H, C> + Send + Sync>;
+
 impl Session {
     /// For each segment, call [Segment::prove] and collect the receipts.
     pub fn prove(&self) -> Result<Receipt> {
@@ -159,31 +246,58 @@ impl Segment {
 
 #[cfg(feature = "cuda")]
 mod cuda {
-    use std::rc::Rc;
+    use std::{rc::Rc, sync::Arc};
 
     use anyhow::{bail, Result};
+    use risc0_circuit_recursion::cuda as recursion;
     use risc0_circuit_rv32im::cuda::{CudaCircuitHalPoseidon, CudaCircuitHalSha256};
     use risc0_zkp::hal::cuda::{CudaHalPoseidon, CudaHalSha256};
 
-    use super::{HalPair, ProverImpl, ProverServer};
+    use super::{HalFactory, HalPair, ProverImpl, ProverServer};
     use crate::ProverOpts;
 
     pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
         match opts.hashfn.as_str() {
             "sha-256" => {
-                let hal = Rc::new(CudaHalSha256::new());
-                let circuit_hal = Rc::new(CudaCircuitHalSha256::new(hal.clone()));
+                let hal_factory: HalFactory<_, _> = Arc::new(|| {
+                    let hal = Rc::new(CudaHalSha256::new());
+                    let circuit_hal = Rc::new(CudaCircuitHalSha256::new(hal.clone()));
+                    HalPair { hal, circuit_hal }
+                });
+                let hal_pair = hal_factory();
+                let recursion_hal_pair = HalPair {
+                    hal: hal_pair.hal.clone(),
+                    circuit_hal: Rc::new(recursion::CudaCircuitHalSha256::new(
+                        hal_pair.hal.clone(),
+                    )),
+                };
                 Ok(Rc::new(ProverImpl::new(
                     "cuda",
-                    HalPair { hal, circuit_hal },
+                    hal_pair,
+                    recursion_hal_pair,
+                    hal_factory,
+                    opts.clone(),
                 )))
             }
             "poseidon" => {
-                let hal = Rc::new(CudaHalPoseidon::new());
-                let circuit_hal = Rc::new(CudaCircuitHalPoseidon::new(hal.clone()));
+                let hal_factory: HalFactory<_, _> = Arc::new(|| {
+                    let hal = Rc::new(CudaHalPoseidon::new());
+                    let circuit_hal = Rc::new(CudaCircuitHalPoseidon::new(hal.clone()));
+                    HalPair { hal, circuit_hal }
+                });
+                let hal_pair = hal_factory();
+                let recursion_hal_pair = HalPair {
+                    hal: hal_pair.hal.clone(),
+                    circuit_hal: Rc::new(recursion::CudaCircuitHalPoseidon::new(
+                        hal_pair.hal.clone(),
+                    )),
+                };
                 Ok(Rc::new(ProverImpl::new(
                     "cuda",
-                    HalPair { hal, circuit_hal },
+                    hal_pair,
+                    recursion_hal_pair,
+                    hal_factory,
+                    opts.clone(),
                 )))
             }
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
@@ -193,33 +307,84 @@ mod cuda {
 
 #[cfg(feature = "metal")]
 mod metal {
-    use std::rc::Rc;
+    use std::{rc::Rc, sync::Arc};
 
     use anyhow::{bail, Result};
+    use risc0_circuit_recursion::metal as recursion;
     use risc0_circuit_rv32im::metal::MetalCircuitHal;
     use risc0_zkp::hal::metal::{
-        MetalHalPoseidon, MetalHalSha256, MetalHashPoseidon, MetalHashSha256,
+        MetalHalPoseidon, MetalHalPoseidon2, MetalHalSha256, MetalHashPoseidon, MetalHashPoseidon2,
+        MetalHashSha256,
     };
 
-    use super::{HalPair, ProverImpl, ProverServer};
+    use super::{HalFactory, HalPair, ProverImpl, ProverServer};
     use crate::ProverOpts;
 
     pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
         match opts.hashfn.as_str() {
             "sha-256" => {
-                let hal = Rc::new(MetalHalSha256::new());
-                let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashSha256>::new(hal.clone()));
+                let hal_factory: HalFactory<_, _> = Arc::new(|| {
+                    let hal = Rc::new(MetalHalSha256::new());
+                    let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashSha256>::new(hal.clone()));
+                    HalPair { hal, circuit_hal }
+                });
+                let hal_pair = hal_factory();
+                let recursion_hal_pair = HalPair {
+                    hal: hal_pair.hal.clone(),
+                    circuit_hal: Rc::new(recursion::MetalCircuitHal::<MetalHashSha256>::new(
+                        hal_pair.hal.clone(),
+                    )),
+                };
                 Ok(Rc::new(ProverImpl::new(
                     "metal",
-                    HalPair { hal, circuit_hal },
+                    hal_pair,
+                    recursion_hal_pair,
+                    hal_factory,
+                    opts.clone(),
                 )))
             }
             "poseidon" => {
-                let hal = Rc::new(MetalHalPoseidon::new());
-                let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashPoseidon>::new(hal.clone()));
+                let hal_factory: HalFactory<_, _> = Arc::new(|| {
+                    let hal = Rc::new(MetalHalPoseidon::new());
+                    let circuit_hal =
+                        Rc::new(MetalCircuitHal::<MetalHashPoseidon>::new(hal.clone()));
+                    HalPair { hal, circuit_hal }
+                });
+                let hal_pair = hal_factory();
+                let recursion_hal_pair = HalPair {
+                    hal: hal_pair.hal.clone(),
+                    circuit_hal: Rc::new(recursion::MetalCircuitHal::<MetalHashPoseidon>::new(
+                        hal_pair.hal.clone(),
+                    )),
+                };
+                Ok(Rc::new(ProverImpl::new(
+                    "metal",
+                    hal_pair,
+                    recursion_hal_pair,
+                    hal_factory,
+                    opts.clone(),
+                )))
+            }
+            "poseidon2" => {
+                let hal_factory: HalFactory<_, _> = Arc::new(|| {
+                    let hal = Rc::new(MetalHalPoseidon2::new());
+                    let circuit_hal =
+                        Rc::new(MetalCircuitHal::<MetalHashPoseidon2>::new(hal.clone()));
+                    HalPair { hal, circuit_hal }
+                });
+                let hal_pair = hal_factory();
+                let recursion_hal_pair = HalPair {
+                    hal: hal_pair.hal.clone(),
+                    circuit_hal: Rc::new(recursion::MetalCircuitHal::<MetalHashPoseidon2>::new(
+                        hal_pair.hal.clone(),
+                    )),
+                };
                 Ok(Rc::new(ProverImpl::new(
                     "metal",
-                    HalPair { hal, circuit_hal },
+                    hal_pair,
+                    recursion_hal_pair,
+                    hal_factory,
+                    opts.clone(),
                 )))
             }
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
@@ -229,28 +394,50 @@ mod metal {
 
 #[allow(dead_code)]
 mod cpu {
-    use std::rc::Rc;
+    use std::{rc::Rc, sync::Arc};
 
     use anyhow::{bail, Result};
     use risc0_circuit_rv32im::cpu::CpuCircuitHal;
+    use risc0_core::field::baby_bear::BabyBear;
     use risc0_zkp::{
-        core::hash::{poseidon::PoseidonHashSuite, sha::Sha256HashSuite},
+        core::hash::{
+            poseidon::PoseidonHashSuite, poseidon2::Poseidon2HashSuite, sha::Sha256HashSuite,
+            HashSuite,
+        },
         hal::cpu::CpuHal,
     };
 
-    use super::{HalPair, ProverImpl, ProverServer};
-    use crate::{host::CIRCUIT, ProverOpts};
+    use super::{HalFactory, HalPair, ProverImpl, ProverServer};
+    use crate::{
+        host::{recursion, CIRCUIT},
+        ProverOpts,
+    };
 
     pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
-        let suite = match opts.hashfn.as_str() {
-            "sha-256" => Sha256HashSuite::new_suite(),
-            "poseidon" => PoseidonHashSuite::new_suite(),
+        let new_suite: fn() -> HashSuite<BabyBear> = match opts.hashfn.as_str() {
+            "sha-256" => Sha256HashSuite::new_suite,
+            "poseidon" => PoseidonHashSuite::new_suite,
+            "poseidon2" => Poseidon2HashSuite::new_suite,
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
         };
-        let hal = Rc::new(CpuHal::new(suite));
-        let circuit_hal = Rc::new(CpuCircuitHal::new(&CIRCUIT));
-        let hal_pair = HalPair { hal, circuit_hal };
-        Ok(Rc::new(ProverImpl::new("cpu", hal_pair)))
+        let hal_factory: HalFactory<_, _> = Arc::new(move || HalPair {
+            hal: Rc::new(CpuHal::new(new_suite())),
+            circuit_hal: Rc::new(CpuCircuitHal::new(&CIRCUIT)),
+        });
+        let hal_pair = hal_factory();
+        let recursion_hal_pair = HalPair {
+            hal: hal_pair.hal.clone(),
+            circuit_hal: Rc::new(risc0_circuit_recursion::cpu::CpuCircuitHal::new(
+                &recursion::CIRCUIT,
+            )),
+        };
+        Ok(Rc::new(ProverImpl::new(
+            "cpu",
+            hal_pair,
+            recursion_hal_pair,
+            hal_factory,
+            opts.clone(),
+        )))
     }
 }
 
@@ -262,6 +449,16 @@ pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
         return Ok(Rc::new(DevModeProver));
     }
 
+    // Succinct receipts are produced by the recursion circuit, which only
+    // verifies seals that use Poseidon.
+    if opts.receipt_kind == ReceiptKind::Succinct && opts.hashfn != "poseidon" {
+        bail!(
+            "{:?} receipts require the poseidon hash function, not {}",
+            opts.receipt_kind,
+            opts.hashfn
+        );
+    }
+
     cfg_if! {
         if #[cfg(feature = "cuda")] {
             cuda::get_prover_server(opts)
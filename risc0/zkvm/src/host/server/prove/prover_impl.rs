@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::thread;
+
 use anyhow::{bail, Result};
 use risc0_circuit_rv32im::{
     layout::{OutBuffer, LAYOUT},
@@ -25,53 +27,75 @@ use risc0_zkp::{
     prove::adapter::ProveAdapter,
 };
 
-use super::{exec::MachineContext, HalPair, ProverServer};
+use super::{exec::MachineContext, workers::SegmentWorkers, HalFactory, HalPair, ProverServer};
 use crate::{
     host::{
         receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
-        recursion::{identity_p254, join, lift},
+        recursion::{
+            self, identity_with_hal, join_with_hal, lift_with_hal, poseidon254_hal_pair,
+            resolve_with_hal,
+        },
         CIRCUIT,
     },
     sha::Digestible,
-    Loader, Receipt, Segment, Session, VerifierContext,
+    ExecutorEnv, ExecutorImpl, FileSegmentRef, Loader, MemoryImage, ProverOpts, Receipt,
+    ReceiptKind, Segment, Session, SessionEvents, VerifierContext,
 };
 
 /// An implementation of a Prover that runs locally.
-pub struct ProverImpl<H, C>
+///
+/// Segments are proven on one [HalPair], for the rv32im circuit, and the
+/// recursion programs used to compress receipts on another, for the recursion
+/// circuit.
+pub struct ProverImpl<H, C, RH, RC>
 where
     H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
     C: CircuitHal<H>,
+    RH: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    RC: CircuitHal<RH>,
 {
     name: String,
     hal_pair: HalPair<H, C>,
+    recursion_hal_pair: HalPair<RH, RC>,
+    hal_factory: HalFactory<H, C>,
+    opts: ProverOpts,
 }
 
-impl<H, C> ProverImpl<H, C>
+impl<H, C, RH, RC> ProverImpl<H, C, RH, RC>
 where
     H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
     C: CircuitHal<H>,
+    RH: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    RC: CircuitHal<RH>,
 {
-    /// Construct a [ProverImpl] with the given name and [HalPair].
-    pub fn new(name: &str, hal_pair: HalPair<H, C>) -> Self {
+    /// Construct a [ProverImpl] with the given name, [HalPair]s for the rv32im
+    /// and recursion circuits, and [ProverOpts].
+    ///
+    /// `hal_factory` must construct [HalPair]s like `hal_pair`. It is used by
+    /// the workers that prove segments concurrently, each of which needs a
+    /// [HalPair] of its own.
+    pub fn new(
+        name: &str,
+        hal_pair: HalPair<H, C>,
+        recursion_hal_pair: HalPair<RH, RC>,
+        hal_factory: HalFactory<H, C>,
+        opts: ProverOpts,
+    ) -> Self {
         Self {
             name: name.to_string(),
             hal_pair,
+            recursion_hal_pair,
+            hal_factory,
+            opts,
         }
     }
-}
 
-impl<H, C> ProverServer for ProverImpl<H, C>
-where
-    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
-    C: CircuitHal<H>,
-{
-    fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<Receipt> {
-        log::info!(
-            "prove_session: {}, exit_code = {:?}, journal = {:?}",
-            self.name,
-            session.exit_code,
-            session.journal.as_ref().map(|x| hex::encode(x))
-        );
+    /// Prove each segment of the [Session] in order on the calling thread.
+    fn prove_segments(
+        &self,
+        ctx: &VerifierContext,
+        session: &Session,
+    ) -> Result<Vec<SegmentReceipt>> {
         let mut segments = Vec::new();
         for segment_ref in session.segments.iter() {
             let segment = segment_ref.resolve()?;
@@ -83,6 +107,99 @@ where
                 hook.on_post_prove_segment(&segment);
             }
         }
+        Ok(segments)
+    }
+
+    /// Prove the segments of the [Session] on a pool of worker threads.
+    fn prove_segments_parallel(
+        &self,
+        ctx: &VerifierContext,
+        session: &Session,
+    ) -> Result<Vec<SegmentReceipt>> {
+        let ((), segments) = self.prove_segment_stream(ctx, &session.hooks, |submit| {
+            for segment_ref in session.segments.iter() {
+                submit(segment_ref.resolve()?)?;
+            }
+            Ok(())
+        })?;
+        Ok(segments)
+    }
+
+    /// Execute the [MemoryImage] while proving its segments on a pool of worker
+    /// threads.
+    ///
+    /// Each [Segment] is submitted for proving as soon as the executor splits
+    /// it off. Submitting blocks the executor while the workers are saturated,
+    /// which bounds the number of segments held in memory.
+    fn execute_and_prove(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        image: MemoryImage,
+    ) -> Result<Receipt> {
+        let mut exec = ExecutorImpl::new(env, image)?;
+        let path = exec.segment_path()?;
+        // Segments go through the same stream as in [Self::prove_session], so
+        // hooks fire the same way. The [Session] only exists once execution
+        // finishes, and a fresh [Session] has no hooks registered.
+        let (session, segments) = self.prove_segment_stream(ctx, &[], |submit| {
+            exec.run_with_callback(|segment| {
+                let segment_ref = FileSegmentRef::new(&segment, &path)?;
+                submit(segment)?;
+                Ok(Box::new(segment_ref))
+            })
+        })?;
+        self.make_receipt(ctx, &session, segments)
+    }
+
+    /// Prove the segments passed to `submit` by `produce` on a pool of worker
+    /// threads, returning the output of `produce` and the receipts in segment
+    /// order.
+    ///
+    /// Segments are produced on the calling thread while previously submitted
+    /// segments are being proven. The [crate::SessionEvents] `hooks` are also
+    /// fired on the calling thread: `on_pre_prove_segment` as each segment is
+    /// submitted and `on_post_prove_segment` as each receipt is collected.
+    fn prove_segment_stream<T>(
+        &self,
+        ctx: &VerifierContext,
+        hooks: &[Box<dyn SessionEvents>],
+        produce: impl FnOnce(&mut dyn FnMut(Segment) -> Result<()>) -> Result<T>,
+    ) -> Result<(T, Vec<SegmentReceipt>)> {
+        let mut on_done = |segment: &Segment| {
+            for hook in hooks {
+                hook.on_post_prove_segment(segment);
+            }
+        };
+        thread::scope(|scope| {
+            let mut workers = SegmentWorkers::new(scope, ctx, &self.opts, &self.hal_factory);
+            let output = produce(&mut |segment| {
+                for hook in hooks {
+                    hook.on_pre_prove_segment(&segment);
+                }
+                workers.submit(segment, &mut on_done)
+            })?;
+            Ok((output, workers.finish(&mut on_done)?))
+        })
+    }
+
+    /// Options for the recursion prover, proving with the hash suite of our
+    /// recursion HAL and accepting the control IDs registered in our
+    /// [ProverOpts].
+    fn recursion_opts(&self) -> recursion::ProverOpts {
+        recursion::ProverOpts::default()
+            .with_hash_suite(self.recursion_hal_pair.hal.get_hash_suite().clone())
+            .with_control_ids(self.opts.recursion_control_ids.clone())
+    }
+
+    /// Assemble the [Receipt] for a [Session] from its [SegmentReceipt]s and
+    /// check that it matches the [Session].
+    fn make_receipt(
+        &self,
+        ctx: &VerifierContext,
+        session: &Session,
+        segments: Vec<SegmentReceipt>,
+    ) -> Result<Receipt> {
         // TODO(#982): Support unresolved assumptions here.
         let inner = InnerReceipt::Composite(CompositeReceipt {
             segments,
@@ -106,66 +223,58 @@ where
                 hex::encode(&receipt.get_metadata()?.digest())
             );
         }
-        Ok(receipt)
+
+        match self.opts.receipt_kind {
+            ReceiptKind::Composite => Ok(receipt),
+            ReceiptKind::Succinct => {
+                let succinct = self.compress(receipt.inner.composite()?)?;
+                let receipt = Receipt::new(InnerReceipt::Succinct(succinct), receipt.journal.bytes);
+                receipt.verify_integrity_with_context(ctx)?;
+                Ok(receipt)
+            }
+        }
     }
+}
 
-    fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt> {
-        use risc0_zkp::prove::executor::Executor;
+impl<H, C, RH, RC> ProverServer for ProverImpl<H, C, RH, RC>
+where
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    C: CircuitHal<H>,
+    RH: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    RC: CircuitHal<RH>,
+{
+    fn prove(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        image: MemoryImage,
+    ) -> Result<Receipt> {
+        if self.opts.segment_workers > 1 {
+            return self.execute_and_prove(env, ctx, image);
+        }
+        let mut exec = ExecutorImpl::new(env, image)?;
+        let session = exec.run()?;
+        self.prove_session(ctx, &session)
+    }
 
+    fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<Receipt> {
         log::info!(
-            "prove_segment[{}]: po2: {}, cycles: {}",
-            segment.index,
-            segment.po2,
-            segment.cycles,
-        );
-        let (hal, circuit_hal) = (self.hal_pair.hal.as_ref(), &self.hal_pair.circuit_hal);
-        let hashfn = &hal.get_hash_suite().name;
-
-        let io = segment.prepare_globals();
-        let machine = MachineContext::new(segment);
-        let po2 = segment.po2 as usize;
-        let mut executor = Executor::new(&CIRCUIT, machine, po2, po2, &io);
-
-        let loader = Loader::new();
-        loader.load(|chunk, fini| executor.step(chunk, fini))?;
-        executor.finalize();
-
-        let mut adapter = ProveAdapter::new(&mut executor);
-        let mut prover = risc0_zkp::prove::Prover::new(hal, CIRCUIT.get_taps());
-
-        adapter.execute(prover.iop());
-
-        prover.set_po2(adapter.po2() as usize);
-
-        prover.commit_group(
-            REGISTER_GROUP_CODE,
-            hal.copy_from_elem("code", &adapter.get_code().as_slice()),
-        );
-        prover.commit_group(
-            REGISTER_GROUP_DATA,
-            hal.copy_from_elem("data", &adapter.get_data().as_slice()),
-        );
-        adapter.accumulate(prover.iop());
-        prover.commit_group(
-            REGISTER_GROUP_ACCUM,
-            hal.copy_from_elem("accum", &adapter.get_accum().as_slice()),
+            "prove_session: {}, exit_code = {:?}, journal = {:?}",
+            self.name,
+            session.exit_code,
+            session.journal.as_ref().map(|x| hex::encode(x))
         );
-
-        let mix = hal.copy_from_elem("mix", &adapter.get_mix().as_slice());
-        let out_slice = &adapter.get_io().as_slice();
-
-        log::debug!("Globals: {:?}", OutBuffer(out_slice).tree(&LAYOUT));
-        let out = hal.copy_from_elem("out", &adapter.get_io().as_slice());
-
-        let seal = prover.finalize(&[&mix, &out], circuit_hal.as_ref());
-
-        let receipt = SegmentReceipt {
-            seal,
-            index: segment.index,
-            hashfn: hashfn.clone(),
+        let segments = if self.opts.segment_workers > 1 && session.segments.len() > 1 {
+            self.prove_segments_parallel(ctx, session)?
+        } else {
+            self.prove_segments(ctx, session)?
         };
-        receipt.verify_integrity_with_context(ctx)?;
+        self.make_receipt(ctx, session, segments)
+    }
 
+    fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt> {
+        let receipt = prove_segment_with_hal(&self.hal_pair, segment)?;
+        receipt.verify_integrity_with_context(ctx)?;
         Ok(receipt)
     }
 
@@ -174,14 +283,92 @@ where
     }
 
     fn lift(&self, receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
-        lift(receipt)
+        lift_with_hal(receipt, self.recursion_opts(), &self.recursion_hal_pair)
     }
 
     fn join(&self, a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-        join(a, b)
+        join_with_hal(a, b, self.recursion_opts(), &self.recursion_hal_pair)
     }
 
     fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
-        identity_p254(a)
+        // The seal must use Poseidon over the 254-bit field, which only has a
+        // CPU HAL, rather than the hash function of the recursion HAL.
+        identity_with_hal(a, self.recursion_opts(), &poseidon254_hal_pair())
     }
+
+    fn resolve(
+        &self,
+        conditional: &SuccinctReceipt,
+        assumption: &SuccinctReceipt,
+    ) -> Result<SuccinctReceipt> {
+        let opts = self.recursion_opts();
+        resolve_with_hal(conditional, assumption, opts, &self.recursion_hal_pair)
+    }
+}
+
+/// Prove a [Segment] on the given [HalPair], without verifying the resulting
+/// [SegmentReceipt].
+pub(crate) fn prove_segment_with_hal<H, C>(
+    hal_pair: &HalPair<H, C>,
+    segment: &Segment,
+) -> Result<SegmentReceipt>
+where
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    C: CircuitHal<H>,
+{
+    use risc0_zkp::prove::executor::Executor;
+
+    log::info!(
+        "prove_segment[{}]: po2: {}, cycles: {}",
+        segment.index,
+        segment.po2,
+        segment.cycles,
+    );
+    let (hal, circuit_hal) = (hal_pair.hal.as_ref(), &hal_pair.circuit_hal);
+    let hashfn = &hal.get_hash_suite().name;
+
+    let io = segment.prepare_globals();
+    let machine = MachineContext::new(segment);
+    let po2 = segment.po2 as usize;
+    let mut executor = Executor::new(&CIRCUIT, machine, po2, po2, &io);
+
+    let loader = Loader::new();
+    loader.load(|chunk, fini| executor.step(chunk, fini))?;
+    executor.finalize();
+
+    let mut adapter = ProveAdapter::new(&mut executor);
+    let mut prover = risc0_zkp::prove::Prover::new(hal, CIRCUIT.get_taps());
+
+    adapter.execute(prover.iop());
+
+    prover.set_po2(adapter.po2() as usize);
+
+    prover.commit_group(
+        REGISTER_GROUP_CODE,
+        hal.copy_from_elem("code", &adapter.get_code().as_slice()),
+    );
+    prover.commit_group(
+        REGISTER_GROUP_DATA,
+        hal.copy_from_elem("data", &adapter.get_data().as_slice()),
+    );
+    adapter.accumulate(prover.iop());
+    prover.commit_group(
+        REGISTER_GROUP_ACCUM,
+        hal.copy_from_elem("accum", &adapter.get_accum().as_slice()),
+    );
+
+    let mix = hal.copy_from_elem("mix", &adapter.get_mix().as_slice());
+    let out_slice = &adapter.get_io().as_slice();
+
+    log::debug!("Globals: {:?}", OutBuffer(out_slice).tree(&LAYOUT));
+    let out = hal.copy_from_elem("out", &adapter.get_io().as_slice());
+
+    let seal = prover.finalize(&[&mix, &out], circuit_hal.as_ref());
+
+    let receipt = SegmentReceipt {
+        seal,
+        index: segment.index,
+        hashfn: hashfn.clone(),
+    };
+    Ok(receipt)
 }
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::rc::Rc;
+use std::{rc::Rc, sync::Arc};
 
 use anyhow::Result;
 use risc0_circuit_rv32im::cpu::CpuCircuitHal;
@@ -26,11 +26,12 @@ use risc0_zkvm_platform::{memory, WORD_SIZE};
 use serial_test::serial;
 use test_log::test;
 
-use super::{get_prover_server, HalPair, ProverImpl};
+use super::{get_prover_server, HalFactory, HalPair, ProverImpl};
 use crate::{
-    host::{server::testutils, CIRCUIT},
+    host::{recursion::poseidon_hal_pair, server::testutils, CIRCUIT},
     serde::{from_slice, to_vec},
-    ExecutorEnv, ExecutorImpl, ExitCode, ProverOpts, ProverServer, Receipt, VerifierContext,
+    ExecutorEnv, ExecutorImpl, ExitCode, InnerReceipt, ProverOpts, ProverServer, Receipt,
+    ReceiptKind, VerifierContext,
 };
 
 fn prove_nothing(hashfn: &str) -> Result<Receipt> {
@@ -42,6 +43,7 @@ fn prove_nothing(hashfn: &str) -> Result<Receipt> {
     let opts = ProverOpts {
         hashfn: hashfn.to_string(),
         prove_guest_errors: false,
+        ..Default::default()
     };
     get_prover_server(&opts)
         .unwrap()
@@ -54,18 +56,37 @@ fn hashfn_poseidon() {
     prove_nothing("poseidon").unwrap();
 }
 
+#[test]
+#[cfg(not(feature = "cuda"))]
+fn hashfn_poseidon2() {
+    let receipt = prove_nothing("poseidon2").unwrap();
+    let segments = &receipt.inner.composite().unwrap().segments;
+    assert!(segments.iter().all(|segment| segment.hashfn == "poseidon2"));
+
+    let encoded: Vec<u32> = to_vec(&receipt).unwrap();
+    let decoded: Receipt = from_slice(&encoded).unwrap();
+    assert_eq!(decoded, receipt);
+    decoded.verify(MULTI_TEST_ID).unwrap();
+}
+
 #[test]
 fn hashfn_blake2b() {
-    let hal_pair = HalPair {
+    let hal_factory: HalFactory<_, _> = Arc::new(|| HalPair {
         hal: Rc::new(CpuHal::new(Blake2bCpuHashSuite::new_suite())),
         circuit_hal: Rc::new(CpuCircuitHal::new(&CIRCUIT)),
-    };
+    });
     let env = ExecutorEnv::builder()
         .write(&MultiTestSpec::DoNothing)
         .unwrap()
         .build()
         .unwrap();
-    let prover = ProverImpl::new("cpu:blake2b", hal_pair);
+    let prover = ProverImpl::new(
+        "cpu:blake2b",
+        hal_factory(),
+        poseidon_hal_pair(),
+        hal_factory,
+        ProverOpts::default(),
+    );
     prover.prove_elf(env, MULTI_TEST_ELF).unwrap();
 }
 
@@ -269,6 +290,129 @@ fn session_events() {
     assert_eq!(on_post_prove_segment_flag.take(), true);
 }
 
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn parallel_segments() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{Segment, SessionEvents};
+
+    struct Counter {
+        pre: Rc<RefCell<Vec<u32>>>,
+        post: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl SessionEvents for Counter {
+        fn on_pre_prove_segment(&self, segment: &Segment) {
+            self.pre.borrow_mut().push(segment.index);
+        }
+
+        fn on_post_prove_segment(&self, segment: &Segment) {
+            self.post.borrow_mut().push(segment.index);
+        }
+    }
+
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(14)
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    let mut session = exec.run().unwrap();
+    let num_segments = session.segments.len();
+    assert!(num_segments > 2);
+
+    let pre = Rc::new(RefCell::new(Vec::new()));
+    let post = Rc::new(RefCell::new(Vec::new()));
+    session.add_hook(Counter {
+        pre: pre.clone(),
+        post: post.clone(),
+    });
+
+    let opts = ProverOpts {
+        segment_workers: 3,
+        // Room for two segments at a time.
+        segment_memory_limit: Some(2 * super::workers::estimate_segment_memory(14)),
+        ..Default::default()
+    };
+    let receipt = get_prover_server(&opts)
+        .unwrap()
+        .prove_session(&VerifierContext::default(), &session)
+        .unwrap();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+
+    let segments = &receipt.inner.composite().unwrap().segments;
+    assert_eq!(segments.len(), num_segments);
+    for (idx, segment) in segments.iter().enumerate() {
+        assert_eq!(segment.index, idx as u32);
+    }
+
+    let expected: Vec<u32> = (0..num_segments as u32).collect();
+    assert_eq!(*pre.borrow(), expected);
+    let mut post = post.take();
+    post.sort();
+    assert_eq!(post, expected);
+}
+
+#[test]
+#[serial]
+fn receipt_kind_succinct() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(16)
+        .build()
+        .unwrap();
+    let opts = ProverOpts {
+        receipt_kind: ReceiptKind::Succinct,
+        ..Default::default()
+    };
+    let receipt = get_prover_server(&opts)
+        .unwrap()
+        .prove_elf(env, MULTI_TEST_ELF)
+        .unwrap();
+    assert!(matches!(receipt.inner, InnerReceipt::Succinct(_)));
+    receipt.verify(MULTI_TEST_ID).unwrap();
+}
+
+#[test]
+fn receipt_kind_succinct_requires_poseidon() {
+    let opts = ProverOpts {
+        hashfn: "sha-256".to_string(),
+        receipt_kind: ReceiptKind::Succinct,
+        ..Default::default()
+    };
+    let err = get_prover_server(&opts).err().unwrap();
+    assert!(err.to_string().contains("poseidon"), "{err}");
+}
+
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn execute_and_prove_streaming() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(14)
+        .build()
+        .unwrap();
+    let opts = ProverOpts {
+        segment_workers: 2,
+        ..Default::default()
+    };
+    let receipt = get_prover_server(&opts)
+        .unwrap()
+        .prove_elf(env, MULTI_TEST_ELF)
+        .unwrap();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+
+    let segments = &receipt.inner.composite().unwrap().segments;
+    assert!(segments.len() > 2);
+    for (idx, segment) in segments.iter().enumerate() {
+        assert_eq!(segment.index, idx as u32);
+    }
+}
+
 // These tests come from:
 // https://github.com/riscv-software-src/riscv-tests
 // They were built using the toolchain from:
@@ -443,14 +587,18 @@ mod sys_verify {
 
     use super::get_prover_server;
     use crate::{
-        serde::to_vec, sha::Digestible, ExecutorEnv, ExecutorEnvBuilder, ExitCode, ProverOpts,
-        Receipt,
+        receipt_metadata::{Assumptions, MaybePruned, Output},
+        serde::to_vec,
+        sha::Digestible,
+        CompositeReceipt, ExecutorEnv, ExecutorEnvBuilder, ExitCode, InnerReceipt, ProverOpts,
+        Receipt, ReceiptKind,
     };
 
     fn prove_hello_commit() -> Receipt {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            ..Default::default()
         };
 
         let hello_commit_receipt = get_prover_server(&opts)
@@ -467,6 +615,7 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: true,
+            ..Default::default()
         };
 
         let env = ExecutorEnvBuilder::default()
@@ -493,6 +642,7 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: true,
+            ..Default::default()
         };
 
         let env = ExecutorEnvBuilder::default()
@@ -524,6 +674,7 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            ..Default::default()
         };
 
         let spec = &MultiTestSpec::SysVerify {
@@ -579,11 +730,120 @@ mod sys_verify {
         // verify with wrong resolution results in verifier error.
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn sys_verify_succinct() {
+        let opts = ProverOpts {
+            receipt_kind: ReceiptKind::Succinct,
+            ..Default::default()
+        };
+
+        let hello_commit_receipt = get_prover_server(&opts)
+            .unwrap()
+            .prove_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+            .unwrap();
+        let spec = &MultiTestSpec::SysVerify {
+            image_id: HELLO_COMMIT_ID.into(),
+            journal: hello_commit_receipt.journal.bytes.clone(),
+        };
+
+        // Test that the assumption is resolved, resulting in an unconditional
+        // succinct receipt.
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .add_assumption(hello_commit_receipt.into())
+            .build()
+            .unwrap();
+        let receipt = get_prover_server(&opts)
+            .unwrap()
+            .prove_elf(env, MULTI_TEST_ELF)
+            .unwrap();
+        let InnerReceipt::Succinct(ref succinct) = receipt.inner else {
+            panic!("expected a succinct receipt");
+        };
+        assert!(succinct
+            .meta
+            .output
+            .as_value()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .assumptions
+            .is_empty());
+        receipt.verify(MULTI_TEST_ID).unwrap();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn sys_verify_resolve() {
+        let prover = get_prover_server(&ProverOpts::default()).unwrap();
+
+        // Prove the assumption as a succinct receipt.
+        let hello_commit_receipt = prover
+            .prove_elf(ExecutorEnv::default(), HELLO_COMMIT_ELF)
+            .unwrap();
+        let assumption = prover
+            .compress(hello_commit_receipt.inner.composite().unwrap())
+            .unwrap();
+        let hello_commit_receipt = Receipt::new(
+            InnerReceipt::Succinct(assumption.clone()),
+            hello_commit_receipt.journal.bytes,
+        );
+
+        // Prove a guest that verifies it with env::verify, and lift and join
+        // its segments into a receipt that is conditional on the assumption.
+        let spec = &MultiTestSpec::SysVerify {
+            image_id: HELLO_COMMIT_ID.into(),
+            journal: hello_commit_receipt.journal.bytes.clone(),
+        };
+        let env = ExecutorEnv::builder()
+            .write(&spec)
+            .unwrap()
+            .add_assumption(hello_commit_receipt.into())
+            .build()
+            .unwrap();
+        let receipt = prover.prove_elf(env, MULTI_TEST_ELF).unwrap();
+        let composite = receipt.inner.composite().unwrap();
+        let mut conditional = prover
+            .compress(&CompositeReceipt {
+                assumptions: Vec::new(),
+                ..composite.clone()
+            })
+            .unwrap();
+        conditional.meta.output = Some(Output {
+            journal: MaybePruned::Pruned(receipt.journal.digest()),
+            assumptions: Assumptions(vec![assumption.meta.clone().into()]).into(),
+        })
+        .into();
+        let conditional_receipt = Receipt::new(
+            InnerReceipt::Succinct(conditional.clone()),
+            receipt.journal.bytes.clone(),
+        );
+        assert!(conditional_receipt.verify(MULTI_TEST_ID).is_err());
+
+        // Resolving the assumption yields an unconditional receipt.
+        let resolved = prover.resolve(&conditional, &assumption).unwrap();
+        assert!(resolved
+            .meta
+            .output
+            .as_value()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .assumptions
+            .is_empty());
+        Receipt::new(InnerReceipt::Succinct(resolved), receipt.journal.bytes)
+            .verify(MULTI_TEST_ID)
+            .unwrap();
+    }
+
     #[test]
     fn sys_verify_integrity() {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            ..Default::default()
         };
 
         let spec = &MultiTestSpec::SysVerifyIntegrity {
@@ -641,6 +901,7 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            ..Default::default()
         };
 
         let spec = &MultiTestSpec::SysVerifyIntegrity {
@@ -672,6 +933,7 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            ..Default::default()
         };
 
         let spec = &MultiTestSpec::SysVerifyIntegrity {
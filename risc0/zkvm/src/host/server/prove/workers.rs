@@ -0,0 +1,217 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A pool of worker threads that prove [Segment]s concurrently.
+//!
+//! HAL implementations are not thread-safe, so each worker constructs its own
+//! [super::HalPair] from the [HalFactory] of the prover. Segments are resolved and
+//! submitted by the calling thread, which allows the I/O involved in resolving
+//! a [crate::SegmentRef] to overlap with proving. Receipts are verified on the
+//! calling thread as well, against the caller's [VerifierContext].
+
+use std::{
+    collections::BTreeMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread::{Scope, ScopedJoinHandle},
+};
+
+use anyhow::{anyhow, bail, Result};
+use risc0_core::field::baby_bear::{BabyBear, Elem, ExtElem};
+use risc0_zkp::{
+    adapter::TapsProvider,
+    hal::{CircuitHal, Hal},
+    INV_RATE,
+};
+
+use super::{prover_impl::prove_segment_with_hal, HalFactory};
+use crate::{
+    host::{receipt::SegmentReceipt, CIRCUIT},
+    ProverOpts, Segment, VerifierContext,
+};
+
+type Job = Segment;
+type JobResult = (u32, Result<(Segment, SegmentReceipt)>);
+
+/// Estimate the peak memory, in bytes, needed to prove a segment of size
+/// `2^po2`.
+///
+/// Every register group of the circuit is held both as coefficients and as an
+/// evaluation over the extended domain. This is a rough heuristic and is only
+/// used to bound how many segments are proven at the same time.
+pub(crate) fn estimate_segment_memory(po2: u32) -> usize {
+    let taps = CIRCUIT.get_taps();
+    let columns: usize = (0..taps.num_groups())
+        .map(|group| taps.group_size(group))
+        .sum();
+    columns * (1 + INV_RATE) * (1 << po2) * std::mem::size_of::<Elem>()
+}
+
+/// Proves segments on a fixed number of worker threads.
+///
+/// Receipts are returned in segment index order regardless of the order in
+/// which the workers complete them.
+pub(crate) struct SegmentWorkers<'scope, 'ctx> {
+    ctx: &'ctx VerifierContext,
+    jobs: Option<mpsc::SyncSender<Job>>,
+    results: mpsc::Receiver<JobResult>,
+    handles: Vec<ScopedJoinHandle<'scope, ()>>,
+    memory_limit: Option<usize>,
+    in_flight: BTreeMap<u32, usize>,
+    receipts: BTreeMap<u32, SegmentReceipt>,
+}
+
+impl<'scope, 'ctx> SegmentWorkers<'scope, 'ctx> {
+    /// Spawn `opts.segment_workers` workers within the given scope, each
+    /// proving on a [super::HalPair] from `hal_factory`. Receipts are verified
+    /// against `ctx`.
+    pub fn new<'env, H, C>(
+        scope: &'scope Scope<'scope, 'env>,
+        ctx: &'ctx VerifierContext,
+        opts: &ProverOpts,
+        hal_factory: &HalFactory<H, C>,
+    ) -> Self
+    where
+        H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem> + 'scope,
+        C: CircuitHal<H> + 'scope,
+    {
+        let num_workers = opts.segment_workers.max(1);
+        log::debug!(
+            "segment workers: {num_workers}, memory limit: {:?}",
+            opts.segment_memory_limit
+        );
+        // Allow one queued segment per worker so that the next segment is
+        // already resolved by the time a worker becomes available.
+        let (jobs, job_rx) = mpsc::sync_channel::<Job>(num_workers);
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let (result_tx, results) = mpsc::channel();
+
+        let handles = (0..num_workers)
+            .map(|_| {
+                let job_rx = job_rx.clone();
+                let result_tx = result_tx.clone();
+                let hal_factory = hal_factory.clone();
+                scope.spawn(move || worker(hal_factory, job_rx, result_tx))
+            })
+            .collect();
+
+        Self {
+            ctx,
+            jobs: Some(jobs),
+            results,
+            handles,
+            memory_limit: opts.segment_memory_limit,
+            in_flight: BTreeMap::new(),
+            receipts: BTreeMap::new(),
+        }
+    }
+
+    /// Submit a [Segment] to be proven.
+    ///
+    /// This blocks while the estimated memory of the segments in flight would
+    /// exceed the memory limit. `on_done` is called on this thread for every
+    /// segment that completes in the meantime.
+    pub fn submit(&mut self, segment: Segment, on_done: &mut dyn FnMut(&Segment)) -> Result<()> {
+        let memory = estimate_segment_memory(segment.po2);
+        if let Some(limit) = self.memory_limit {
+            // A single segment larger than the limit is still proven, alone.
+            while !self.in_flight.is_empty() && self.in_flight_memory() + memory > limit {
+                self.recv(on_done)?;
+            }
+        }
+        // Collect whatever has already finished so that errors surface early.
+        while let Ok(result) = self.results.try_recv() {
+            self.complete(result, on_done)?;
+        }
+
+        self.in_flight.insert(segment.index, memory);
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send(segment)
+            .map_err(|_| anyhow!("segment workers exited unexpectedly"))
+    }
+
+    /// Wait for all submitted segments to be proven and return the receipts
+    /// ordered by segment index.
+    pub fn finish(mut self, on_done: &mut dyn FnMut(&Segment)) -> Result<Vec<SegmentReceipt>> {
+        // Closing the job queue lets the workers exit once it is drained.
+        self.jobs = None;
+        while !self.in_flight.is_empty() {
+            self.recv(on_done)?;
+        }
+        for handle in self.handles.drain(..) {
+            handle
+                .join()
+                .map_err(|_| anyhow!("segment worker panicked"))?;
+        }
+        Ok(std::mem::take(&mut self.receipts).into_values().collect())
+    }
+
+    fn in_flight_memory(&self) -> usize {
+        self.in_flight.values().sum()
+    }
+
+    fn recv(&mut self, on_done: &mut dyn FnMut(&Segment)) -> Result<()> {
+        let result = self
+            .results
+            .recv()
+            .map_err(|_| anyhow!("segment workers exited unexpectedly"))?;
+        self.complete(result, on_done)
+    }
+
+    fn complete(&mut self, result: JobResult, on_done: &mut dyn FnMut(&Segment)) -> Result<()> {
+        let (index, result) = result;
+        self.in_flight.remove(&index);
+        let (segment, receipt) = result?;
+        receipt.verify_integrity_with_context(self.ctx)?;
+        on_done(&segment);
+        if self.receipts.insert(index, receipt).is_some() {
+            bail!("segment {index} was proven more than once");
+        }
+        Ok(())
+    }
+}
+
+fn worker<H, C>(
+    hal_factory: HalFactory<H, C>,
+    jobs: Arc<Mutex<mpsc::Receiver<Job>>>,
+    results: mpsc::Sender<JobResult>,
+) where
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
+    C: CircuitHal<H>,
+{
+    let hal_pair = panic::catch_unwind(AssertUnwindSafe(|| hal_factory()));
+    loop {
+        // Only hold the lock while waiting for the next job.
+        let segment = match jobs.lock().unwrap().recv() {
+            Ok(segment) => segment,
+            Err(_) => return,
+        };
+        let index = segment.index;
+        let result = match hal_pair {
+            // A panicking worker would otherwise leave its segment in flight
+            // forever, so report it as an error instead.
+            Ok(ref hal_pair) => panic::catch_unwind(AssertUnwindSafe(|| {
+                prove_segment_with_hal(hal_pair, &segment)
+            }))
+            .unwrap_or_else(|_| Err(anyhow!("segment {index} prover panicked")))
+            .map(|receipt| (segment, receipt)),
+            Err(_) => Err(anyhow!("failed to create segment prover")),
+        };
+        if results.send((index, result)).is_err() {
+            return;
+        }
+    }
+}
@@ -30,7 +30,7 @@ use crate::{
     host::server::exec::executor::SyscallRecord,
     receipt_metadata::{Assumptions, Output},
     sha::Digest,
-    Assumption, ExitCode, Journal, MemoryImage, ReceiptMetadata, SystemState,
+    Assumption, ExecutionStats, ExitCode, Journal, MemoryImage, ReceiptMetadata, SystemState,
 };
 
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
@@ -65,6 +65,9 @@ pub struct Session {
     /// The list of assumptions made by the guest and resolved by the host.
     pub assumptions: Vec<Assumption>,
 
+    /// The breakdown of the cycles of all segments in the session.
+    pub stats: ExecutionStats,
+
     /// The hooks to be called during the proving phase.
     #[serde(skip)]
     pub hooks: Vec<Box<dyn SessionEvents>>,
@@ -102,11 +105,18 @@ pub struct Segment {
     pub po2: u32,
 
     /// The index of this [Segment] within the [Session]
+    ///
+    /// Indices continue across [ExitCode::Paused]: the first [Segment] after
+    /// a resume, in the same executor or from a
+    /// [Checkpoint](crate::Checkpoint), follows the last one before the pause.
     pub index: u32,
 
     /// The number of user cycles without any overhead for continuations or po2
     /// padding.
     pub cycles: u32,
+
+    /// The breakdown of the cycles of this [Segment].
+    pub stats: ExecutionStats,
 }
 
 /// The Events of [Session]
@@ -128,6 +138,7 @@ impl Session {
         exit_code: ExitCode,
         post_image: MemoryImage,
         assumptions: Vec<Assumption>,
+        stats: ExecutionStats,
     ) -> Self {
         Self {
             segments,
@@ -135,6 +146,7 @@ impl Session {
             exit_code,
             post_image,
             assumptions,
+            stats,
             hooks: Vec::new(),
         }
     }
@@ -256,6 +268,7 @@ impl Segment {
         po2: u32,
         index: u32,
         cycles: u32,
+        stats: ExecutionStats,
     ) -> Self {
         log::info!("segment[{index}]> reads: {}, writes: {}, exit_code: {exit_code:?}, split_insn: {split_insn:?}, po2: {po2}, cycles: {cycles}",
             faults.reads.len(),
@@ -271,6 +284,7 @@ impl Segment {
             po2,
             index,
             cycles,
+            stats,
         }
     }
 }
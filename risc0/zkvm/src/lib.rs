@@ -19,6 +19,7 @@
 
 extern crate alloc;
 
+pub mod ec;
 mod fault_ids;
 pub use fault_ids::{FAULT_CHECKER_ELF, FAULT_CHECKER_ID};
 
@@ -30,6 +31,7 @@ pub use self::fault_monitor::FaultCheckMonitor;
 pub mod guest;
 #[cfg(not(target_os = "zkvm"))]
 mod host;
+pub mod merkle;
 pub mod serde;
 pub mod sha;
 
@@ -57,10 +59,10 @@ pub use risc0_zkvm_platform::{declare_syscall, memory::GUEST_MAX_MEM, PAGE_SIZE}
 pub use self::host::server::exec::profiler::Profiler;
 #[cfg(all(not(target_os = "zkvm"), feature = "prove"))]
 pub use self::host::{
-    api::server::Server as ApiServer,
+    api::server::{Daemon as ApiDaemon, DaemonHandle as ApiDaemonHandle, Server as ApiServer},
     client::prove::local::LocalProver,
     server::{
-        exec::executor::ExecutorImpl,
+        exec::executor::{Checkpoint, ExecutorImpl},
         prove::{get_prover_server, loader::Loader, HalPair, ProverServer},
         session::{FileSegmentRef, Segment, SegmentRef, Session, SessionEvents, SimpleSegmentRef},
     },
@@ -68,15 +70,15 @@ pub use self::host::{
 #[cfg(all(not(target_os = "zkvm"), feature = "client"))]
 pub use self::host::{
     api::{
-        client::Client as ApiClient, Asset, AssetRequest, Binary, Connector, SegmentInfo,
-        SessionInfo,
+        client::Client as ApiClient, Asset, AssetRequest, Binary, Connector, ExecutionStats,
+        SegmentInfo, SessionInfo, SyscallStats,
     },
     client::{
         env::{ExecutorEnv, ExecutorEnvBuilder},
         exec::TraceEvent,
         prove::{
             bonsai::BonsaiProver, default_executor, default_prover, external::ExternalProver,
-            Executor, Prover, ProverOpts,
+            Executor, Prover, ProverOpts, ReceiptKind,
         },
     },
 };
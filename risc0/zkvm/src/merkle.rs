@@ -0,0 +1,284 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle trees over SHA-256, usable from both the host and the guest.
+//!
+//! Leaves are digests, typically computed with [hash_leaf]. Internal nodes
+//! are computed with [hash_node], which is a single call to the SHA-256
+//! compression function and so is cheap in the guest. Trees with a number of
+//! leaves that is not a power of two are padded with [pad_leaf], which is
+//! hashed with its own prefix so that no leaf from [hash_leaf] can take the
+//! place of the padding.
+//!
+//! A typical use is for the host to build a tree over a large data set and
+//! commit to its root, and for the guest to check [MerkleProof]s for the few
+//! elements it needs.
+//!
+//! ```rust
+//! use risc0_zkvm::merkle::{hash_leaf, MerkleTree};
+//!
+//! let elements = ["apple", "banana", "cherry"];
+//! let tree = MerkleTree::new(elements.iter().map(|e| hash_leaf(e.as_bytes())).collect());
+//!
+//! let proof = tree.prove(1);
+//! assert!(proof.verify(&tree.root(), &hash_leaf(b"banana")));
+//! assert!(!proof.verify(&tree.root(), &hash_leaf(b"cherry")));
+//! ```
+
+use alloc::{vec, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::sha::{
+    rust_crypto::{Digest as _, Sha256 as Hasher},
+    Digest, Impl, Sha256,
+};
+
+/// Prefix added to leaf data before hashing, so that a leaf can never be
+/// mistaken for an internal node.
+const LEAF_PREFIX: u8 = 0;
+
+/// Prefix of the padding leaf, so that it differs from every [hash_leaf].
+const PAD_PREFIX: u8 = 1;
+
+/// Computes the leaf digest of the given data.
+///
+/// This is the SHA-256 hash of the data with a one byte prefix.
+pub fn hash_leaf(data: &[u8]) -> Digest {
+    let hash = Hasher::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(data)
+        .finalize();
+    Digest::try_from(hash.as_slice()).unwrap()
+}
+
+/// Returns the leaf used to pad a tree to a power of two leaves.
+///
+/// This is the SHA-256 hash of a one byte prefix that no leaf from
+/// [hash_leaf] starts with, so a proof for a padded index cannot be made for
+/// any leaf from [hash_leaf].
+pub fn pad_leaf() -> Digest {
+    let hash = Hasher::new().chain_update([PAD_PREFIX]).finalize();
+    Digest::try_from(hash.as_slice()).unwrap()
+}
+
+/// Computes the digest of an internal node from the digests of its children.
+pub fn hash_node(left: &Digest, right: &Digest) -> Digest {
+    *Impl::hash_pair(left, right)
+}
+
+/// A Merkle tree, holding all of its nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleTree {
+    // Levels of the tree, from the padded leaves up to the root.
+    levels: Vec<Vec<Digest>>,
+    // Number of leaves, not counting the padding.
+    len: usize,
+}
+
+impl MerkleTree {
+    /// Builds a tree over the given leaves.
+    ///
+    /// The root of a tree with no leaves is [Digest::ZERO].
+    pub fn new(mut leaves: Vec<Digest>) -> Self {
+        let len = leaves.len();
+        leaves.resize(len.next_power_of_two(), pad_leaf());
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks_exact(2)
+                .map(|pair| hash_node(&pair[0], &pair[1]))
+                .collect();
+            levels.push(level);
+        }
+        Self { levels, len }
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> Digest {
+        match self.len {
+            0 => Digest::ZERO,
+            _ => self.levels.last().unwrap()[0],
+        }
+    }
+
+    /// Returns the number of leaves in the tree, not counting the padding.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the leaves of the tree, not counting the padding.
+    pub fn leaves(&self) -> &[Digest] {
+        &self.levels[0][..self.len]
+    }
+
+    /// Returns the number of levels of internal nodes, which is the length
+    /// of every proof.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns a proof that the leaf at `index` is in the tree.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn prove(&self, index: usize) -> MerkleProof {
+        assert!(
+            index < self.len,
+            "leaf index {index} out of bounds for a tree with {} leaves",
+            self.len
+        );
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect();
+        MerkleProof {
+            index: index as u32,
+            siblings,
+        }
+    }
+}
+
+/// A proof that a leaf is at a given position in a [MerkleTree].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Index of the leaf in the tree.
+    pub index: u32,
+
+    /// Digests of the siblings of the nodes on the path from the leaf to the
+    /// root, starting with the sibling of the leaf.
+    pub siblings: Vec<Digest>,
+}
+
+impl MerkleProof {
+    /// Computes the root of the tree implied by this proof and the given leaf.
+    pub fn root(&self, leaf: &Digest) -> Digest {
+        let mut index = self.index;
+        let mut node = *leaf;
+        for sibling in self.siblings.iter() {
+            node = if index & 1 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+            index >>= 1;
+        }
+        node
+    }
+
+    /// Returns true if this proof shows that `leaf` is in the tree with the
+    /// given root, at [Self::index].
+    pub fn verify(&self, root: &Digest, leaf: &Digest) -> bool {
+        // Reject indices that do not fit in a tree of this depth, which would
+        // otherwise alias smaller indices.
+        let in_range = self.siblings.len() >= 32 || self.index >> self.siblings.len() == 0;
+        in_range && self.root(leaf) == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest as _, Sha256};
+
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<Digest> {
+        (0..count as u32)
+            .map(|i| hash_leaf(&i.to_le_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn leaf_hash() {
+        let expected = Sha256::new()
+            .chain_update([0])
+            .chain_update(b"leaf")
+            .finalize();
+        assert_eq!(hash_leaf(b"leaf").as_bytes(), expected.as_slice());
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        for count in [1, 2, 3, 4, 5, 8, 13] {
+            let tree = MerkleTree::new(leaves(count));
+            assert_eq!(tree.len(), count);
+            assert_eq!(
+                tree.depth(),
+                count.next_power_of_two().trailing_zeros() as usize
+            );
+            for (index, leaf) in tree.leaves().iter().enumerate() {
+                let proof = tree.prove(index);
+                assert_eq!(proof.siblings.len(), tree.depth());
+                assert!(proof.verify(&tree.root(), leaf));
+                assert!(!proof.verify(&tree.root(), &hash_leaf(b"other")));
+            }
+        }
+    }
+
+    #[test]
+    fn root() {
+        let [a, b, c] = leaves(3).try_into().unwrap();
+        let tree = MerkleTree::new(vec![a, b, c]);
+        assert_eq!(
+            tree.root(),
+            hash_node(&hash_node(&a, &b), &hash_node(&c, &pad_leaf()))
+        );
+        assert_eq!(MerkleTree::new(vec![a]).root(), a);
+        assert_eq!(MerkleTree::new(vec![]).root(), Digest::ZERO);
+    }
+
+    #[test]
+    fn pad_leaf_hash() {
+        let expected = Sha256::new().chain_update([1]).finalize();
+        assert_eq!(pad_leaf().as_bytes(), expected.as_slice());
+    }
+
+    #[test]
+    fn reject_padding() {
+        // Index 3 of a tree with 3 leaves is padding. Its sibling path is the
+        // one a forged proof for that index would use.
+        let tree = MerkleTree::new(leaves(3));
+        let proof = MerkleProof {
+            index: 3,
+            siblings: vec![tree.leaves()[2], hash_node(&tree.leaves()[0], &tree.leaves()[1])],
+        };
+        assert!(!proof.verify(&tree.root(), &Digest::ZERO));
+        assert!(!proof.verify(&tree.root(), &hash_leaf(b"")));
+        // The padding itself still matches, but hash_leaf never returns it.
+        assert!(proof.verify(&tree.root(), &pad_leaf()));
+    }
+
+    #[test]
+    fn reject_wrong_index() {
+        let tree = MerkleTree::new(leaves(4));
+        let mut proof = tree.prove(2);
+        proof.index = 3;
+        assert!(!proof.verify(&tree.root(), &tree.leaves()[2]));
+        proof.index = 2 + 4;
+        assert!(!proof.verify(&tree.root(), &tree.leaves()[2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn prove_out_of_bounds() {
+        MerkleTree::new(leaves(3)).prove(3);
+    }
+}
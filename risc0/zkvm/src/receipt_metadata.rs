@@ -362,6 +362,14 @@ where
             MaybePruned::Pruned(ref digest) => Err(PrunedValueError(digest.clone())),
         }
     }
+
+    /// Unwrap the value as a mutable reference, or return an error.
+    pub fn as_value_mut(&mut self) -> Result<&mut T, PrunedValueError> {
+        match self {
+            MaybePruned::Value(ref mut value) => Ok(value),
+            MaybePruned::Pruned(ref digest) => Err(PrunedValueError(digest.clone())),
+        }
+    }
 }
 
 impl<T> From<T> for MaybePruned<T>
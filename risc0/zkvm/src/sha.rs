@@ -100,6 +100,14 @@ pub mod rust_crypto {
     //!     "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
     //! );
     //! ```
+    //!
+    //! [StreamingSha256] keeps its state inline and, in the guest, compresses
+    //! each block in place with the SHA-256 accelerator, so it can be used to
+    //! hash data as it arrives, e.g. with `std::io::copy` from
+    //! [`env::stdin()`](crate::guest::env::stdin), without buffering it all
+    //! first. Both hashers work with crates built on the [digest] traits, such
+    //! as `hmac`.
+
     // NOTE: When used on the host, these functions are strictly less efficient,
     // with the primary loss being passing blocks received through the
     // RustCrypto interface as [u8] into blocks compatible with the RISC0
@@ -108,11 +116,175 @@ pub mod rust_crypto {
     // crate meaning that copy was wasted. This is the result of prioritizing code
     // factoring and guest performance over host performance.
 
+    use core::fmt;
+
+    use digest::{
+        block_buffer::Eager,
+        const_oid::{AssociatedOid, ObjectIdentifier},
+        core_api::{
+            AlgorithmName, Block, BlockSizeUser, Buffer, BufferKindUser, CoreWrapper,
+            FixedOutputCore, OutputSizeUser, Reset, UpdateCore,
+        },
+        typenum::{U32, U64},
+        HashMarker,
+    };
     use risc0_zkp::core::hash::sha::rust_crypto;
     pub use rust_crypto::{Digest, Output};
 
+    use super::{Digest as ShaDigest, BLOCK_BYTES, SHA256_INIT};
+
+    /// Core block-level SHA-256 hasher used by [StreamingSha256].
+    #[derive(Clone)]
+    pub struct Sha256Core {
+        // Current internal state of the SHA-256 hashing operation.
+        state: ShaDigest,
+        // Number of blocks hashed so far.
+        block_len: u64,
+    }
+
+    impl Default for Sha256Core {
+        fn default() -> Self {
+            Self {
+                state: SHA256_INIT,
+                block_len: 0,
+            }
+        }
+    }
+
+    impl HashMarker for Sha256Core {}
+
+    impl BlockSizeUser for Sha256Core {
+        type BlockSize = U64;
+    }
+
+    impl BufferKindUser for Sha256Core {
+        type BufferKind = Eager;
+    }
+
+    impl OutputSizeUser for Sha256Core {
+        type OutputSize = U32;
+    }
+
+    impl UpdateCore for Sha256Core {
+        #[inline]
+        fn update_blocks(&mut self, blocks: &[Block<Self>]) {
+            self.block_len += blocks.len() as u64;
+
+            // SAFETY: Block<Self> is an array of 64 bytes, and so can be
+            // reinterpreted as a block of words if it is aligned.
+            match unsafe { blocks.align_to::<super::Block>() } {
+                (&[], aligned_blocks, &[]) => compress_in_place(&mut self.state, aligned_blocks),
+                _ => {
+                    // Copy unaligned blocks one at a time, to avoid allocating.
+                    for block in blocks {
+                        let block: super::Block = bytemuck::pod_read_unaligned(block.as_slice());
+                        compress_in_place(&mut self.state, core::slice::from_ref(&block));
+                    }
+                }
+            }
+        }
+    }
+
+    impl FixedOutputCore for Sha256Core {
+        #[inline]
+        fn finalize_fixed_core(&mut self, buffer: &mut Buffer<Self>, out: &mut Output<Self>) {
+            let bit_len = 8 * (buffer.get_pos() as u64 + BLOCK_BYTES as u64 * self.block_len);
+            buffer.len64_padding_be(bit_len, |block| {
+                let block: super::Block = bytemuck::pod_read_unaligned(block.as_slice());
+                compress_in_place(&mut self.state, core::slice::from_ref(&block));
+            });
+            out.copy_from_slice(self.state.as_bytes());
+        }
+    }
+
+    impl Reset for Sha256Core {
+        #[inline]
+        fn reset(&mut self) {
+            *self = Self::default();
+        }
+    }
+
+    impl AlgorithmName for Sha256Core {
+        #[inline]
+        fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Sha256")
+        }
+    }
+
+    impl fmt::Debug for Sha256Core {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("Sha256Core { ... }")
+        }
+    }
+
+    impl AssociatedOid for Sha256Core {
+        const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+    }
+
     /// Sha256 is a [Rust Crypto] wrapper on the RISC Zero SHA-256
     /// implementations. This type will automatically select the correct
     /// implementation for usage in the zkVM guest and on the host.
     pub type Sha256 = rust_crypto::Sha256<super::Impl>;
+
+    /// A [Rust Crypto] SHA-256 hasher which compresses each block in place, for
+    /// hashing data as it arrives.
+    ///
+    /// [Rust Crypto]: https://github.com/RustCrypto
+    pub type StreamingSha256 = CoreWrapper<Sha256Core>;
+
+    /// Runs the SHA-256 compression function over the given blocks, updating
+    /// `state`.
+    fn compress_in_place(state: &mut ShaDigest, blocks: &[super::Block]) {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "zkvm")] {
+                let state: *mut ShaDigest = state;
+                crate::guest::sha::compress_slice(state, state, blocks);
+            } else {
+                use super::Sha256 as _;
+                *state = *super::Impl::compress_slice(state, blocks);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hmac::{Hmac, Mac};
+
+    use super::rust_crypto::{Digest as _, Sha256, StreamingSha256};
+
+    #[test]
+    fn streaming_matches_sha2() {
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        for chunk_size in [1, 7, 63, 64, 65, 128, 1000] {
+            let mut hasher = StreamingSha256::new();
+            for chunk in data.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+            assert_eq!(
+                hasher.finalize().as_slice(),
+                sha2::Sha256::digest(&data).as_slice(),
+                "chunk size {chunk_size}"
+            );
+        }
+
+        // Unaligned input.
+        assert_eq!(
+            StreamingSha256::digest(&data[1..]).as_slice(),
+            sha2::Sha256::digest(&data[1..]).as_slice()
+        );
+    }
+
+    #[test]
+    fn hmac_matches_sha2() {
+        fn hmac<D: Mac + hmac::digest::KeyInit>() -> Vec<u8> {
+            let mut mac = <D as hmac::digest::KeyInit>::new_from_slice(b"key").unwrap();
+            mac.update(b"The quick brown fox jumps over the lazy dog");
+            mac.finalize().into_bytes().to_vec()
+        }
+
+        let expected = hmac::<Hmac<sha2::Sha256>>();
+        assert_eq!(hmac::<Hmac<Sha256>>(), expected);
+        assert_eq!(hmac::<Hmac<StreamingSha256>>(), expected);
+    }
 }
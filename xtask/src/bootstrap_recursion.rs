@@ -0,0 +1,130 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use clap::Parser;
+use hex::FromHex;
+use risc0_circuit_recursion::zkr::{get_all_zkrs, get_control_id};
+use risc0_zkp::{
+    core::{
+        digest::Digest,
+        hash::{poseidon::PoseidonHashSuite, HashFn},
+    },
+    field::baby_bear::BabyBear,
+};
+use risc0_zkvm::POSEIDON_CONTROL_ID;
+use sha2::{Digest as _, Sha256};
+
+const CONTROL_ID_PATH: &str = "risc0/circuit/recursion/src/control_id.rs";
+const BUNDLE_PATH: &str = "risc0/circuit/recursion/src/recursion_zkr.zip";
+
+// Must match the depth of the allowed tree in risc0-zkvm.
+const ALLOWED_CODE_MERKLE_DEPTH: usize = 8;
+
+const LICENSE_HEADER: &str = r#"// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+"#;
+
+// The recursion programs that must be part of the bundle.
+const REQUIRED_PROGRAMS: &[&str] = &["identity.zkr", "join.zkr", "resolve.zkr"];
+
+/// Regenerate the control IDs of the recursion programs in the zkr bundle, and
+/// the root of the tree of allowed control IDs.
+///
+/// Also prints the SHA-256 of the bundle, which must be pinned in the build
+/// script of risc0-circuit-recursion along with the commit that ships it.
+#[derive(Parser)]
+pub struct BootstrapRecursion;
+
+impl BootstrapRecursion {
+    pub fn run(&self) {
+        let names: Vec<String> = get_all_zkrs()
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        for name in REQUIRED_PROGRAMS {
+            assert!(
+                names.iter().any(|x| x == name),
+                "{name} is missing from the recursion bundle"
+            );
+        }
+        let ids: Vec<Digest> = names
+            .iter()
+            .map(|name| get_control_id(name).unwrap())
+            .collect();
+
+        // The leaves of the allowed tree are the rv32im control IDs followed by
+        // those of the recursion programs.
+        let mut leaves: Vec<Digest> = POSEIDON_CONTROL_ID
+            .iter()
+            .map(|id| Digest::from_hex(id).unwrap())
+            .collect();
+        leaves.extend_from_slice(&ids);
+        assert!(leaves.len() <= 1 << ALLOWED_CODE_MERKLE_DEPTH);
+        let hashfn = PoseidonHashSuite::new_suite().hashfn;
+        let root = merkle_root(&leaves, 0, 1 << ALLOWED_CODE_MERKLE_DEPTH, hashfn.as_ref());
+
+        let mut contents = String::from(LICENSE_HEADER);
+        writeln!(
+            contents,
+            "\npub const RECURSION_CONTROL_IDS: [&str; {}] = [",
+            ids.len()
+        )
+        .unwrap();
+        for (name, id) in names.iter().zip(ids.iter()) {
+            writeln!(contents, "    \"{}\", // {name}", hex::encode(id)).unwrap();
+        }
+        writeln!(contents, "];\n").unwrap();
+        writeln!(contents, "/// Merkle root of the RECURSION_CONTROL_IDS").unwrap();
+        writeln!(contents, "pub const ALLOWED_IDS_ROOT: &str =").unwrap();
+        writeln!(contents, "    \"{}\";", hex::encode(root)).unwrap();
+
+        println!("{contents}");
+        std::fs::write(CONTROL_ID_PATH, contents).unwrap();
+
+        let bundle = std::fs::read(BUNDLE_PATH).unwrap();
+        println!(
+            "{BUNDLE_PATH} sha256: {}",
+            hex::encode(Sha256::digest(bundle))
+        );
+    }
+}
+
+fn merkle_root(
+    leaves: &[Digest],
+    start: usize,
+    end: usize,
+    hashfn: &dyn HashFn<BabyBear>,
+) -> Digest {
+    if start + 1 == end {
+        return leaves.get(start).copied().unwrap_or_default();
+    }
+    let mid = (start + end) / 2;
+    let left = merkle_root(leaves, start, mid, hashfn);
+    let right = merkle_root(leaves, mid, end, hashfn);
+    *hashfn.hash_pair(&left, &right)
+}
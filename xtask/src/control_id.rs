@@ -46,6 +46,21 @@ pub const POSEIDON_CONTROL_ID: RawControlId = [
     "{}", //
 ];
 
+/// Control ID for Poseidon2
+pub const POSEIDON2_CONTROL_ID: RawControlId = [
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+    "{}", //
+];
+
 /// Control ID for Blake2b
 pub const BLAKE2B_CONTROL_ID: RawControlId = [
     "{}", //
@@ -15,6 +15,7 @@
 mod bootstrap;
 mod bootstrap_fault;
 mod bootstrap_poseidon;
+mod bootstrap_recursion;
 mod gen_receipt;
 mod install;
 
@@ -22,7 +23,7 @@ use clap::{Parser, Subcommand};
 
 use self::{
     bootstrap::Bootstrap, bootstrap_fault::BootstrapFault, bootstrap_poseidon::BootstrapPoseidon,
-    gen_receipt::GenReceipt, install::Install,
+    bootstrap_recursion::BootstrapRecursion, gen_receipt::GenReceipt, install::Install,
 };
 
 #[derive(Parser)]
@@ -36,6 +37,7 @@ enum Commands {
     Bootstrap(Bootstrap),
     BootstrapFault(BootstrapFault),
     BootstrapPoseidon(BootstrapPoseidon),
+    BootstrapRecursion(BootstrapRecursion),
     GenReceipt(GenReceipt),
     Install(Install),
 }
@@ -46,6 +48,7 @@ impl Commands {
             Commands::Bootstrap(cmd) => cmd.run(),
             Commands::BootstrapFault(cmd) => cmd.run(),
             Commands::BootstrapPoseidon(cmd) => cmd.run(),
+            Commands::BootstrapRecursion(cmd) => cmd.run(),
             Commands::Install(cmd) => cmd.run(),
             Commands::GenReceipt(cmd) => cmd.run(),
         }